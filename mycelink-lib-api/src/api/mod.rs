@@ -14,9 +14,13 @@ use crate::mycelink::mycelink_service::MycelinkService;
 use futures::future::join_all;
 use futures::{Stream, StreamExt};
 use mycelink_lib_fcp::fcp_connector::FCPConnector;
+use mycelink_lib_fcp::model::message_type_identifier::ClientMessageType;
 use std::error::Error;
 use std::sync::Arc;
-use tokio::net::TcpStream;
+
+/// Requests running on the node at once, polling many chats queues the rest client side
+const MAX_CONCURRENT_GETS: usize = 16;
+const MAX_CONCURRENT_PUTS: usize = 4;
 
 pub struct APIConnector<T: TenantState> {
     db_connector: DBConnector<T>,
@@ -79,7 +83,11 @@ impl APIConnector<NoTenant> {
 
     pub async fn init(config: &Config) -> Result<APIConnector<NoTenant>, Box<dyn Error>> {
         let fcp_connector =
-            FCPConnector::new(TcpStream::connect(config.fcp_endpoint).await?, "Mycelink").await?;
+            FCPConnector::new_reconnecting("Mycelink", config.fcp_transport.reconnect_policy())
+                .await?;
+        let scheduler = fcp_connector.scheduler();
+        scheduler.set_limit(ClientMessageType::ClientGet, MAX_CONCURRENT_GETS);
+        scheduler.set_limit(ClientMessageType::ClientPut, MAX_CONCURRENT_PUTS);
         let db_connector =
             DBConnector::new(config.database_path.as_os_str().to_str().unwrap()).await?;
 
@@ -139,9 +147,10 @@ impl APIConnector<Tenant> {
     }
 
     pub async fn get_mycelink_account_request_key(&self) -> sqlx::Result<Option<Box<str>>> {
-        self.db_connector
-            .get_mycelink_account()
-            .await
-            .map(|e| e.map(|acc| acc.request_ssk_key().into()))
+        let mut tx = self.db_connector.begin().await?;
+        let account = self.db_connector.get_mycelink_account(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(account.map(|acc| acc.request_ssk_key().into()))
     }
 }
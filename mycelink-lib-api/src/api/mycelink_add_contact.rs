@@ -6,6 +6,7 @@ use crate::db::actions::contact_actions::ContactId;
 use crate::db::actions::tenant_actions::Tenant;
 use crate::fcp_tools::fcp_get::{fcp_get_inline, FcpGetError};
 use crate::fcp_tools::fcp_put::{fcp_put_inline, FcpPutError};
+use crate::fcp_tools::MESSAGING;
 use crate::model::connection_details::{PublicConnectionDetails, PublicMycelinkConnectionDetails};
 use crate::model::contact::ContactDisplay;
 use crate::model::protocol_config::Protocol;
@@ -27,8 +28,10 @@ impl APIConnector<Tenant> {
         let details = fcp_get_inline(
             account_request_key.deref().try_into()?,
             self.fcp_connector.deref(),
+            MESSAGING,
             "add_contact",
             PriorityClass::High,
+            None,
         )
         .await?;
         let public_details: PublicMycelinkConnectionDetails =
@@ -13,7 +13,7 @@ impl DBConnector<Tenant> {
         tx: &mut Transaction<'_, DatabaseBackend>,
         account: &MycelinkAccount,
     ) -> Result<(), MycelinkAccountEntryError> {
-        if self.get_mycelink_account().await?.is_some() {
+        if self.get_mycelink_account(tx).await?.is_some() {
             return Err(MycelinkAccountEntryError::AccountAlreadyExists);
         }
 
@@ -30,10 +30,13 @@ impl DBConnector<Tenant> {
         Ok(())
     }
 
-    pub async fn get_mycelink_account(&self) -> sqlx::Result<Option<MycelinkAccount>> {
+    pub async fn get_mycelink_account(
+        &self,
+        tx: &mut Transaction<'_, DatabaseBackend>,
+    ) -> sqlx::Result<Option<MycelinkAccount>> {
         let query = sqlx::query("SELECT (config) FROM protocol_config_per_tenant WHERE protocol = 'Mycelink' AND tenant = ?")
             .bind(self.tenant());
-        let res = query.fetch_optional(self.pool().await).await?;
+        let res = query.fetch_optional(&mut **tx).await?;
 
         if let Some(row) = res {
             let account: Json<MycelinkAccount> = row.try_get("config")?;
@@ -1,33 +1,81 @@
+use crate::fcp_tools::progress::{verbosity, with_progress};
+use mycelink_lib_fcp::codec::MAX_PAYLOAD_PREALLOCATION;
 use mycelink_lib_fcp::decode_error::DecodeError;
-use mycelink_lib_fcp::fcp_connector::filters::{identity_filter, type_filter};
-use mycelink_lib_fcp::fcp_connector::{FCPConnector, Listener};
-use mycelink_lib_fcp::messages::all_data::AllDataMessage;
-use mycelink_lib_fcp::messages::client_get::ClientGetMessage;
+use mycelink_lib_fcp::fcp_connector::progress::ProgressEvent;
+use mycelink_lib_fcp::fcp_connector::request::RequestError;
+use mycelink_lib_fcp::fcp_connector::FCPConnector;
+use mycelink_lib_fcp::messages::all_data::{AllDataMessage, AllDataStream};
+use mycelink_lib_fcp::messages::client_get::{ClientGetMessage, StreamedClientGetMessage};
 use mycelink_lib_fcp::messages::get_failed::GetFailedMessage;
-use mycelink_lib_fcp::model::message_type_identifier::NodeMessageType::{AllData, GetFailed};
+use mycelink_lib_fcp::messages::protocol_error::ProtocolErrorMessage;
 use mycelink_lib_fcp::model::persistence::Persistence;
 use mycelink_lib_fcp::model::priority_class::PriorityClass;
 use mycelink_lib_fcp::model::return_type::ReturnType;
 use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
 use mycelink_lib_fcp::model::uri::URI;
+use mycelink_lib_fcp::model::verbosity::Verbosity;
 use std::fmt::{Display, Formatter};
 use std::io::Error;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc::UnboundedSender;
 
 const CLIENT_GET_MAX_INLINE_SIZE: usize = 1024 * 1024 * 256; // 256 MiB
 
 pub async fn fcp_get_inline(
     uri: URI,
     fcp_connector: &FCPConnector,
+    caller: &str,
     purpose: &str,
     priority: PriorityClass,
+    timeout: Option<Duration>,
 ) -> Result<AllDataMessage, FcpGetError> {
-    let identifier = UniqueIdentifier::new(purpose);
-    let client_get = ClientGetMessage {
-        identifier: identifier.clone(),
+    let client_get = client_get(uri, purpose, priority, Some(CLIENT_GET_MAX_INLINE_SIZE))?;
+    let mut stream = fcp_get_stream(client_get, fcp_connector, caller, timeout, None).await?;
+
+    let mut data = Vec::with_capacity(stream.data.data_length().min(MAX_PAYLOAD_PREALLOCATION));
+    stream.data.read_to_end(&mut data).await?;
+
+    Ok(AllDataMessage {
+        identifier: stream.identifier,
+        content_type: stream.content_type,
+        data: data.into(),
+    })
+}
+
+/// Gets the data without holding all of it in memory, `timeout` only covers the wait until the
+/// data starts to arrive
+///
+/// The get shares the slots of the scheduler with the requests of other callers, see
+/// [FCPConnector::request_as]. The progress of the fetch is reported to `progress` if given.
+pub async fn fcp_get_stream(
+    mut client_get: ClientGetMessage,
+    fcp_connector: &FCPConnector,
+    caller: &str,
+    timeout: Option<Duration>,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+) -> Result<AllDataStream, FcpGetError> {
+    client_get.verbosity = verbosity(progress.as_ref());
+    let (response, intermediate) = fcp_connector
+        .request_as(caller, &StreamedClientGetMessage(client_get), timeout)
+        .await?;
+
+    Ok(with_progress(response, intermediate, progress).await?)
+}
+
+/// A get of `uri` returning the data directly, its identifier starts with `purpose`
+pub fn client_get(
+    uri: URI,
+    purpose: &str,
+    priority: PriorityClass,
+    max_size: Option<usize>,
+) -> Result<ClientGetMessage, tokio::io::Error> {
+    Ok(ClientGetMessage {
+        identifier: UniqueIdentifier::new(purpose)?,
         uri,
-        verbosity: Default::default(),
+        verbosity: Verbosity::default(),
         return_type: ReturnType::Direct,
-        max_size: Some(CLIENT_GET_MAX_INLINE_SIZE),
+        max_size,
         max_temp_size: None,
         max_retries: 0,
         priority,
@@ -35,48 +83,34 @@ pub async fn fcp_get_inline(
         ignore_data_store: false,
         data_store_only: false,
         real_time: true,
-    };
-
-    let (success_listener, mut success_rx) = Listener::new(
-        vec![identity_filter(identifier.clone()), type_filter(AllData)],
-        0,
-    );
-
-    let (failure_listener, mut failure_rx) =
-        Listener::new(vec![identity_filter(identifier), type_filter(GetFailed)], 0);
-
-    fcp_connector.add_listener(success_listener).await;
-    fcp_connector.add_listener(failure_listener).await;
-
-    fcp_connector.send(&client_get).await?;
-
-    tokio::select! {
-        success = success_rx.recv() => {
-            Ok(success.unwrap().try_into()?)
-        },
-        failure = failure_rx.recv() => {
-            Err(FcpGetError::GetFailed {inner: failure.unwrap().try_into()?})
-        }
-    }
+    })
 }
 
 #[derive(Debug)]
 pub enum FcpGetError {
-    GetFailed { inner: GetFailedMessage },
+    GetFailed { inner: Box<GetFailedMessage> },
+    ProtocolError { inner: ProtocolErrorMessage },
     TokioIo { inner: tokio::io::Error },
     DecodeError { inner: DecodeError },
+    ConnectionClosed,
+    TimedOut,
 }
 
 impl Display for FcpGetError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             FcpGetError::GetFailed { inner } => write!(f, "GetFailed: {inner:?}"),
+            FcpGetError::ProtocolError { inner } => write!(f, "ProtocolError: {inner:?}"),
             FcpGetError::TokioIo { inner } => {
                 write!(f, "TokioIoError: {inner}")
             }
             FcpGetError::DecodeError { inner } => {
                 write!(f, "DecodeError: {inner}")
             }
+            FcpGetError::ConnectionClosed => {
+                write!(f, "Connection closed before the get finished")
+            }
+            FcpGetError::TimedOut => write!(f, "Get timed out"),
         }
     }
 }
@@ -94,3 +128,17 @@ impl From<DecodeError> for FcpGetError {
         Self::DecodeError { inner: value }
     }
 }
+
+impl From<RequestError<GetFailedMessage>> for FcpGetError {
+    fn from(value: RequestError<GetFailedMessage>) -> Self {
+        match value {
+            RequestError::Failed(inner) => Self::GetFailed {
+                inner: Box::new(inner),
+            },
+            RequestError::ProtocolError(inner) => Self::ProtocolError { inner },
+            RequestError::DecodeError(inner) => Self::DecodeError { inner },
+            RequestError::ConnectionClosed => Self::ConnectionClosed,
+            RequestError::TimedOut => Self::TimedOut,
+        }
+    }
+}
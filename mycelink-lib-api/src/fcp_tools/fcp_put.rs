@@ -1,10 +1,12 @@
+use crate::fcp_tools::progress::{verbosity, with_progress};
 use mycelink_lib_fcp::decode_error::DecodeError;
-use mycelink_lib_fcp::fcp_connector::filters::{identity_filter, type_filter};
-use mycelink_lib_fcp::fcp_connector::{FCPConnector, Listener};
+use mycelink_lib_fcp::fcp_connector::progress::ProgressEvent;
+use mycelink_lib_fcp::fcp_connector::request::RequestError;
+use mycelink_lib_fcp::fcp_connector::FCPConnector;
 use mycelink_lib_fcp::messages::client_put::ClientPutMessage;
+use mycelink_lib_fcp::messages::protocol_error::ProtocolErrorMessage;
 use mycelink_lib_fcp::messages::put_failed::PutFailedMessage;
 use mycelink_lib_fcp::messages::put_successful::PutSuccessfulMessage;
-use mycelink_lib_fcp::model::message_type_identifier::NodeMessageType::{PutFailed, PutSuccessful};
 use mycelink_lib_fcp::model::persistence::Persistence;
 use mycelink_lib_fcp::model::priority_class::PriorityClass;
 use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
@@ -13,19 +15,58 @@ use mycelink_lib_fcp::model::uri::URI;
 use mycelink_lib_fcp::model::verbosity::Verbosity;
 use std::fmt::{Display, Formatter};
 use std::io::Error;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc::UnboundedSender;
 
 pub async fn fcp_put_inline(
     data: Box<[u8]>,
     uri: URI,
     fcp_connector: &FCPConnector,
+    caller: &str,
     intent: &str,
+    timeout: Option<Duration>,
 ) -> Result<PutSuccessfulMessage, FcpPutError> {
-    let identifier = UniqueIdentifier::new(intent);
+    fcp_put_stream(
+        data.as_ref(),
+        data.len() as u64,
+        client_put(uri, intent)?,
+        fcp_connector,
+        caller,
+        timeout,
+        None,
+    )
+    .await
+}
 
-    let put_message = ClientPutMessage {
+/// Inserts `len` bytes read from `data` without holding all of them in memory, `timeout` covers
+/// the whole insert
+///
+/// The insert shares the slots of the scheduler with the requests of other callers, see
+/// [FCPConnector::request_as]. The progress of the insert is reported to `progress` if given.
+pub async fn fcp_put_stream(
+    data: impl AsyncRead + Unpin,
+    len: u64,
+    mut put_message: ClientPutMessage,
+    fcp_connector: &FCPConnector,
+    caller: &str,
+    timeout: Option<Duration>,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+) -> Result<PutSuccessfulMessage, FcpPutError> {
+    put_message.verbosity = verbosity(progress.as_ref());
+    let (response, intermediate) = fcp_connector
+        .request_with_payload_as(caller, &put_message, data, len, timeout)
+        .await?;
+
+    Ok(with_progress(response, intermediate, progress).await?)
+}
+
+/// An insert to `uri` of data sent along with the message, its identifier starts with `intent`
+pub fn client_put(uri: URI, intent: &str) -> Result<ClientPutMessage, tokio::io::Error> {
+    Ok(ClientPutMessage {
         uri,
         content_type: None,
-        identifier: identifier.clone(),
+        identifier: UniqueIdentifier::new(intent)?,
         verbosity: Verbosity::default(),
         max_retries: 1,
         priority: PriorityClass::High,
@@ -33,54 +74,39 @@ pub async fn fcp_put_inline(
         dont_compress: false,
         persistence: Persistence::Connection,
         target_filename: None,
-        upload_from: UploadType::Direct { data },
+        upload_from: UploadType::Stream,
         is_binary_blob: false,
         real_time: false,
-    };
-
-    let (listener_success, mut success_rx) = Listener::new(
-        vec![
-            identity_filter(identifier.clone()),
-            type_filter(PutSuccessful),
-        ],
-        10,
-    );
-
-    let (listener_failed, mut failure_rx) =
-        Listener::new(vec![identity_filter(identifier), type_filter(PutFailed)], 0);
-
-    fcp_connector.add_listener(listener_failed).await;
-    fcp_connector.add_listener(listener_success).await;
-
-    fcp_connector.send(&put_message).await?;
-
-    tokio::select! {
-        success = success_rx.recv() => {
-            Ok(success.unwrap().try_into()?)
-        },
-        failure = failure_rx.recv() => {
-            Err(FcpPutError::PutFailed{inner: failure.unwrap().try_into()?})?
-        }
-    }
+        compatibility_mode: None,
+        override_splitfile_crypto_key: None,
+    })
 }
 
 #[derive(Debug)]
 pub enum FcpPutError {
     PutFailed { inner: PutFailedMessage },
+    ProtocolError { inner: ProtocolErrorMessage },
     TokioIo { inner: tokio::io::Error },
     DecodeError { inner: DecodeError },
+    ConnectionClosed,
+    TimedOut,
 }
 
 impl Display for FcpPutError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             FcpPutError::PutFailed { inner } => write!(f, "PutFailed: {inner:?}"),
+            FcpPutError::ProtocolError { inner } => write!(f, "ProtocolError: {inner:?}"),
             FcpPutError::TokioIo { inner } => {
                 write!(f, "TokioIoError: {inner}")
             }
             FcpPutError::DecodeError { inner } => {
                 write!(f, "DecodeError: {inner}")
             }
+            FcpPutError::ConnectionClosed => {
+                write!(f, "Connection closed before the put finished")
+            }
+            FcpPutError::TimedOut => write!(f, "Put timed out"),
         }
     }
 }
@@ -98,3 +124,15 @@ impl From<DecodeError> for FcpPutError {
         Self::DecodeError { inner: value }
     }
 }
+
+impl From<RequestError<PutFailedMessage>> for FcpPutError {
+    fn from(value: RequestError<PutFailedMessage>) -> Self {
+        match value {
+            RequestError::Failed(inner) => Self::PutFailed { inner },
+            RequestError::ProtocolError(inner) => Self::ProtocolError { inner },
+            RequestError::DecodeError(inner) => Self::DecodeError { inner },
+            RequestError::ConnectionClosed => Self::ConnectionClosed,
+            RequestError::TimedOut => Self::TimedOut,
+        }
+    }
+}
@@ -1,55 +1,58 @@
 use mycelink_lib_fcp::decode_error::DecodeError;
-use mycelink_lib_fcp::fcp_connector::{filters, FCPConnector, Listener};
+use mycelink_lib_fcp::fcp_connector::request::{NoFailure, RequestError};
+use mycelink_lib_fcp::fcp_connector::FCPConnector;
 use mycelink_lib_fcp::messages::generate_ssk::GenerateSSKMessage;
+use mycelink_lib_fcp::messages::protocol_error::ProtocolErrorMessage;
 use mycelink_lib_fcp::messages::ssk_keypair::SSKKeypairMessage;
-use mycelink_lib_fcp::model::message_type_identifier::NodeMessageType;
 use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 pub async fn generate_ssk(
     fcp_connector: &FCPConnector,
+    timeout: Option<Duration>,
 ) -> Result<SSKKeypairMessage, GenerateSSKKeypairError> {
-    let identifier = UniqueIdentifier::new("generate_ssk");
-
-    let (listener, mut receiver) = Listener::new(
-        vec![
-            filters::identity_filter(identifier.clone()),
-            filters::type_filter(NodeMessageType::SSKKeypair),
-        ],
-        Listener::DEFAULT_PRIORITY,
-    );
-
-    fcp_connector.add_listener(listener).await;
+    let identifier = UniqueIdentifier::new("generate_ssk").map_err(tokio::io::Error::from)?;
 
     let generate_message = GenerateSSKMessage { identifier };
-    fcp_connector.send(generate_message).await?;
+    let (response, _) = fcp_connector.request(&generate_message, timeout).await?;
 
-    match receiver.recv().await {
-        None => Err(GenerateSSKKeypairError::Internal()),
-        Some(message) => Ok(message.try_into()?),
-    }
+    Ok(response.await?)
 }
 
 #[derive(Debug)]
 pub enum GenerateSSKKeypairError {
-    Internal(),
-    Tokio { inner: tokio::io::Error },
-    FCP { inner: DecodeError },
+    /// The connection stopped before the keypair was generated
+    ConnectionClosed,
+    TimedOut,
+    Tokio {
+        inner: tokio::io::Error,
+    },
+    FCP {
+        inner: DecodeError,
+    },
+    Protocol {
+        inner: ProtocolErrorMessage,
+    },
 }
 
 impl Display for GenerateSSKKeypairError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            GenerateSSKKeypairError::Internal() => {
-                write!(f, "Internal error")
+            GenerateSSKKeypairError::ConnectionClosed => {
+                write!(f, "Connection closed before the keypair was generated")
             }
+            GenerateSSKKeypairError::TimedOut => write!(f, "Generating the keypair timed out"),
             GenerateSSKKeypairError::Tokio { inner } => {
                 write!(f, "Tokio IO error ({inner})")
             }
             GenerateSSKKeypairError::FCP { inner } => {
                 write!(f, "FCP Decode error ({inner})")
             }
+            GenerateSSKKeypairError::Protocol { inner } => {
+                write!(f, "FCP Protocol error ({inner:?})")
+            }
         }
     }
 }
@@ -62,6 +65,18 @@ impl From<tokio::io::Error> for GenerateSSKKeypairError {
     }
 }
 
+impl From<RequestError<NoFailure>> for GenerateSSKKeypairError {
+    fn from(value: RequestError<NoFailure>) -> Self {
+        match value {
+            RequestError::Failed(inner) => match inner {},
+            RequestError::DecodeError(inner) => GenerateSSKKeypairError::FCP { inner },
+            RequestError::ProtocolError(inner) => GenerateSSKKeypairError::Protocol { inner },
+            RequestError::ConnectionClosed => GenerateSSKKeypairError::ConnectionClosed,
+            RequestError::TimedOut => GenerateSSKKeypairError::TimedOut,
+        }
+    }
+}
+
 impl From<DecodeError> for GenerateSSKKeypairError {
     fn from(value: DecodeError) -> Self {
         GenerateSSKKeypairError::FCP { inner: value }
@@ -70,16 +85,36 @@ impl From<DecodeError> for GenerateSSKKeypairError {
 
 #[cfg(test)]
 mod tests {
-    use crate::fcp_tools::generate_ssk::generate_ssk;
-    use crate::test::create_test_fcp_connector;
+    use crate::fcp_tools::generate_ssk::{generate_ssk, GenerateSSKKeypairError};
+    use crate::test::{create_test_fcp_connector, create_test_mock_node};
+    use mycelink_lib_fcp::mock_node::fault::{MockBehaviour, MockFault};
+    use mycelink_lib_fcp::model::message_type_identifier::ClientMessageType;
     use std::sync::Arc;
+    use std::time::Duration;
 
     #[tokio::test]
     pub async fn test_generate_ssk() {
         let connector =
             Arc::new(create_test_fcp_connector("generate_ssk::test_generate_ssk").await);
 
-        let keypair = generate_ssk(&connector).await.unwrap();
+        let keypair = generate_ssk(&connector, None).await.unwrap();
         assert_ne!(keypair.request_uri, keypair.insert_uri);
     }
+
+    #[tokio::test]
+    pub async fn test_generate_ssk_timeout() {
+        let (node, connector) = create_test_mock_node("generate_ssk::test_timeout").await;
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::GenerateSSK,
+            uri: None,
+            behaviour: MockBehaviour::Drop,
+            times: None,
+        })
+        .await;
+
+        assert!(matches!(
+            generate_ssk(&connector, Some(Duration::from_millis(50))).await,
+            Err(GenerateSSKKeypairError::TimedOut)
+        ));
+    }
 }
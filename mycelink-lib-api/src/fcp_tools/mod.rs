@@ -1,3 +1,11 @@
 pub mod fcp_get;
 pub mod fcp_put;
 pub mod generate_ssk;
+pub mod progress;
+
+/// Caller of the requests polling chats for new messages, see [FCPConnector::request_as]
+///
+/// [FCPConnector::request_as]: mycelink_lib_fcp::fcp_connector::FCPConnector::request_as
+pub const CHAT_POLLING: &str = "chat polling";
+/// Caller of the requests sending messages and publishing account details
+pub const MESSAGING: &str = "messaging";
@@ -0,0 +1,94 @@
+use mycelink_lib_fcp::fcp_connector::progress::{Progress, ProgressEvent};
+use mycelink_lib_fcp::model::message::Message;
+use mycelink_lib_fcp::model::verbosity::Verbosity;
+use std::future::Future;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// Progress events are only requested from the node if someone listens to them
+pub(crate) fn verbosity(progress: Option<&UnboundedSender<ProgressEvent>>) -> Verbosity {
+    match progress {
+        None => Verbosity::default(),
+        Some(_) => Verbosity::all(),
+    }
+}
+
+/// Awaits `response` while passing the progress events of the request on to `progress`
+pub(crate) async fn with_progress<F: Future>(
+    response: F,
+    intermediate: UnboundedReceiver<Message>,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+) -> F::Output {
+    let Some(progress_tx) = progress else {
+        return response.await;
+    };
+    let mut progress = Progress::new(intermediate);
+    tokio::pin!(response);
+
+    loop {
+        tokio::select! {
+            output = &mut response => {
+                // Events sent right before the terminal message may not be forwarded yet
+                while let Some(event) = progress.next().await {
+                    let _ = progress_tx.send(event);
+                }
+                return output;
+            }
+            Some(event) = progress.next() => {
+                let _ = progress_tx.send(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fcp_tools::fcp_get::{client_get, fcp_get_stream};
+    use crate::fcp_tools::fcp_put::{client_put, fcp_put_stream};
+    use crate::test::create_test_fcp_connector;
+    use mycelink_lib_fcp::fcp_connector::progress::ProgressEvent;
+    use mycelink_lib_fcp::model::priority_class::PriorityClass;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn test_put_get_progress() {
+        let connector = create_test_fcp_connector("progress::test_put_get_progress").await;
+        let data = [1; 2048];
+
+        let (put_tx, mut put_rx) = unbounded_channel();
+        let put = fcp_put_stream(
+            data.as_slice(),
+            data.len() as u64,
+            client_put("CHK@".try_into().unwrap(), "Progress test").unwrap(),
+            &connector,
+            "progress test",
+            None,
+            Some(put_tx),
+        )
+        .await
+        .unwrap();
+
+        let mut put_events = Vec::new();
+        while let Some(event) = put_rx.recv().await {
+            put_events.push(event);
+        }
+        assert!(put_events
+            .iter()
+            .any(|e| matches!(e, ProgressEvent::SimpleProgress(e) if e.total == 2)));
+
+        let (get_tx, mut get_rx) = unbounded_channel();
+        let _stream = fcp_get_stream(
+            client_get(put.uri, "Progress test", PriorityClass::High, None).unwrap(),
+            &connector,
+            "progress test",
+            None,
+            Some(get_tx),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            get_rx.recv().await,
+            Some(ProgressEvent::ExpectedMIME(_))
+        ));
+    }
+}
@@ -8,11 +8,16 @@ mod mycelink;
 #[cfg(test)]
 mod test {
     use mycelink_lib_fcp::fcp_connector::FCPConnector;
+    use mycelink_lib_fcp::mock_node::MockNode;
     use std::sync::Arc;
-    use tokio::net::TcpStream;
 
     pub async fn create_test_fcp_connector(test_name: &str) -> Arc<FCPConnector> {
-        let stream = TcpStream::connect("localhost:9481").await.unwrap();
+        create_test_mock_node(test_name).await.1
+    }
+
+    pub async fn create_test_mock_node(test_name: &str) -> (MockNode, Arc<FCPConnector>) {
+        let node = MockNode::start().await.unwrap();
+        let stream = node.connect().await.unwrap();
         let connector = FCPConnector::new(stream, format!("MycelinkTest {test_name}").as_str())
             .await
             .unwrap();
@@ -21,6 +26,6 @@ mod test {
 
         let _handle = tokio::spawn(async move { listen_connector.listen().await });
 
-        connector
+        (node, connector)
     }
 }
@@ -1,15 +1,41 @@
+use mycelink_lib_fcp::fcp_connector::reconnect::{ConnectFn, ReconnectPolicy};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub struct Config {
-    pub fcp_endpoint: SocketAddr,
+    pub fcp_transport: FcpTransport,
     pub database_path: PathBuf,
 }
 
+/// How to reach the FCP interface of the node
+#[derive(Clone)]
+pub enum FcpTransport {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// Any other stream, e.g. TLS or a tunnel
+    Custom(Arc<ConnectFn>),
+}
+
+impl FcpTransport {
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        match self {
+            FcpTransport::Tcp(address) => ReconnectPolicy::tcp(*address),
+            #[cfg(unix)]
+            FcpTransport::Unix(path) => ReconnectPolicy::unix(path.clone()),
+            FcpTransport::Custom(connect) => {
+                let connect = connect.clone();
+                ReconnectPolicy::new(Box::new(move || connect()))
+            }
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
-            fcp_endpoint: "127.0.0.1:9481".parse().unwrap(),
+            fcp_transport: FcpTransport::Tcp("127.0.0.1:9481".parse().unwrap()),
             database_path: "./mycelink.sqlite3".into(),
         }
     }
@@ -8,6 +8,7 @@ use crate::crypto::tagged_types::tagged_keypair::{
 use crate::db::actions::mycelink_account_actions::MycelinkAccountEntryError;
 use crate::fcp_tools::fcp_put::{fcp_put_inline, FcpPutError};
 use crate::fcp_tools::generate_ssk::{generate_ssk, GenerateSSKKeypairError};
+use crate::fcp_tools::MESSAGING;
 use crate::model::connection_details::PublicMycelinkConnectionDetails;
 use mycelink_lib_fcp::fcp_connector::FCPConnector;
 use serde::{Deserialize, Serialize};
@@ -55,22 +56,24 @@ impl MycelinkAccount {
         let encryption_keys = vec![X25519::generate_encryption_keypair().into()];
         let signing_keys = vec![Ed25519::generate_signing_keypair().into()];
 
-        let ssk_keypair = generate_ssk(fcp).await?;
-        let dropbox_keypair = generate_ssk(fcp).await?;
+        let ssk_keypair = generate_ssk(fcp, None).await?;
+        let dropbox_keypair = generate_ssk(fcp, None).await?;
 
         let account = Self {
-            request_ssk_key: ssk_keypair.request_uri,
-            insert_ssk_key: ssk_keypair.insert_uri,
-            channel_request_dropbox_request_key: format!(
-                "{}/requests",
-                dropbox_keypair.request_uri
-            )
-            .into(),
-            channel_request_dropbox_insert_key: format!(
-                "{}/requests/0",
-                dropbox_keypair.insert_uri.replace("SSK@", "USK@")
-            )
-            .into(),
+            request_ssk_key: (&ssk_keypair.request_uri).into(),
+            insert_ssk_key: (&ssk_keypair.insert_uri).into(),
+            channel_request_dropbox_request_key: dropbox_keypair
+                .request_uri
+                .usk_at("requests", 0)
+                .as_ref()
+                .ok_or(CreateAccountError::InvalidKeypair)?
+                .into(),
+            channel_request_dropbox_insert_key: dropbox_keypair
+                .insert_uri
+                .usk_at("requests", 0)
+                .as_ref()
+                .ok_or(CreateAccountError::InvalidKeypair)?
+                .into(),
             encryption_keys,
             signing_keys,
         };
@@ -92,7 +95,9 @@ impl MycelinkAccount {
             public_details_buf.into(),
             self.insert_ssk_key.deref().try_into().unwrap(),
             fcp,
+            MESSAGING,
             "publish account",
+            None,
         )
         .await?;
         Ok(())
@@ -130,6 +135,8 @@ pub enum CreateAccountError {
     GenerateSSK(GenerateSSKKeypairError),
     FcpPut(FcpPutError),
     AccountEntry(MycelinkAccountEntryError),
+    /// The node returned an insert URI without a key
+    InvalidKeypair,
 }
 
 impl From<GenerateSSKKeypairError> for CreateAccountError {
@@ -155,3 +162,36 @@ impl From<sqlx::Error> for CreateAccountError {
         Self::AccountEntry(MycelinkAccountEntryError::SqlxError { inner: value })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::mycelink::mycelink_account::MycelinkAccount;
+    use crate::test::create_test_fcp_connector;
+    use mycelink_lib_fcp::model::uri::URI;
+
+    #[tokio::test]
+    async fn test_dropbox_keys_match() {
+        let connector = create_test_fcp_connector("mycelink_account::test_dropbox_keys").await;
+        let account = MycelinkAccount::create_new("Alice", &connector)
+            .await
+            .unwrap();
+
+        for key in [
+            &account.channel_request_dropbox_request_key,
+            &account.channel_request_dropbox_insert_key,
+        ] {
+            let URI::USK {
+                docname, edition, ..
+            } = key.parse().unwrap()
+            else {
+                panic!("Expected USK, got {key}");
+            };
+            assert_eq!(&*docname, "requests");
+            assert_eq!(edition, 0);
+        }
+        assert_ne!(
+            account.channel_request_dropbox_request_key,
+            account.channel_request_dropbox_insert_key
+        );
+    }
+}
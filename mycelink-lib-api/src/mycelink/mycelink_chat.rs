@@ -3,6 +3,7 @@ use crate::db::actions::chat_actions::ChatId;
 use crate::db::actions::tenant_actions::Tenant;
 use crate::db::db_connector::DBConnector;
 use crate::fcp_tools::fcp_put::{fcp_put_inline, FcpPutError};
+use crate::fcp_tools::MESSAGING;
 use crate::model::message::ProtocolMessageMeta;
 use crate::model::message_types::MessageType;
 use crate::model::messenger_service::PollError;
@@ -54,7 +55,9 @@ impl MycelinkChat {
                 .try_into()
                 .unwrap(),
             fcp,
+            MESSAGING,
             "send channel request",
+            None,
         )
         .await?;
 
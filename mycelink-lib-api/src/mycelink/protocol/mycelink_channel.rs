@@ -13,6 +13,7 @@ use crate::crypto::tagged_types::tagged_keypair::TaggedEncryptionKeyPair;
 use crate::crypto::tagged_types::tagged_secret_box::TaggedSecretBox;
 use crate::fcp_tools::fcp_get::{fcp_get_inline, FcpGetError};
 use crate::fcp_tools::fcp_put::{fcp_put_inline, FcpPutError};
+use crate::fcp_tools::{CHAT_POLLING, MESSAGING};
 use crate::mycelink::protocol::compressed_box::{
     CompressedBox, CompressionHint, CompressionHinting,
 };
@@ -133,7 +134,9 @@ impl MycelinkChannel {
             encoded_encrypted.into(),
             self.send_ratchet.generate_send_message_ksk(),
             fcp_connector,
+            MESSAGING,
             "Send Mycelink Channel",
+            None,
         )
         .await?;
         self.send_ratchet.advance();
@@ -226,8 +229,10 @@ impl MycelinkChannel {
         let message = fcp_get_inline(
             ksk,
             fcp_connector,
+            CHAT_POLLING,
             "Receive Mycelink Message",
             PriorityClass::High,
+            None,
         )
         .await;
 
@@ -344,8 +349,11 @@ mod tests {
     use crate::mycelink::protocol::mycelink_chat_message::{
         MycelinkChatMessage, MycelinkChatMessageContent, MycelinkChatMessageType,
     };
-    use crate::test::create_test_fcp_connector;
+    use crate::mycelink::protocol::mycelink_ratchet_key_generator::MycelinkRatchetKeyGenerator;
+    use crate::test::{create_test_fcp_connector, create_test_mock_node};
     use mycelink_lib_fcp::fcp_connector::FCPConnector;
+    use mycelink_lib_fcp::mock_node::fault::{MockBehaviour, MockFault};
+    use mycelink_lib_fcp::model::message_type_identifier::ClientMessageType;
 
     async fn open_channel(
         fcp_connector: &FCPConnector,
@@ -539,4 +547,137 @@ mod tests {
             panic!()
         }
     }
+
+    #[tokio::test]
+    async fn test_receive_waits_for_hidden_message() {
+        let _ = env_logger::try_init();
+        let (node, fcp_connector) =
+            create_test_mock_node("test_receive_waits_for_hidden_message").await;
+
+        let (mut channel_a, mut channel_b) = open_channel(&fcp_connector).await.unwrap();
+        channel_b
+            .try_receive_initial_message(&fcp_connector)
+            .await
+            .unwrap();
+
+        let ksk: Box<str> = (&channel_a.send_ratchet.generate_send_message_ksk()).into();
+        node.inject_fault(MockFault::hide_key(&ksk, 2)).await;
+
+        channel_a
+            .send_chat_message(
+                MycelinkChatMessageType::Standard {
+                    content: MycelinkChatMessageContent::Text("Hello World".into()),
+                },
+                &fcp_connector,
+            )
+            .await
+            .unwrap();
+
+        let iteration = channel_b.receive_ratchet.current_iteration();
+        for _ in 0..2 {
+            assert!(channel_b
+                .try_receive_message(&fcp_connector)
+                .await
+                .unwrap()
+                .is_none());
+            assert_eq!(channel_b.receive_ratchet.current_iteration(), iteration);
+        }
+
+        assert!(channel_b
+            .try_receive_message(&fcp_connector)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(channel_b.receive_ratchet.current_iteration(), iteration + 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_keeps_ratchet() {
+        let _ = env_logger::try_init();
+        let (node, fcp_connector) = create_test_mock_node("test_failed_send_keeps_ratchet").await;
+
+        let (mut channel_a, mut channel_b) = open_channel(&fcp_connector).await.unwrap();
+
+        let ksk: Box<str> = (&channel_a.send_ratchet.generate_send_message_ksk()).into();
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientPut,
+            uri: Some(ksk),
+            behaviour: MockBehaviour::Fail { code: 5 },
+            times: Some(1),
+        })
+        .await;
+
+        let message = MycelinkChatMessageType::Standard {
+            content: MycelinkChatMessageContent::Text("Hello World".into()),
+        };
+        let iteration = channel_a.send_ratchet.current_iteration();
+
+        let failed = channel_a
+            .send_chat_message(message.clone(), &fcp_connector)
+            .await;
+        assert!(matches!(failed, Err(FcpPutError::PutFailed { .. })));
+        assert_eq!(channel_a.send_ratchet.current_iteration(), iteration);
+
+        channel_a
+            .send_chat_message(message.clone(), &fcp_connector)
+            .await
+            .unwrap();
+
+        let received_message = channel_b.try_receive_message(&fcp_connector).await.unwrap();
+        let received_message: &MycelinkChatMessage =
+            received_message.as_ref().unwrap().try_into().unwrap();
+        assert_eq!(received_message.message_type(), &message);
+    }
+
+    #[tokio::test]
+    async fn test_rekey_waits_for_hidden_final_message() {
+        let _ = env_logger::try_init();
+        let (node, fcp_connector) =
+            create_test_mock_node("test_rekey_waits_for_hidden_final_message").await;
+
+        let (mut channel_a, mut channel_b) = open_channel(&fcp_connector).await.unwrap();
+
+        // Ensure a has b public components so the next message rekeys
+        assert!(channel_a
+            .try_receive_message(&fcp_connector)
+            .await
+            .unwrap()
+            .is_none());
+        channel_b
+            .try_receive_initial_message(&fcp_connector)
+            .await
+            .unwrap();
+
+        let ksk: Box<str> = (&channel_a.send_ratchet.generate_send_message_ksk()).into();
+        node.inject_fault(MockFault::hide_key(&ksk, 1)).await;
+
+        channel_a
+            .send_chat_message(
+                MycelinkChatMessageType::Standard {
+                    content: MycelinkChatMessageContent::Text("Hello World".into()),
+                },
+                &fcp_connector,
+            )
+            .await
+            .unwrap();
+        assert_eq!(channel_a.send_ratchet.current_iteration(), 0);
+
+        let iteration = channel_b.receive_ratchet.current_iteration();
+        assert!(channel_b
+            .try_receive_message(&fcp_connector)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(channel_b.receive_ratchet.current_iteration(), iteration);
+
+        assert!(channel_b
+            .try_receive_message(&fcp_connector)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            channel_a.send_ratchet.current_key("unit test"),
+            channel_b.receive_ratchet.current_key("unit test")
+        );
+    }
 }
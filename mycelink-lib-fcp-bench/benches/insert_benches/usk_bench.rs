@@ -11,7 +11,6 @@ use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
 use mycelink_lib_fcp::model::upload_type::UploadType::Direct;
 use mycelink_lib_fcp_bench::fcp_helper::{generate_ssk, prepare_connection};
 use rand::RngCore;
-use tokio::io::AsyncWriteExt;
 
 pub fn usk_bench(c: &mut Criterion) {
     c.bench_function("usk_initial_bench", |b| {
@@ -32,13 +31,10 @@ async fn usk_bench_initial_fn() {
 
     let keypair = generate_ssk(&mut tx, &mut rx).await;
 
-    let mut insert_uri = keypair.insert_uri.replace("SSK@", "USK@");
-    insert_uri.push_str("test/0");
-
     let put_message = ClientPutMessage {
-        uri: insert_uri.as_str().try_into().unwrap(),
+        uri: keypair.insert_uri.usk_at("test", 0).unwrap(),
         content_type: None,
-        identifier: UniqueIdentifier::new("Bench insert USK"),
+        identifier: UniqueIdentifier::new("Bench insert USK").unwrap(),
         verbosity: Default::default(),
         max_retries: 0,
         priority: PriorityClass::High,
@@ -49,10 +45,11 @@ async fn usk_bench_initial_fn() {
         upload_from: Direct { data: data.into() },
         is_binary_blob: false,
         real_time: true,
+        compatibility_mode: None,
+        override_splitfile_crypto_key: None,
     };
 
-    let encoded = (&put_message).to_message().encode();
-    tx.write_all(encoded.as_slice()).await.unwrap();
+    put_message.to_message().write_to(&mut tx).await.unwrap();
 
     let _uri_updated: UriGeneratedMessage =
         Message::decode(&mut rx).await.unwrap().try_into().unwrap();
@@ -0,0 +1,560 @@
+use crate::decode_error::DecodeError;
+use crate::encode_error::EncodeError;
+use crate::model::fields::{Field, Fields, DATA_LIT, END_MESSAGE_LIT, PAYLOAD_LENGTH_HINT_KEYS};
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::MessageType;
+use bytes::{Buf, Bytes, BytesMut};
+use std::mem;
+use std::str::from_utf8;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Longest line accepted, a longer one is reported as malformed message and skipped
+pub const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Most payload bytes reserved up front, as the `DataLength` announced by the node is not trusted
+pub const MAX_PAYLOAD_PREALLOCATION: usize = 64 * 1024;
+
+/// Sans-IO FCP framing, turning bytes into [Message]s and [Message]s into bytes
+///
+/// Malformed messages are reported once and then skipped up to the next `EndMessage` or `Data`
+/// boundary (including the payload of the broken message), so decoding can continue with the
+/// following message.
+#[derive(Debug, Default)]
+pub struct FCPCodec {
+    state: DecodeState,
+    /// Message whose payload [FCPCodec::decode] is collecting
+    buffered: Option<(Message, Box<str>, Vec<u8>)>,
+    /// Bytes at the start of the buffer already searched for the end of the current line
+    scanned: usize,
+    /// Skipping the rest of a line longer than [MAX_LINE_LENGTH]
+    discarding_line: bool,
+}
+
+/// A piece of the decoded stream, see [FCPCodec::decode_frame]
+#[derive(Debug)]
+pub enum Frame {
+    /// A message without payload
+    Message(Message),
+    /// A message whose payload of `len` bytes follows as [Frame::PayloadChunk]s, terminated by a
+    /// [Frame::PayloadEnd]
+    PayloadStart {
+        message: Message,
+        data_len_identifier: Box<str>,
+        len: usize,
+    },
+    PayloadChunk(Bytes),
+    PayloadEnd,
+}
+
+#[derive(Debug, Default)]
+enum DecodeState {
+    /// Waiting for the line naming the next message
+    #[default]
+    MessageType,
+    Fields {
+        message_type: MessageType,
+        fields: Vec<Field>,
+    },
+    Payload {
+        remaining: usize,
+    },
+    /// Skipping the rest of a malformed message
+    Resync {
+        payload_len: Option<usize>,
+    },
+    /// Skipping the payload of a malformed message
+    Skip {
+        remaining: usize,
+    },
+}
+
+impl FCPCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the next message from the start of `src`, consuming the bytes it was made of
+    ///
+    /// Returns [None] if `src` does not hold a complete message yet.
+    pub fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>, DecodeError> {
+        loop {
+            if let DecodeState::Payload { remaining } = self.state {
+                // Waiting for the whole payload copies it only once
+                if src.len() < remaining {
+                    src.reserve((remaining - src.len()).min(MAX_PAYLOAD_PREALLOCATION));
+                    return Ok(None);
+                }
+            }
+
+            match self.decode_frame(src)? {
+                None => return Ok(None),
+                Some(Frame::Message(message)) => return Ok(Some(message)),
+                Some(Frame::PayloadStart {
+                    message,
+                    data_len_identifier,
+                    len,
+                }) => {
+                    let data = Vec::with_capacity(len.min(MAX_PAYLOAD_PREALLOCATION));
+                    self.buffered = Some((message, data_len_identifier, data));
+                }
+                Some(Frame::PayloadChunk(chunk)) => {
+                    if let Some((_, _, data)) = &mut self.buffered {
+                        data.extend_from_slice(&chunk);
+                    }
+                }
+                Some(Frame::PayloadEnd) => {
+                    if let Some((message, data_len_identifier, data)) = self.buffered.take() {
+                        let payload = MessagePayload {
+                            data: data.into(),
+                            data_len_identifier,
+                        };
+                        return Ok(Some(message.with_payload(payload)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like [FCPCodec::decode], but no more bytes will follow `src`
+    pub fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Message>, DecodeError> {
+        match self.decode(src)? {
+            Some(message) => Ok(Some(message)),
+            None if src.is_empty() && matches!(self.state, DecodeState::MessageType) => Ok(None),
+            None => Err(DecodeError::UnexpectedEOF),
+        }
+    }
+
+    /// Decodes the next [Frame] from the start of `src`, handing out payloads in chunks as they
+    /// arrive instead of waiting for them to be complete
+    pub fn decode_frame(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, DecodeError> {
+        loop {
+            match &mut self.state {
+                DecodeState::Skip { remaining } => {
+                    let skipped = (*remaining).min(src.len());
+                    src.advance(skipped);
+                    *remaining -= skipped;
+
+                    if *remaining > 0 {
+                        return Ok(None);
+                    }
+                    self.state = DecodeState::MessageType;
+                }
+                DecodeState::Payload { remaining } => {
+                    if *remaining == 0 {
+                        self.state = DecodeState::MessageType;
+                        return Ok(Some(Frame::PayloadEnd));
+                    }
+                    if src.is_empty() {
+                        return Ok(None);
+                    }
+
+                    let chunk = src.split_to((*remaining).min(src.len())).freeze();
+                    *remaining -= chunk.len();
+                    return Ok(Some(Frame::PayloadChunk(chunk)));
+                }
+                _ => {
+                    let Some(line) = self.next_line(src)? else {
+                        return Ok(None);
+                    };
+
+                    match from_utf8(&line) {
+                        Ok(line) => {
+                            if let Some(frame) = self.decode_line(line)? {
+                                return Ok(Some(frame));
+                            }
+                        }
+                        Err(_) if matches!(self.state, DecodeState::Resync { .. }) => {}
+                        Err(err) => {
+                            self.start_resync();
+                            return Err(err.into());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn encode(&mut self, message: &Message, dst: &mut BytesMut) -> Result<(), EncodeError> {
+        message.encode_into(dst)
+    }
+
+    fn decode_line(&mut self, line: &str) -> Result<Option<Frame>, DecodeError> {
+        match mem::take(&mut self.state) {
+            DecodeState::MessageType => {
+                if !line.trim_end().is_empty() {
+                    let message_type = MessageType::try_from(line)
+                        .unwrap_or_else(|_| MessageType::Unknown(line.into()));
+                    self.state = DecodeState::Fields {
+                        message_type,
+                        fields: Vec::new(),
+                    };
+                }
+                Ok(None)
+            }
+            DecodeState::Fields {
+                message_type,
+                mut fields,
+            } => {
+                if line == END_MESSAGE_LIT {
+                    return Ok(Some(Frame::Message(Message::new(
+                        message_type,
+                        fields.into(),
+                        None,
+                    ))));
+                }
+                if line == DATA_LIT {
+                    return self.start_payload(message_type, fields.into());
+                }
+
+                if Field::is_field(line) {
+                    fields.push(line.try_into()?);
+                } else if !line.trim_end().is_empty() {
+                    self.state = DecodeState::Resync {
+                        payload_len: last_payload_len(fields.iter()),
+                    };
+                    return Err(DecodeError::ParseError(
+                        format!(
+                            "'{line}' neither indicates the end of a Fields nor is a field itself."
+                        )
+                        .into(),
+                    ));
+                }
+
+                self.state = DecodeState::Fields {
+                    message_type,
+                    fields,
+                };
+                Ok(None)
+            }
+            DecodeState::Resync { mut payload_len } => {
+                if line == END_MESSAGE_LIT {
+                    return Ok(None);
+                }
+                if line == DATA_LIT {
+                    self.state = DecodeState::Skip {
+                        remaining: payload_len.unwrap_or(0),
+                    };
+                    return Ok(None);
+                }
+
+                let hint = Field::try_from(line)
+                    .ok()
+                    .filter(|e| PAYLOAD_LENGTH_HINT_KEYS.contains(&e.key()));
+                if let Some(hint) = hint {
+                    payload_len = hint.value().parse().ok();
+                }
+                self.state = DecodeState::Resync { payload_len };
+                Ok(None)
+            }
+            DecodeState::Payload { .. } | DecodeState::Skip { .. } => {
+                unreachable!("Payloads are not decoded line by line")
+            }
+        }
+    }
+
+    fn start_payload(
+        &mut self,
+        message_type: MessageType,
+        fields: Fields,
+    ) -> Result<Option<Frame>, DecodeError> {
+        let len = fields
+            .get_payload_size_hint()
+            .and_then(|hint| Ok((hint.key().into(), hint.value().parse::<usize>()?)));
+
+        match len {
+            Ok((data_len_identifier, len)) => {
+                self.state = DecodeState::Payload { remaining: len };
+                Ok(Some(Frame::PayloadStart {
+                    message: Message::new(message_type, fields, None),
+                    data_len_identifier,
+                    len,
+                }))
+            }
+            Err(err) => {
+                self.state = DecodeState::Skip {
+                    remaining: last_payload_len(fields.iter()).unwrap_or(0),
+                };
+                Err(err)
+            }
+        }
+    }
+
+    fn start_resync(&mut self) {
+        let payload_len = match &self.state {
+            DecodeState::Fields { fields, .. } => last_payload_len(fields.iter()),
+            DecodeState::Resync { payload_len } => *payload_len,
+            _ => None,
+        };
+        self.state = DecodeState::Resync { payload_len };
+    }
+
+    /// Splits off the next line without its `\n`, if `src` contains a complete one
+    ///
+    /// Bytes searched by an earlier call are not searched again, a line longer than
+    /// [MAX_LINE_LENGTH] is dropped as soon as it is known to be too long.
+    fn next_line(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, DecodeError> {
+        loop {
+            let start = self.scanned.min(src.len());
+            let Some(end) = src[start..].iter().position(|e| *e == b'\n') else {
+                self.scanned = src.len();
+                if self.discarding_line {
+                    src.clear();
+                    self.scanned = 0;
+                } else if src.len() > MAX_LINE_LENGTH {
+                    src.clear();
+                    self.scanned = 0;
+                    self.discarding_line = true;
+                    return Err(self.line_too_long());
+                }
+                return Ok(None);
+            };
+
+            let end = start + end;
+            let mut line = src.split_to(end + 1);
+            self.scanned = 0;
+            if self.discarding_line {
+                self.discarding_line = false;
+                continue;
+            }
+            if end > MAX_LINE_LENGTH {
+                return Err(self.line_too_long());
+            }
+
+            line.truncate(end);
+            return Ok(Some(line));
+        }
+    }
+
+    /// Skips the rest of the message with the line that was too long
+    fn line_too_long(&mut self) -> DecodeError {
+        self.start_resync();
+        DecodeError::ParseError(format!("Line is longer than {MAX_LINE_LENGTH} bytes").into())
+    }
+}
+
+fn last_payload_len<'a>(fields: impl DoubleEndedIterator<Item = &'a Field>) -> Option<usize> {
+    fields
+        .rev()
+        .find(|e| PAYLOAD_LENGTH_HINT_KEYS.contains(&e.key()))
+        .and_then(|e| e.value().parse().ok())
+}
+
+impl Decoder for FCPCodec {
+    type Item = Message;
+    type Error = DecodeError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        FCPCodec::decode(self, src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        FCPCodec::decode_eof(self, src)
+    }
+}
+
+impl Encoder<&Message> for FCPCodec {
+    type Error = EncodeError;
+
+    fn encode(&mut self, item: &Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        FCPCodec::encode(self, item, dst)
+    }
+}
+
+impl Encoder<Message> for FCPCodec {
+    type Error = EncodeError;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        FCPCodec::encode(self, &item, dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codec::{FCPCodec, Frame, MAX_LINE_LENGTH, MAX_PAYLOAD_PREALLOCATION};
+    use crate::decode_error::DecodeError;
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::MessageType::Node;
+    use crate::model::message_type_identifier::NodeMessageType::{AllData, NodeHello};
+    use bytes::BytesMut;
+
+    const NODE_HELLO: &[u8] = b"NodeHello\nFCPVersion=2.0\nEndMessage\n";
+    const ALL_DATA: &[u8] = b"AllData\nIdentifier=abc\nDataLength=5\nData\nab\ncd";
+
+    fn decode_all(codec: &mut FCPCodec, src: &mut BytesMut) -> Vec<Result<Message, DecodeError>> {
+        let mut res = Vec::new();
+        loop {
+            match codec.decode(src) {
+                Ok(Some(message)) => res.push(Ok(message)),
+                Ok(None) => return res,
+                Err(err) => res.push(Err(err)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_needs_complete_message() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from(&NODE_HELLO[..NODE_HELLO.len() - 1]);
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+
+        src.extend_from_slice(b"\n");
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_byte_by_byte_matches_whole() {
+        let encoded = [
+            ALL_DATA,
+            b"\n\nNodeHello\nbroken\nDataLength=3\nData\nxyz",
+            NODE_HELLO,
+            b"AllData\nData\n",
+            NODE_HELLO,
+        ]
+        .concat();
+
+        let mut codec = FCPCodec::new();
+        let whole = decode_all(&mut codec, &mut BytesMut::from(encoded.as_slice()));
+
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::new();
+        let mut byte_by_byte = Vec::new();
+        for byte in encoded {
+            src.extend_from_slice(&[byte]);
+            byte_by_byte.append(&mut decode_all(&mut codec, &mut src));
+        }
+
+        assert_eq!(whole.len(), 5);
+        assert_eq!(whole.len(), byte_by_byte.len());
+        for (whole, byte_by_byte) in whole.into_iter().zip(byte_by_byte) {
+            match (whole, byte_by_byte) {
+                (Ok(whole), Ok(byte_by_byte)) => assert_eq!(whole, byte_by_byte),
+                (Err(_), Err(_)) => {}
+                _ => panic!("Decoding in pieces changed the result"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_payload() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from(ALL_DATA);
+
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message_type(), Node(AllData));
+        assert_eq!(message.fields().get("Identifier").unwrap().value(), "abc");
+        assert_eq!(&*message.payload().unwrap().data, b"ab\ncd");
+    }
+
+    #[test]
+    fn test_announced_payload_length_is_not_preallocated() {
+        let mut codec = FCPCodec::new();
+        let mut src =
+            BytesMut::from(&b"AllData\nIdentifier=abc\nDataLength=1073741824\nData\nab"[..]);
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(src.capacity() <= 2 * MAX_PAYLOAD_PREALLOCATION);
+        let (_, _, data) = codec.buffered.as_ref().unwrap();
+        assert!(data.capacity() <= MAX_PAYLOAD_PREALLOCATION);
+    }
+
+    #[test]
+    fn test_payload_frames() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from(&ALL_DATA[..ALL_DATA.len() - 2]);
+
+        let Some(Frame::PayloadStart { message, len, .. }) = codec.decode_frame(&mut src).unwrap()
+        else {
+            panic!("Expected the payload to start")
+        };
+        assert_eq!(message.message_type(), Node(AllData));
+        assert_eq!(len, 5);
+
+        let Some(Frame::PayloadChunk(chunk)) = codec.decode_frame(&mut src).unwrap() else {
+            panic!("Expected the available part of the payload")
+        };
+        assert_eq!(&chunk[..], b"ab\n");
+        assert!(codec.decode_frame(&mut src).unwrap().is_none());
+
+        src.extend_from_slice(b"cd");
+        let Some(Frame::PayloadChunk(chunk)) = codec.decode_frame(&mut src).unwrap() else {
+            panic!("Expected the rest of the payload")
+        };
+        assert_eq!(&chunk[..], b"cd");
+        assert!(matches!(
+            codec.decode_frame(&mut src).unwrap(),
+            Some(Frame::PayloadEnd)
+        ));
+    }
+
+    #[test]
+    fn test_missing_payload_hint_is_skipped() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from([b"AllData\nData\n", NODE_HELLO].concat().as_slice());
+
+        let err = codec.decode(&mut src).unwrap_err();
+        assert!(matches!(err, DecodeError::MissingField(_)));
+
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+    }
+
+    #[test]
+    fn test_non_utf8_message_type_is_skipped() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from([b"\xff\nA=b\nEndMessage\n", NODE_HELLO].concat().as_slice());
+
+        let err = codec.decode(&mut src).unwrap_err();
+        assert!(matches!(err, DecodeError::Utf8Error(_)));
+
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+    }
+
+    #[test]
+    fn test_long_line_is_skipped() {
+        let mut codec = FCPCodec::new();
+        let mut src = BytesMut::from(&b"NodeHello\nVersion="[..]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+
+        src.extend_from_slice(&[b'a'; MAX_LINE_LENGTH]);
+        let err = codec.decode(&mut src).unwrap_err();
+        assert!(matches!(err, DecodeError::ParseError(_)));
+        assert!(src.is_empty());
+
+        src.extend_from_slice(&[b'a'; MAX_LINE_LENGTH]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(src.is_empty());
+
+        src.extend_from_slice(&[b"aaa\nEndMessage\n", NODE_HELLO].concat());
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_eof() {
+        let mut codec = FCPCodec::new();
+
+        assert!(codec.decode_eof(&mut BytesMut::new()).unwrap().is_none());
+
+        let mut src = BytesMut::from(&b"NodeHello\nFCPVersion=2.0\n"[..]);
+        let err = codec.decode_eof(&mut src).unwrap_err();
+        assert!(matches!(err, DecodeError::UnexpectedEOF));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let mut codec = FCPCodec::new();
+        let message = codec
+            .decode(&mut BytesMut::from(ALL_DATA))
+            .unwrap()
+            .unwrap();
+
+        let mut encoded = BytesMut::new();
+        codec.encode(&message, &mut encoded).unwrap();
+        assert_eq!(&encoded[..], ALL_DATA);
+    }
+}
@@ -3,6 +3,7 @@ use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::num::ParseIntError;
 use std::str::{ParseBoolError, Utf8Error};
+use tokio::io::ErrorKind;
 
 #[derive(Debug)]
 pub enum DecodeError {
@@ -18,6 +19,7 @@ pub enum DecodeError {
     ParseError(Box<str>),
     Utf8Error(Utf8Error),
     InvalidVersion(Box<str>),
+    InvalidURI(Box<str>),
     MissingField(Box<str>),
     MissingPayload,
     UnexpectedEOF,
@@ -44,6 +46,7 @@ impl Display for DecodeError {
             DecodeError::InvalidVersion(inner) => {
                 write!(f, "Version {inner} is unknown.")
             }
+            DecodeError::InvalidURI(inner) => Display::fmt(inner, f),
             DecodeError::MissingField(inner) => {
                 write!(f, "Field {inner} is missing in message.")
             }
@@ -65,8 +68,24 @@ impl Display for DecodeError {
 
 impl Error for DecodeError {}
 
+impl DecodeError {
+    /// Whether the connection can keep being read from after this error
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            DecodeError::TokioIoError(_) | DecodeError::UnexpectedEOF
+        )
+    }
+}
+
 impl From<tokio::io::Error> for DecodeError {
     fn from(value: std::io::Error) -> Self {
+        if value.kind() == ErrorKind::InvalidData {
+            if let Some(inner) = value.get_ref().and_then(|e| e.downcast_ref::<Utf8Error>()) {
+                return DecodeError::Utf8Error(*inner);
+            }
+        }
+
         DecodeError::TokioIoError(value)
     }
 }
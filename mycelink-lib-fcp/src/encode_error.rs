@@ -0,0 +1,65 @@
+use crate::model::redaction::redact_value;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use tokio::io::ErrorKind;
+
+/// A message that cannot be written to the FCP stream without changing its meaning
+///
+/// Private keys in an invalid value are masked when the error is formatted.
+pub enum EncodeError {
+    TokioIoError(tokio::io::Error),
+    /// The key is empty or contains `=` or a forbidden character
+    InvalidKey(Box<str>),
+    /// The value of the field `key` contains a forbidden character
+    InvalidValue {
+        key: Box<str>,
+        value: Box<str>,
+    },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::TokioIoError(inner) => Display::fmt(inner, f),
+            EncodeError::InvalidKey(key) => write!(f, "Invalid field key {key:?}"),
+            EncodeError::InvalidValue { key, value } => {
+                write!(
+                    f,
+                    "Invalid value {:?} for field {key}",
+                    redact_value(key, value)
+                )
+            }
+        }
+    }
+}
+
+impl Debug for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::TokioIoError(inner) => f.debug_tuple("TokioIoError").field(inner).finish(),
+            EncodeError::InvalidKey(key) => f.debug_tuple("InvalidKey").field(key).finish(),
+            EncodeError::InvalidValue { key, value } => f
+                .debug_struct("InvalidValue")
+                .field("key", key)
+                .field("value", &redact_value(key, value))
+                .finish(),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+impl From<tokio::io::Error> for EncodeError {
+    fn from(value: tokio::io::Error) -> Self {
+        EncodeError::TokioIoError(value)
+    }
+}
+
+impl From<EncodeError> for tokio::io::Error {
+    fn from(value: EncodeError) -> Self {
+        match value {
+            EncodeError::TokioIoError(inner) => inner,
+            invalid => tokio::io::Error::new(ErrorKind::InvalidInput, invalid),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::fcp_connector::request::RequestError;
+use crate::fcp_connector::FCPConnector;
+use crate::messages::client_put::{CHKOnlyClientPutMessage, ClientPutMessage};
+use crate::messages::put_failed::PutFailedMessage;
+use crate::model::compatibility_mode::CompatibilityMode;
+use crate::model::upload_type::UploadType;
+use crate::model::uri::URI;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// Key for [ClientPutMessage::override_splitfile_crypto_key], [content_crypto_key] instead lets
+/// equal attachments share their CHK
+pub fn random_crypto_key() -> [u8; 32] {
+    rand::random()
+}
+
+/// Key for [ClientPutMessage::override_splitfile_crypto_key] derived from the SHA-256 of `data`,
+/// so equal data always ends up under the same CHK
+///
+/// Everyone holding the same data can compute the CHK as well and check whether it was inserted.
+pub fn content_crypto_key(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+impl FCPConnector {
+    /// The CHK `put` will be inserted under, computed by the node without inserting anything
+    ///
+    /// Only inserts to `CHK@` with a pinned [CompatibilityMode] and splitfile crypto key are
+    /// accepted, as the node could pick another URI for the actual insert otherwise. Streamed data
+    /// is refused as well, as it is not sent along.
+    pub async fn compute_chk(
+        &self,
+        put: &ClientPutMessage,
+        timeout: Option<Duration>,
+    ) -> Result<URI, CHKError> {
+        check_deterministic(put).map_err(CHKError::NotDeterministic)?;
+        let (response, _) = self.request(&CHKOnlyClientPutMessage(put), timeout).await?;
+        Ok(response.await?.uri)
+    }
+
+    /// Inserts `put`, failing with [CHKError::URIMismatch] if the node inserted it under another
+    /// URI than `expected`, e.g. one returned by [FCPConnector::compute_chk]
+    pub async fn insert_chk(
+        &self,
+        put: &ClientPutMessage,
+        expected: &URI,
+        timeout: Option<Duration>,
+    ) -> Result<URI, CHKError> {
+        check_deterministic(put).map_err(CHKError::NotDeterministic)?;
+        let (response, _) = self.request(put, timeout).await?;
+        let uri = response.await?.uri;
+        if &uri != expected {
+            return Err(CHKError::URIMismatch {
+                expected: expected.clone(),
+                got: uri,
+            });
+        }
+        Ok(uri)
+    }
+}
+
+/// Why the node could insert `put` under another URI than the one computed for it
+fn check_deterministic(put: &ClientPutMessage) -> Result<(), &'static str> {
+    let reason = match put {
+        ClientPutMessage {
+            upload_from: UploadType::Stream,
+            ..
+        } => "the streamed data is only sent by request_with_payload",
+        ClientPutMessage {
+            uri: URI::CHK { key: Some(_), .. } | URI::SSK { .. } | URI::USK { .. } | URI::KSK { .. },
+            ..
+        } => "the insert is not to CHK@",
+        ClientPutMessage {
+            compatibility_mode: None | Some(CompatibilityMode::Current),
+            ..
+        } => "no fixed compatibility mode is set",
+        ClientPutMessage {
+            override_splitfile_crypto_key: None,
+            ..
+        } => "no splitfile crypto key is set",
+        _ => return Ok(()),
+    };
+    Err(reason)
+}
+
+#[derive(Debug)]
+pub enum CHKError {
+    TokioIo(tokio::io::Error),
+    /// The URI of the insert could change between computing and inserting it
+    NotDeterministic(&'static str),
+    Request(RequestError<PutFailedMessage>),
+    /// The data has been inserted, but not under the computed URI
+    URIMismatch {
+        expected: URI,
+        got: URI,
+    },
+}
+
+impl Display for CHKError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CHKError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            CHKError::NotDeterministic(reason) => {
+                write!(f, "CHK is not deterministic as {reason}")
+            }
+            CHKError::Request(inner) => Display::fmt(inner, f),
+            CHKError::URIMismatch { expected, got } => {
+                write!(f, "Expected insert under {expected} but got {got}")
+            }
+        }
+    }
+}
+
+impl Error for CHKError {}
+
+impl From<tokio::io::Error> for CHKError {
+    fn from(value: tokio::io::Error) -> Self {
+        CHKError::TokioIo(value)
+    }
+}
+
+impl From<RequestError<PutFailedMessage>> for CHKError {
+    fn from(value: RequestError<PutFailedMessage>) -> Self {
+        CHKError::Request(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::chk::{content_crypto_key, random_crypto_key, CHKError};
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::mock_node::MockNode;
+    use crate::model::compatibility_mode::CompatibilityMode;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::verbosity::Verbosity;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn put(data: &[u8], crypto_key: [u8; 32]) -> ClientPutMessage {
+        ClientPutMessage {
+            uri: "CHK@".try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("CHK test").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Direct { data: data.into() },
+            is_binary_blob: false,
+            real_time: true,
+            compatibility_mode: Some(CompatibilityMode::Compat1468),
+            override_splitfile_crypto_key: Some(crypto_key),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compute_then_insert() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("CHK test").await;
+
+        let crypto_key = random_crypto_key();
+        let put = put(b"Attachment", crypto_key);
+        let computed = connector.compute_chk(&put, TIMEOUT).await.unwrap();
+        assert!(node.get(&computed.to_string()).await.is_none());
+
+        let inserted = connector
+            .insert_chk(&put, &computed, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(inserted, computed);
+        assert_eq!(
+            node.get(&computed.to_string()).await.unwrap().as_ref(),
+            b"Attachment"
+        );
+
+        let other_key = connector
+            .compute_chk(&self::put(b"Attachment", random_crypto_key()), TIMEOUT)
+            .await
+            .unwrap();
+        assert_ne!(other_key, computed);
+        assert!(matches!(
+            connector
+                .insert_chk(&self::put(b"Other", crypto_key), &computed, TIMEOUT)
+                .await,
+            Err(CHKError::URIMismatch { .. })
+        ));
+
+        let mut current = self::put(b"Attachment", crypto_key);
+        current.compatibility_mode = Some(CompatibilityMode::Current);
+        assert!(matches!(
+            connector.compute_chk(&current, TIMEOUT).await,
+            Err(CHKError::NotDeterministic(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_equal_content_shares_chk() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("CHK test").await;
+
+        let first = put(b"Attachment", content_crypto_key(b"Attachment"));
+        let second = put(b"Attachment", content_crypto_key(b"Attachment"));
+        let computed = connector.compute_chk(&first, TIMEOUT).await.unwrap();
+        assert_eq!(
+            connector.compute_chk(&second, TIMEOUT).await.unwrap(),
+            computed
+        );
+        assert_ne!(
+            content_crypto_key(b"Attachment"),
+            content_crypto_key(b"Other")
+        );
+
+        let mut streamed = put(b"", content_crypto_key(b"Attachment"));
+        streamed.upload_from = UploadType::Stream;
+        assert!(matches!(
+            connector.compute_chk(&streamed, TIMEOUT).await,
+            Err(CHKError::NotDeterministic(_))
+        ));
+    }
+}
@@ -0,0 +1,454 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::filters::MessageFilter;
+use crate::fcp_connector::reconnect::ConnectionState;
+use crate::fcp_connector::request::FCPRequest;
+use crate::fcp_connector::FCPConnector;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::messages::test_dda_complete::TestDDACompleteMessage;
+use crate::messages::test_dda_reply::TestDDAReplyMessage;
+use crate::messages::test_dda_request::TestDDARequestMessage;
+use crate::messages::test_dda_response::TestDDAResponseMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    ProtocolError, TestDDAComplete, TestDDAReply,
+};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::ErrorKind;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::Instant;
+
+/// Direct Disk Access (DDA) of the node to a directory of the client
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiskAccess {
+    pub read: bool,
+    pub write: bool,
+}
+
+impl DiskAccess {
+    pub const READ: Self = Self {
+        read: true,
+        write: false,
+    };
+    pub const WRITE: Self = Self {
+        read: false,
+        write: true,
+    };
+
+    /// Whether everything allowed by `other` is allowed by `self` as well
+    pub fn covers(&self, other: &DiskAccess) -> bool {
+        (self.read || !other.read) && (self.write || !other.write)
+    }
+
+    pub fn union(&self, other: &DiskAccess) -> Self {
+        Self {
+            read: self.read || other.read,
+            write: self.write || other.write,
+        }
+    }
+}
+
+/// Outcome of the last `TestDDA` handshake for a directory
+#[derive(Copy, Clone, Debug)]
+pub(super) struct TestedDirectory {
+    requested: DiskAccess,
+    allowed: DiskAccess,
+}
+
+impl FCPConnector {
+    /// Negotiates direct disk access to `directory`, proving to the node that it runs on the
+    /// same machine, and returns the access the node allows
+    ///
+    /// Results are cached per directory until the connection is lost, so the handshake only
+    /// runs again if more access is requested than before.
+    pub async fn test_dda(
+        &self,
+        directory: &Path,
+        access: DiskAccess,
+        timeout: Option<Duration>,
+    ) -> Result<DiskAccess, DDAError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let directory: Box<Path> = match directory.as_os_str().is_empty() {
+            true => tokio::fs::canonicalize(".").await?,
+            false => tokio::fs::canonicalize(directory).await?,
+        }
+        .into();
+
+        let _handshake = self.dda_handshake.lock().await;
+        let tested = self
+            .dda_directories
+            .lock()
+            .unwrap()
+            .get(&directory)
+            .copied();
+        let requested = match tested {
+            Some(tested) if tested.requested.covers(&access) => return Ok(tested.allowed),
+            Some(tested) => tested.requested.union(&access),
+            None => access,
+        };
+
+        let allowed = self.dda_handshake(&directory, requested, deadline).await?;
+        log::debug!("Node allows {allowed:?} to {}", directory.display());
+        self.dda_directories
+            .lock()
+            .unwrap()
+            .insert(directory, TestedDirectory { requested, allowed });
+
+        Ok(allowed)
+    }
+
+    /// Tests the directories a request needs direct disk access to
+    pub(super) async fn prepare_disk_access<R: FCPRequest>(
+        &self,
+        request: &R,
+        timeout: Option<Duration>,
+    ) -> Result<(), tokio::io::Error> {
+        for (directory, access) in request.disk_access() {
+            let allowed = match self.test_dda(directory, access, timeout).await {
+                Ok(allowed) => allowed,
+                Err(DDAError::TokioIo(err)) => return Err(err),
+                Err(err) => return Err(tokio::io::Error::other(err)),
+            };
+            if !allowed.covers(&access) {
+                return Err(tokio::io::Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!("Node does not allow {access:?} to {}", directory.display()),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn dda_handshake(
+        &self,
+        directory: &Path,
+        requested: DiskAccess,
+        deadline: Option<Instant>,
+    ) -> Result<DiskAccess, DDAError> {
+        let filter_directory = directory.to_path_buf();
+        let filter: Box<MessageFilter> = Box::new(move |message| match message.message_type() {
+            MessageType::Node(TestDDAReply | TestDDAComplete) => message
+                .fields()
+                .get("Directory")
+                .map(|e| Path::new(e.value()) == filter_directory)
+                .unwrap_or(false),
+            // The node does not tell which request a refused TestDDA belongs to
+            MessageType::Node(ProtocolError) => message.fields().get("Identifier").is_none(),
+            _ => false,
+        });
+        let test_dda_request = TestDDARequestMessage {
+            directory: directory.into(),
+            want_read_directory: requested.read,
+            want_write_directory: requested.write,
+        };
+        let mut answers = self.query(vec![filter], test_dda_request.into()).await?;
+
+        let reply: TestDDAReplyMessage = self.next_dda_answer(&mut answers, deadline).await?;
+        let read_filename = match &reply.read_filename {
+            Some(read_filename) => Some(dda_file(directory, read_filename).await?),
+            None => None,
+        };
+        let write_filename = match &reply.write_filename {
+            Some(write_filename) => Some(dda_file(directory, write_filename).await?),
+            None => None,
+        };
+
+        let mut read_content = String::new();
+        if let Some(read_filename) = &read_filename {
+            // A node on another machine created the file on its own disk, which is reported by
+            // answering without the content
+            match tokio::fs::read_to_string(read_filename).await {
+                Ok(content) => read_content = content,
+                Err(err) => log::debug!("Failed to read DDA file {read_filename:?} {err}"),
+            }
+        }
+        if let (Some(write_filename), Some(content)) = (&write_filename, &reply.content_to_write) {
+            if let Err(err) = tokio::fs::write(write_filename, content.as_bytes()).await {
+                log::debug!("Failed to write DDA file {write_filename:?} {err}");
+            }
+        }
+
+        let test_dda_response = TestDDAResponseMessage {
+            directory: directory.into(),
+            read_content: read_content.into(),
+        };
+        self.send(test_dda_response).await?;
+
+        let complete: TestDDACompleteMessage = self.next_dda_answer(&mut answers, deadline).await?;
+        Ok(DiskAccess {
+            read: complete.read_directory_allowed,
+            write: complete.write_directory_allowed,
+        })
+    }
+
+    async fn next_dda_answer<T: TryFrom<Message, Error = DecodeError>>(
+        &self,
+        answers: &mut UnboundedReceiver<Message>,
+        deadline: Option<Instant>,
+    ) -> Result<T, DDAError> {
+        let mut state = self.connection_state();
+        let answer = async {
+            tokio::select! {
+                answer = answers.recv() => answer,
+                _ = state.wait_for(|state| *state != ConnectionState::Connected) => None,
+            }
+        };
+
+        let answer = match deadline {
+            None => answer.await,
+            Some(deadline) => tokio::time::timeout_at(deadline, answer)
+                .await
+                .map_err(|_| DDAError::TimedOut)?,
+        };
+        let message = answer.ok_or(DDAError::ConnectionClosed)?;
+
+        if message
+            .message_type()
+            .is_specific_node_message(ProtocolError)
+        {
+            return Err(DDAError::ProtocolError(ProtocolErrorMessage::try_from(
+                message,
+            )?));
+        }
+
+        Ok(message.try_into()?)
+    }
+}
+
+/// Resolves a check file named by the node, refusing every file that is not directly inside the
+/// canonical `directory` under test
+async fn dda_file(directory: &Path, path: &Path) -> Result<PathBuf, DDAError> {
+    let outside = || DDAError::OutsideDirectory(path.into());
+    let canonical = match tokio::fs::canonicalize(path).await {
+        Ok(canonical) => canonical,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                return Err(outside());
+            };
+            match tokio::fs::canonicalize(parent).await {
+                Ok(parent) => parent.join(name),
+                Err(_) => return Err(outside()),
+            }
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    match canonical.parent() == Some(directory) {
+        true => Ok(canonical),
+        false => Err(outside()),
+    }
+}
+
+#[derive(Debug)]
+pub enum DDAError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node refused the test, e.g. as DDA is disabled for the directory
+    ProtocolError(ProtocolErrorMessage),
+    /// The node named a check file outside the tested directory
+    OutsideDirectory(Box<Path>),
+    /// The connection stopped before the test finished
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for DDAError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DDAError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            DDAError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            DDAError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            DDAError::OutsideDirectory(path) => {
+                write!(
+                    f,
+                    "Node named {} outside the tested directory",
+                    path.display()
+                )
+            }
+            DDAError::ConnectionClosed => {
+                write!(f, "Connection closed before the DDA test finished")
+            }
+            DDAError::TimedOut => write!(f, "DDA test timed out"),
+        }
+    }
+}
+
+impl Error for DDAError {}
+
+impl From<tokio::io::Error> for DDAError {
+    fn from(value: tokio::io::Error) -> Self {
+        DDAError::TokioIo(value)
+    }
+}
+
+impl From<DecodeError> for DDAError {
+    fn from(value: DecodeError) -> Self {
+        DDAError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::dda::{DDAError, DiskAccess};
+    use crate::messages::client_get::{ClientGetMessage, DiskClientGetMessage};
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::messages::protocol_error::ProtocolErrorMessage;
+    use crate::mock_node::fault::{MockBehaviour, MockFault};
+    use crate::mock_node::MockNode;
+    use crate::model::message_type_identifier::ClientMessageType;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::protocol_error_code::ProtocolErrorCode;
+    use crate::model::return_type::ReturnType;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::verbosity::Verbosity;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "mycelink-dda-{name}-{:016x}",
+            rand::random::<u64>()
+        ));
+        std::fs::create_dir(&directory).unwrap();
+        directory
+    }
+
+    #[tokio::test]
+    async fn test_disk_put_and_get() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("DDA test").await;
+        let directory = test_directory("put-get");
+        let upload = directory.join("upload.txt");
+        std::fs::write(&upload, b"Hello Disk").unwrap();
+
+        let put = ClientPutMessage {
+            uri: "KSK@disk".try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Disk put").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Disk {
+                path: upload.into(),
+            },
+            is_binary_blob: false,
+            real_time: false,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        };
+        let (response, _) = connector.request(&put, TIMEOUT).await.unwrap();
+        response.await.unwrap();
+
+        let download = directory.join("download.txt");
+        let get = DiskClientGetMessage(ClientGetMessage {
+            identifier: UniqueIdentifier::new("Disk get").unwrap(),
+            uri: "KSK@disk".try_into().unwrap(),
+            verbosity: Verbosity::default(),
+            return_type: ReturnType::Disk {
+                path: download.clone().into(),
+            },
+            max_size: None,
+            max_temp_size: None,
+            max_retries: 0,
+            priority: PriorityClass::High,
+            persistence: Persistence::Connection,
+            ignore_data_store: false,
+            data_store_only: false,
+            real_time: false,
+        });
+        let (response, _) = connector.request(&get, TIMEOUT).await.unwrap();
+        let data_found = response.await.unwrap();
+        assert_eq!(data_found.data_length, 10);
+        assert_eq!(std::fs::read(&download).unwrap(), b"Hello Disk");
+
+        // Only the uploaded and downloaded file are left, the node removed its check files
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 2);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dda_cached_per_directory() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("DDA test").await;
+        let directory = test_directory("cached");
+
+        let allowed = connector
+            .test_dda(&directory, DiskAccess::READ, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(allowed, DiskAccess::READ);
+
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::TestDDARequest,
+            uri: None,
+            behaviour: MockBehaviour::Fail {
+                code: ProtocolErrorCode::DirectDiskAccessDenied.into(),
+            },
+            times: None,
+        })
+        .await;
+        let cached = connector
+            .test_dda(&directory, DiskAccess::READ, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(cached, DiskAccess::READ);
+
+        let err = connector
+            .test_dda(&directory, DiskAccess::WRITE, TIMEOUT)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DDAError::ProtocolError(ProtocolErrorMessage {
+                code: ProtocolErrorCode::DirectDiskAccessDenied,
+                ..
+            })
+        ));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dda_refuses_files_outside_directory() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("DDA test").await;
+        let directory = test_directory("inside");
+        let outside = test_directory("outside");
+        node.misdirect_dda(&outside).await;
+
+        let err = connector
+            .test_dda(
+                &directory,
+                DiskAccess::READ.union(&DiskAccess::WRITE),
+                TIMEOUT,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DDAError::OutsideDirectory(_)));
+
+        // The client wrote no file, and the node never got a `TestDDAResponse`, which would have
+        // made it remove the file it wanted read
+        assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 1);
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(directory).unwrap();
+        std::fs::remove_dir_all(outside).unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::progress::ProgressEvent;
+use crate::fcp_connector::request::{FCPRequest, RequestError, Response};
+use crate::fcp_connector::FCPConnector;
+use crate::messages::put_failed::PutFailedMessage;
+use crate::messages::put_successful::PutSuccessfulMessage;
+use crate::messages::uri_generated::UriGeneratedMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::URIGenerated;
+use crate::model::uri::URI;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// What happened to a directory insert, see [DirectoryInsert]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DirectoryInsertEvent {
+    /// The URI of the manifest is known, the files are still being inserted
+    URIGenerated(URI),
+    Progress(ProgressEvent),
+    /// The manifest was inserted under this URI, always the last event
+    Finished(URI),
+}
+
+/// The events of a `ClientPutComplexDir` or `ClientPutDiskDir`, ending with the URI of the
+/// manifest or the error of the insert
+///
+/// Dropping it cancels the insert like dropping its [Response] does.
+pub struct DirectoryInsert<R: FCPRequest> {
+    /// Taken once the insert finished
+    response: Option<Response<R>>,
+    intermediate: UnboundedReceiver<Message>,
+}
+
+impl<R> DirectoryInsert<R>
+where
+    R: FCPRequest<Success = PutSuccessfulMessage, Failure = PutFailedMessage>,
+{
+    pub fn new(response: Response<R>, intermediate: UnboundedReceiver<Message>) -> Self {
+        Self {
+            response: Some(response),
+            intermediate,
+        }
+    }
+
+    /// Waits for the next event, [None] after the insert finished or failed
+    pub async fn next(
+        &mut self,
+    ) -> Option<Result<DirectoryInsertEvent, RequestError<PutFailedMessage>>> {
+        loop {
+            let response = self.response.as_mut()?;
+            // Intermediate messages are sent before the terminal one, so none is skipped
+            let message = tokio::select! {
+                biased;
+                Some(message) = self.intermediate.recv() => message,
+                res = response => {
+                    self.response = None;
+                    return Some(res.map(|e| DirectoryInsertEvent::Finished(e.uri)));
+                }
+            };
+
+            match event(message) {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => continue,
+                Err(err) => log::warn!("Skipping malformed insert event: {err}"),
+            }
+        }
+    }
+
+    /// Waits until the insert finished, skipping all other events
+    pub async fn finished(mut self) -> Result<URI, RequestError<PutFailedMessage>> {
+        while let Some(event) = self.next().await {
+            if let DirectoryInsertEvent::Finished(uri) = event? {
+                return Ok(uri);
+            }
+        }
+        Err(RequestError::ConnectionClosed)
+    }
+}
+
+fn event(message: Message) -> Result<Option<DirectoryInsertEvent>, DecodeError> {
+    let message_type = message.message_type();
+    if message_type == MessageType::Node(URIGenerated) {
+        let uri_generated = UriGeneratedMessage::try_from(message)?;
+        return Ok(Some(DirectoryInsertEvent::URIGenerated(uri_generated.uri)));
+    }
+    if ProgressEvent::is_progress_event(&message_type) {
+        return Ok(Some(DirectoryInsertEvent::Progress(message.try_into()?)));
+    }
+    Ok(None)
+}
+
+impl FCPConnector {
+    /// Starts a `ClientPutComplexDir` or `ClientPutDiskDir`, negotiating direct disk access to
+    /// the directories it reads from first
+    pub async fn put_directory<R>(
+        &self,
+        request: &R,
+        timeout: Option<Duration>,
+    ) -> Result<DirectoryInsert<R>, tokio::io::Error>
+    where
+        R: FCPRequest<Success = PutSuccessfulMessage, Failure = PutFailedMessage>,
+        for<'a> &'a R: Into<Message>,
+    {
+        let (response, intermediate) = self.request(request, timeout).await?;
+        Ok(DirectoryInsert::new(response, intermediate))
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::directory::DirectoryInsertEvent;
+    use crate::fcp_connector::progress::ProgressEvent;
+    use crate::messages::client_put_complex_dir::{
+        ClientPutComplexDirMessage, DirectoryEntry, DirectoryEntrySource,
+    };
+    use crate::messages::client_put_disk_dir::ClientPutDiskDirMessage;
+    use crate::mock_node::MockNode;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::verbosity::Verbosity;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    #[tokio::test]
+    async fn test_complex_dir_events_end_with_manifest() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Directory test").await;
+        node.insert("KSK@logo", b"PNG".as_slice()).await;
+
+        let direct = |name: &str, data: &[u8]| DirectoryEntry {
+            name: name.into(),
+            content_type: Some("text/plain".parse().unwrap()),
+            source: DirectoryEntrySource::Direct { data: data.into() },
+        };
+        let put = ClientPutComplexDirMessage {
+            uri: "CHK@".try_into().unwrap(),
+            identifier: UniqueIdentifier::new("Complex dir").unwrap(),
+            verbosity: Verbosity {
+                simple_progress: true,
+                ..Verbosity::default()
+            },
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            default_name: Some("index.html".into()),
+            files: vec![
+                direct("index.html", b"<h1>Help</h1>"),
+                direct("css/style.css", b"h1 {}"),
+                DirectoryEntry {
+                    name: "logo.png".into(),
+                    content_type: None,
+                    source: DirectoryEntrySource::Redirect {
+                        target: "KSK@logo".try_into().unwrap(),
+                    },
+                },
+            ],
+            real_time: false,
+        };
+
+        let mut insert = connector.put_directory(&put, TIMEOUT).await.unwrap();
+        let mut events = Vec::new();
+        while let Some(event) = insert.next().await {
+            events.push(event.unwrap());
+        }
+
+        let [DirectoryInsertEvent::URIGenerated(generated), DirectoryInsertEvent::Progress(ProgressEvent::SimpleProgress(_)), DirectoryInsertEvent::Finished(manifest)] =
+            events.as_slice()
+        else {
+            panic!("Unexpected events {events:?}");
+        };
+        assert_eq!(generated, manifest);
+
+        let manifest = manifest.to_string();
+        let manifest = manifest.trim_start_matches("freenet:");
+        assert_eq!(
+            node.get(&format!("{manifest}/css/style.css"))
+                .await
+                .as_deref(),
+            Some(b"h1 {}".as_slice())
+        );
+        assert_eq!(
+            node.get(&format!("{manifest}/logo.png")).await.as_deref(),
+            Some(b"PNG".as_slice())
+        );
+        assert_eq!(
+            node.get(manifest).await.as_deref(),
+            Some(b"<h1>Help</h1>".as_slice())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disk_dir_skips_hidden_files() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Directory test").await;
+        let directory =
+            std::env::temp_dir().join(format!("mycelink-disk-dir-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(directory.join("docs")).unwrap();
+        std::fs::write(directory.join("index.html"), b"Start").unwrap();
+        std::fs::write(directory.join("docs").join("faq.txt"), b"FAQ").unwrap();
+        std::fs::write(directory.join(".secret"), b"Hidden").unwrap();
+
+        let put = ClientPutDiskDirMessage {
+            uri: "KSK@help".try_into().unwrap(),
+            identifier: UniqueIdentifier::new("Disk dir").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            default_name: None,
+            directory: directory.clone().into(),
+            allow_unreadable_files: false,
+            include_hidden_files: false,
+            real_time: false,
+        };
+        let insert = connector.put_directory(&put, TIMEOUT).await.unwrap();
+        insert.finished().await.unwrap();
+
+        assert_eq!(
+            node.get("KSK@help/docs/faq.txt").await.as_deref(),
+            Some(b"FAQ".as_slice())
+        );
+        assert!(node.get("KSK@help/.secret").await.is_none());
+        assert!(node.get("KSK@help").await.is_none());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
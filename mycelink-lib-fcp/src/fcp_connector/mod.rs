@@ -0,0 +1,1655 @@
+pub mod chk;
+pub mod dda;
+pub mod directory;
+pub mod node;
+pub mod payload;
+pub mod peers;
+pub mod persistent;
+pub mod plugin;
+pub mod progress;
+pub mod reconnect;
+pub mod request;
+pub mod scheduler;
+pub mod subscription;
+pub mod transport;
+
+use crate::codec::{Frame, MAX_PAYLOAD_PREALLOCATION};
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::dda::TestedDirectory;
+use crate::fcp_connector::filters::MessageFilter;
+use crate::fcp_connector::payload::{payload_channel, PayloadReader};
+use crate::fcp_connector::persistent::is_persistent_request_notification;
+use crate::fcp_connector::reconnect::{ConnectionState, ReconnectPolicy};
+use crate::fcp_connector::request::{
+    terminal_response_kind, Cancellation, FCPRequest, PendingRequest, Response, TerminalMessage,
+};
+use crate::fcp_connector::scheduler::{RequestScheduler, SchedulerPermit, DEFAULT_CALLER};
+use crate::fcp_connector::transport::{split_transport, BoxedReader, BoxedWriter, FCPTransport};
+use crate::message_reader::MessageReader;
+use crate::messages::client_hello::{ClientHelloMessage, EXPECTED_VERSION};
+use crate::messages::get_request_status::GetRequestStatusMessage;
+use crate::messages::node_hello::NodeHelloMessage;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::model::fields::PAYLOAD_LENGTH_HINT_KEYS;
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    CloseConnectionDuplicateClientName, NodeHello, ProtocolError,
+};
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::redaction::TracedMessage;
+use crate::model::unique_identifier::UniqueIdentifier;
+use log::error;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ErrorKind};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::{broadcast, oneshot, watch, Mutex};
+use tokio::time::error::Elapsed;
+use tokio::time::Instant;
+
+pub struct FCPConnector {
+    tx: Mutex<BoxedWriter>,
+    rx: Mutex<MessageReader<BoxedReader>>,
+    client_name: Box<str>,
+    reconnect: Option<ReconnectPolicy>,
+    state: watch::Sender<ConnectionState>,
+
+    /// Requests by their `Identifier`, only locked without awaiting
+    requests: std::sync::Mutex<HashMap<Box<str>, PendingRequest>>,
+    /// Requests whose [Response] timed out or was dropped, removed by [FCPConnector::listen]
+    cancel_tx: UnboundedSender<Cancellation>,
+    cancel_rx: Mutex<UnboundedReceiver<Cancellation>>,
+    listeners: Mutex<Vec<Listener>>,
+    /// Held while waiting for the answer to a query about persistent requests
+    persistent_query: Mutex<()>,
+    /// Directories tested with [FCPConnector::test_dda] on the current connection
+    dda_directories: std::sync::Mutex<HashMap<Box<Path>, TestedDirectory>>,
+    /// Held during a `TestDDA` handshake, as its messages carry no `Identifier`
+    dda_handshake: Mutex<()>,
+    /// Sent by the node after every `ClientHello`
+    node_hello: watch::Sender<Option<NodeHelloMessage>>,
+    /// `ProtocolError`s without an `Identifier` or whose request is not known
+    protocol_errors: broadcast::Sender<ProtocolErrorMessage>,
+    scheduler: RequestScheduler,
+    /// Logs messages with private keys and payloads, see [FCPConnector::set_unredacted_tracing]
+    unredacted_tracing: AtomicBool,
+}
+
+/// Errors kept for [FCPConnector::protocol_errors] subscribers which fall behind
+const PROTOCOL_ERROR_CAPACITY: usize = 32;
+
+impl FCPConnector {
+    pub async fn new(
+        transport: impl FCPTransport,
+        client_name: &str,
+    ) -> Result<Self, tokio::io::Error> {
+        let (rx, tx) = split_transport(transport);
+        Self::with_halves(rx, tx, client_name, None).await
+    }
+
+    /// Speaks FCP over separate streams for reading and writing
+    pub async fn from_halves(
+        rx: impl AsyncRead + Send + Unpin + 'static,
+        tx: impl AsyncWrite + Send + Unpin + 'static,
+        client_name: &str,
+    ) -> Result<Self, tokio::io::Error> {
+        Self::with_halves(Box::new(rx), Box::new(tx), client_name, None).await
+    }
+
+    /// Connects with `reconnect` and uses it again whenever the connection is lost
+    ///
+    /// Requests running while the connection is lost fail with
+    /// [request::RequestError::ConnectionClosed], as the node forgets connection scoped requests.
+    /// Persistent requests and USK subscriptions are picked up again on the new connection,
+    /// requests sent while reconnecting wait for it.
+    pub async fn new_reconnecting(
+        client_name: &str,
+        reconnect: ReconnectPolicy,
+    ) -> Result<Self, tokio::io::Error> {
+        let (rx, tx) = split_transport((reconnect.connect)().await?);
+        Self::with_halves(rx, tx, client_name, Some(reconnect)).await
+    }
+
+    async fn with_halves(
+        rx: BoxedReader,
+        tx: BoxedWriter,
+        client_name: &str,
+        reconnect: Option<ReconnectPolicy>,
+    ) -> Result<Self, tokio::io::Error> {
+        let rx = MessageReader::new(rx);
+        let (cancel_tx, cancel_rx) = unbounded_channel();
+
+        let s = Self {
+            tx: Mutex::new(tx),
+            rx: Mutex::new(rx),
+            client_name: client_name.into(),
+            reconnect,
+            state: watch::Sender::new(ConnectionState::Connected),
+            requests: std::sync::Mutex::new(HashMap::new()),
+            cancel_tx,
+            cancel_rx: Mutex::new(cancel_rx),
+            listeners: Mutex::new(Vec::new()),
+            persistent_query: Mutex::new(()),
+            dda_directories: std::sync::Mutex::new(HashMap::new()),
+            dda_handshake: Mutex::new(()),
+            node_hello: watch::Sender::new(None),
+            protocol_errors: broadcast::Sender::new(PROTOCOL_ERROR_CAPACITY),
+            scheduler: RequestScheduler::new(),
+            unredacted_tracing: AtomicBool::new(false),
+        };
+
+        log::info!("Connecting to Freenet over FCP");
+        s.send_client_hello().await?;
+
+        Ok(s)
+    }
+
+    async fn send_client_hello(&self) -> Result<(), tokio::io::Error> {
+        self.send(self.client_hello()).await
+    }
+
+    fn client_hello(&self) -> ClientHelloMessage {
+        ClientHelloMessage {
+            name: self.client_name.clone(),
+            version: EXPECTED_VERSION,
+        }
+    }
+
+    /// Follows the state of the connection, which changes while [FCPConnector::listen] runs
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// `ProtocolError`s the node sent without an `Identifier`, or for a request that is not
+    /// known; errors of running requests end up in their [Response] instead
+    pub fn protocol_errors(&self) -> broadcast::Receiver<ProtocolErrorMessage> {
+        self.protocol_errors.subscribe()
+    }
+
+    /// Logs sent and received messages including insert keys, KSK names and payloads
+    ///
+    /// Anyone reading such logs can act as the account of the user, so this is only meant to debug
+    /// the protocol.
+    pub fn set_unredacted_tracing(&self, enabled: bool) {
+        self.unredacted_tracing.store(enabled, Ordering::Relaxed);
+    }
+
+    fn traced<'a>(&self, message: &'a Message) -> TracedMessage<'a> {
+        message.traced(self.unredacted_tracing.load(Ordering::Relaxed))
+    }
+
+    pub async fn listen(&self) {
+        let mut rx = self
+            .rx
+            .try_lock()
+            .expect("FCPConnector::listen my only be called once per struct");
+        let mut cancelled = self.cancel_rx.lock().await;
+        loop {
+            let frame = tokio::select! {
+                frame = rx.read_frame() => frame,
+                Some(cancellation) = cancelled.recv() => {
+                    self.cancel_request(cancellation).await;
+                    continue;
+                }
+            };
+
+            let decoded = match frame {
+                Ok(Frame::Message(message)) => Ok(message),
+                Ok(Frame::PayloadStart {
+                    message,
+                    data_len_identifier,
+                    len,
+                }) => match self
+                    .receive_payload(&mut rx, message, data_len_identifier, len)
+                    .await
+                {
+                    Ok(Some(message)) => Ok(message),
+                    Ok(None) => continue,
+                    Err(err) => Err(err),
+                },
+                Ok(Frame::PayloadChunk(_) | Frame::PayloadEnd) => {
+                    unreachable!("Payloads are read by FCPConnector::receive_payload")
+                }
+                Err(err) => Err(err),
+            };
+
+            match decoded {
+                Ok(message)
+                    if message
+                        .message_type()
+                        .is_specific_node_message(CloseConnectionDuplicateClientName) =>
+                {
+                    // Reconnecting would only disconnect the other client in turn
+                    self.close("Another client connected with the same name");
+                    return;
+                }
+                Ok(message) if message.message_type().is_specific_node_message(NodeHello) => {
+                    self.receive_node_hello(message);
+                }
+                Ok(message) => {
+                    let fatal = is_fatal_protocol_error(&message);
+                    if let Err(err) = self.handle_message(message).await {
+                        error!("Received error while handling message {err}")
+                    }
+                    // The node closes the connection after a fatal error
+                    if fatal && !self.connection_lost(&mut rx, "Fatal ProtocolError").await {
+                        return;
+                    }
+                }
+                Err(err) if err.is_recoverable() => {
+                    log::warn!("Skipped malformed message {err}");
+                }
+                Err(err) => {
+                    if !self.connection_lost(&mut rx, &err.to_string()).await {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fails the requests scoped to the connection and reconnects if configured, closes the
+    /// connector otherwise
+    ///
+    /// Returns whether listening continues on a new connection.
+    async fn connection_lost(&self, rx: &mut MessageReader<BoxedReader>, reason: &str) -> bool {
+        // Nothing is sent until the connection has been replaced, so no request is lost on the
+        // old one
+        let mut tx = self.tx.lock().await;
+        let reattach = self.drop_connection_requests();
+        // The node only grants direct disk access to the connection that tested it
+        self.dda_directories.lock().unwrap().clear();
+
+        let reconnected = match &self.reconnect {
+            None => false,
+            Some(policy) => {
+                log::warn!("Lost connection to node ({reason}), reconnecting");
+                self.reconnect(rx, &mut tx, policy).await
+            }
+        };
+        if !reconnected {
+            error!("Stopped listening after losing the connection ({reason})");
+            self.close(reason);
+            return false;
+        }
+
+        drop(tx);
+        for message in reattach {
+            if let Err(err) = self.send(message).await {
+                error!("Failed to pick up request after reconnect {err}");
+            }
+        }
+        true
+    }
+
+    /// Resolves the requests scoped to the lost connection as closed, returns the messages
+    /// picking up the others on a new connection
+    fn drop_connection_requests(&self) -> Vec<Message> {
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, request| request.reattach.is_some());
+        requests
+            .values()
+            .filter_map(|request| request.reattach.clone())
+            .collect()
+    }
+
+    /// Replaces the connection with a new one, returns whether that succeeded
+    async fn reconnect(
+        &self,
+        rx: &mut MessageReader<BoxedReader>,
+        tx: &mut BoxedWriter,
+        policy: &ReconnectPolicy,
+    ) -> bool {
+        let mut attempt = 0;
+        loop {
+            self.state
+                .send_replace(ConnectionState::Reconnecting { attempt });
+            if policy
+                .max_attempts
+                .map(|max| attempt >= max)
+                .unwrap_or(false)
+            {
+                return false;
+            }
+            if attempt > 0 {
+                tokio::time::sleep(policy.backoff(attempt - 1)).await;
+            }
+
+            match (policy.connect)().await {
+                Ok(transport) => {
+                    let (new_rx, new_tx) = split_transport(transport);
+                    *rx = MessageReader::new(new_rx);
+                    *tx = new_tx;
+
+                    let client_hello: Message = self.client_hello().into();
+                    match client_hello.write_to(tx).await {
+                        Ok(()) => {
+                            log::info!("Reconnected to node");
+                            self.state.send_replace(ConnectionState::Connected);
+                            return true;
+                        }
+                        Err(err) => log::warn!("Failed to send ClientHello after reconnect {err}"),
+                    }
+                }
+                Err(err) => log::warn!("Reconnect attempt {attempt} failed {err}"),
+            }
+
+            attempt += 1;
+        }
+    }
+
+    fn close(&self, reason: &str) {
+        // Changed together, so no request is added after the others were resolved as closed
+        let mut requests = self.requests.lock().unwrap();
+        requests.clear();
+        self.state.send_replace(ConnectionState::Closed {
+            reason: reason.into(),
+        });
+        drop(requests);
+        self.dda_directories.lock().unwrap().clear();
+    }
+
+    /// Reads the payload following `message`, streaming it to the request waiting for it or
+    /// collecting it otherwise
+    ///
+    /// Returns the message including its payload if it has been collected.
+    async fn receive_payload(
+        &self,
+        rx: &mut MessageReader<BoxedReader>,
+        message: Message,
+        data_len_identifier: Box<str>,
+        len: usize,
+    ) -> Result<Option<Message>, DecodeError> {
+        if !self.streams_payload(&message) {
+            let mut data = Vec::with_capacity(len.min(MAX_PAYLOAD_PREALLOCATION));
+            while let Frame::PayloadChunk(chunk) = rx.read_frame().await? {
+                data.extend_from_slice(&chunk);
+            }
+
+            let payload = MessagePayload {
+                data: data.into(),
+                data_len_identifier,
+            };
+            return Ok(Some(message.with_payload(payload)));
+        }
+
+        log::debug!("Streaming payload of message {:?}", self.traced(&message));
+        let identifier: Box<str> = match message.fields().get("Identifier") {
+            Some(identifier) => identifier.value().into(),
+            None => "".into(),
+        };
+        let (mut chunks, reader) = payload_channel(len);
+        self.dispatch_request(message, Some(reader));
+
+        while let Frame::PayloadChunk(chunk) = rx.read_frame().await? {
+            if !chunks.send(chunk) {
+                log::warn!("Skipping the payload of request {identifier} as it is not read");
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn streams_payload(&self, message: &Message) -> bool {
+        let Some(identifier) = message.fields().get("Identifier") else {
+            return false;
+        };
+
+        self.requests
+            .lock()
+            .unwrap()
+            .get(identifier.value())
+            .map(|request| request.streams_payload(&message.message_type()))
+            .unwrap_or(false)
+    }
+
+    async fn handle_message(&self, message: Message) -> Result<(), Infallible> {
+        log::debug!("Received message {:?}", self.traced(&message));
+        if is_persistent_request_notification(&message.message_type()) {
+            // Answers to queries about persistent requests, even if the request itself runs
+            let Some(message) = self.notify_listeners(message).await else {
+                return Ok(());
+            };
+            if let Some(message) = self.dispatch_request(message, None) {
+                self.unhandled(message);
+            }
+            return Ok(());
+        }
+
+        let Some(message) = self.dispatch_request(message, None) else {
+            return Ok(());
+        };
+        if let Some(message) = self.notify_listeners(message).await {
+            self.unhandled(message);
+        }
+
+        Ok(())
+    }
+
+    /// Publishes `ProtocolError`s no request or listener waited for on [Self::protocol_errors]
+    fn unhandled(&self, message: Message) {
+        if !message
+            .message_type()
+            .is_specific_node_message(ProtocolError)
+        {
+            log::warn!("Received Message with no listener for it {message:?}");
+            return;
+        }
+        match ProtocolErrorMessage::try_from(message) {
+            Ok(protocol_error) => {
+                log::warn!("Node reported a ProtocolError {protocol_error:?}");
+                // Nobody subscribed to the errors
+                let _ = self.protocol_errors.send(protocol_error);
+            }
+            Err(err) => log::warn!("Received invalid ProtocolError: {err}"),
+        }
+    }
+
+    /// Hands the message to the first matching listener, or back if there is none
+    async fn notify_listeners(&self, message: Message) -> Option<Message> {
+        let mut has_marked_for_delete = false;
+        let mut listeners = self.listeners.lock().await;
+        let listener = listeners
+            .iter_mut()
+            .inspect(|e| {
+                has_marked_for_delete |= e.is_marked_for_delete();
+            }) // Detect old listeners
+            .filter(|e| !e.is_marked_for_delete())
+            .find(|e| e.filter(&message));
+
+        let unhandled = match listener {
+            Some(listener) => {
+                listener.action(message);
+                None
+            }
+            None => Some(message),
+        };
+
+        if has_marked_for_delete {
+            listeners.retain(|e| !e.is_marked_for_delete())
+        }
+
+        unhandled
+    }
+
+    /// Routes the message to the request with its `Identifier`, or hands it back if there is none
+    fn dispatch_request(
+        &self,
+        message: Message,
+        payload: Option<PayloadReader>,
+    ) -> Option<Message> {
+        let identifier = match message.fields().get("Identifier") {
+            None => return Some(message),
+            Some(identifier) => identifier.value(),
+        };
+
+        let mut requests = self.requests.lock().unwrap();
+        let Some(request) = requests.remove(identifier) else {
+            return Some(message);
+        };
+
+        let identifier = identifier.into();
+        if let Some(request) = request.dispatch(message, payload) {
+            requests.insert(identifier, request);
+        }
+
+        None
+    }
+
+    /// Sends the request and routes every answer carrying its `Identifier` to the returned
+    /// [Response] (terminal message) or receiver (intermediate messages).
+    ///
+    /// If no terminal message arrived within `timeout`, the [Response] resolves to
+    /// [request::RequestError::TimedOut] and the request is removed from the node. The time spent
+    /// waiting for the scheduler counts towards `timeout`, a request still waiting when it passes
+    /// is never sent.
+    ///
+    /// Direct disk access needed by the request is negotiated first, failing with
+    /// [ErrorKind::PermissionDenied] if the node refuses it. The request is only sent once the
+    /// [FCPConnector::scheduler] has a free slot for it.
+    pub async fn request<R: FCPRequest>(
+        &self,
+        request: &R,
+        timeout: Option<Duration>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error>
+    where
+        for<'a> &'a R: Into<Message>,
+    {
+        self.request_as(DEFAULT_CALLER, request, timeout).await
+    }
+
+    /// Like [FCPConnector::request], but shares the slots of the scheduler fairly with requests
+    /// of other callers
+    pub async fn request_as<R: FCPRequest>(
+        &self,
+        caller: &str,
+        request: &R,
+        timeout: Option<Duration>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error>
+    where
+        for<'a> &'a R: Into<Message>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.prepare_disk_access(request, timeout).await?;
+        let (message, payload) = match request.split_payload() {
+            Some((message, payload)) => (message, Some(payload)),
+            None => (request.into(), None),
+        };
+        let Ok(permit) = self
+            .schedule(&message, request.priority(), caller, deadline)
+            .await
+        else {
+            let (_, intermediate_rx) = unbounded_channel();
+            return Ok((
+                Response::timed_out(request.identifier().clone()),
+                intermediate_rx,
+            ));
+        };
+        let send = async {
+            match payload {
+                None => self.send(message).await,
+                Some(payload) => {
+                    self.send_with_payload(message, payload, payload.len() as u64)
+                        .await
+                }
+            }
+        };
+        self.start_request(
+            request.identifier().clone(),
+            reattach_persistent(request),
+            deadline,
+            permit,
+            send,
+        )
+        .await
+    }
+
+    /// Like [FCPConnector::request], but the request is followed by `len` bytes read from
+    /// `payload`, see [FCPConnector::send_with_payload]
+    pub async fn request_with_payload<R: FCPRequest>(
+        &self,
+        request: &R,
+        payload: impl AsyncRead + Unpin,
+        len: u64,
+        timeout: Option<Duration>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error>
+    where
+        for<'a> &'a R: Into<Message>,
+    {
+        self.request_with_payload_as(DEFAULT_CALLER, request, payload, len, timeout)
+            .await
+    }
+
+    /// Like [FCPConnector::request_with_payload], scheduled as [FCPConnector::request_as]
+    pub async fn request_with_payload_as<R: FCPRequest>(
+        &self,
+        caller: &str,
+        request: &R,
+        payload: impl AsyncRead + Unpin,
+        len: u64,
+        timeout: Option<Duration>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error>
+    where
+        for<'a> &'a R: Into<Message>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.prepare_disk_access(request, timeout).await?;
+        let message: Message = request.into();
+        let Ok(permit) = self
+            .schedule(&message, request.priority(), caller, deadline)
+            .await
+        else {
+            let (_, intermediate_rx) = unbounded_channel();
+            return Ok((
+                Response::timed_out(request.identifier().clone()),
+                intermediate_rx,
+            ));
+        };
+        self.start_request(
+            request.identifier().clone(),
+            reattach_persistent(request),
+            deadline,
+            permit,
+            self.send_with_payload(message, payload, len),
+        )
+        .await
+    }
+
+    /// Limits and queue depth of the requests sent with [FCPConnector::request], no type is
+    /// limited unless configured
+    pub fn scheduler(&self) -> &RequestScheduler {
+        &self.scheduler
+    }
+
+    /// Waits for a slot of the scheduler until `deadline`
+    async fn schedule(
+        &self,
+        message: &Message,
+        priority: PriorityClass,
+        caller: &str,
+        deadline: Option<Instant>,
+    ) -> Result<Option<SchedulerPermit>, Elapsed> {
+        let MessageType::Client(message_type) = message.message_type() else {
+            return Ok(None);
+        };
+        let permit = self.scheduler.acquire(message_type, priority, caller);
+        match deadline {
+            None => Ok(Some(permit.await)),
+            Some(deadline) => tokio::time::timeout_at(deadline, permit).await.map(Some),
+        }
+    }
+
+    /// Follows a persistent request of type `R` started earlier, e.g. before a restart
+    ///
+    /// The node is asked for the status of the request, so a [Response] of a request that already
+    /// finished resolves right away. Dropping the [Response] keeps the request on the node.
+    pub async fn resume_request<R: FCPRequest>(
+        &self,
+        identifier: UniqueIdentifier,
+        global: bool,
+        timeout: Option<Duration>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error> {
+        let get_request_status: Message = GetRequestStatusMessage {
+            identifier: identifier.clone(),
+            global,
+            only_data: false,
+        }
+        .into();
+        self.start_request(
+            identifier,
+            Some(get_request_status.clone()),
+            timeout.map(|timeout| Instant::now() + timeout),
+            None,
+            self.send(get_request_status),
+        )
+        .await
+    }
+
+    /// Starts routing the answers to `identifier` to the returned [Response], requests with a
+    /// `reattach` message are persistent
+    async fn start_request<R: FCPRequest>(
+        &self,
+        identifier: UniqueIdentifier,
+        reattach: Option<Message>,
+        deadline: Option<Instant>,
+        permit: Option<SchedulerPermit>,
+        send: impl Future<Output = Result<(), tokio::io::Error>>,
+    ) -> Result<(Response<R>, UnboundedReceiver<Message>), tokio::io::Error> {
+        let (terminal_tx, terminal_rx) = oneshot::channel();
+        let (intermediate_tx, intermediate_rx) = unbounded_channel();
+
+        let persistent = reattach.is_some();
+        let pending = PendingRequest {
+            response_kind: terminal_response_kind::<R>,
+            streams_payload: R::Success::STREAMS_PAYLOAD,
+            terminal: terminal_tx,
+            intermediate: intermediate_tx,
+            reattach,
+        };
+        self.insert_request(&identifier, pending, send).await?;
+
+        let response = Response::new(
+            identifier,
+            terminal_rx,
+            deadline,
+            self.cancel_tx.clone(),
+            persistent,
+            permit,
+        );
+        Ok((response, intermediate_rx))
+    }
+
+    /// Routes the answers to `identifier` to `pending` and then sends the request with `send`
+    async fn insert_request(
+        &self,
+        identifier: &UniqueIdentifier,
+        pending: PendingRequest,
+        send: impl Future<Output = Result<(), tokio::io::Error>>,
+    ) -> Result<(), tokio::io::Error> {
+        let key: Box<str> = identifier.into();
+        {
+            let mut requests = self.requests.lock().unwrap();
+            if let ConnectionState::Closed { reason } = &*self.state.borrow() {
+                return Err(tokio::io::Error::new(
+                    ErrorKind::NotConnected,
+                    format!("The connection to the node is closed: {reason}"),
+                ));
+            }
+            if requests.contains_key(&key) {
+                return Err(tokio::io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("A request with identifier {key} is already running"),
+                ));
+            }
+            requests.insert(key.clone(), pending);
+        }
+
+        if let Err(err) = send.await {
+            self.requests.lock().unwrap().remove(&key);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Stops routing messages to the request and stops it on the node if requested, unless it
+    /// already finished
+    async fn cancel_request(&self, cancellation: Cancellation) {
+        let identifier = cancellation.identifier;
+        let key: Box<str> = (&identifier).into();
+        if self.requests.lock().unwrap().remove(&key).is_none() {
+            return;
+        }
+        let Some(stop) = cancellation.stop else {
+            return;
+        };
+
+        log::debug!("Cancelling request {identifier}");
+        if let Err(err) = self.send(stop).await {
+            error!("Failed to stop request {identifier} {err}");
+        }
+    }
+
+    pub async fn add_listener(&self, listener: Listener) {
+        let mut cursor = 0;
+        let mut listeners = self.listeners.lock().await;
+        while cursor < listeners.len() && listeners[cursor].priority() < listener.priority() {
+            cursor += 1;
+        }
+        listeners.insert(cursor, listener);
+    }
+
+    /// Sends `message` and returns all following messages matching the `filters`, ahead of other
+    /// listeners, until the receiver is dropped
+    pub(super) async fn query(
+        &self,
+        filters: Vec<Box<MessageFilter>>,
+        message: Message,
+    ) -> Result<UnboundedReceiver<Message>, tokio::io::Error> {
+        // Checked before all other listeners, so no answer is taken by them
+        let (listener, answers) = Listener::new(filters, i8::MIN);
+        self.add_listener(listener).await;
+        self.send(message).await?;
+
+        Ok(answers)
+    }
+
+    /// Waits for the next answer to a [FCPConnector::query] until `deadline`
+    pub(super) async fn next_answer(
+        &self,
+        answers: &mut UnboundedReceiver<Message>,
+        deadline: Option<Instant>,
+    ) -> Result<Message, NoAnswer> {
+        let mut state = self.connection_state();
+        let answer = async {
+            tokio::select! {
+                answer = answers.recv() => answer,
+                // The answer was lost with the connection
+                _ = state.wait_for(|state| *state != ConnectionState::Connected) => None,
+            }
+        };
+
+        let answer = match deadline {
+            None => answer.await,
+            Some(deadline) => tokio::time::timeout_at(deadline, answer)
+                .await
+                .map_err(|_| NoAnswer::TimedOut)?,
+        };
+        answer.ok_or(NoAnswer::ConnectionClosed)
+    }
+
+    pub async fn send(&self, message: impl Into<Message>) -> Result<(), tokio::io::Error> {
+        let message = message.into();
+        log::debug!("Send Message {:?}", self.traced(&message));
+        let mut tx = self.tx.lock().await;
+        Ok(message.write_to(&mut *tx).await?)
+    }
+
+    /// Sends `message` followed by exactly `len` bytes read from `payload`, without holding more
+    /// than a small buffer of it in memory
+    ///
+    /// Other messages are sent once the payload has been. If reading `payload` fails or it ends
+    /// early, the connection is shut down, as the node would wait for the missing bytes forever.
+    pub async fn send_with_payload(
+        &self,
+        message: impl Into<Message>,
+        payload: impl AsyncRead + Unpin,
+        len: u64,
+    ) -> Result<(), tokio::io::Error> {
+        let message = message.into();
+        log::debug!(
+            "Send Message {:?} with payload of {len} bytes",
+            self.traced(&message)
+        );
+        let mut header = Vec::new();
+        message.encode_payload_header_into(PAYLOAD_LENGTH_HINT_KEYS[0], len, &mut header)?;
+
+        let mut tx = self.tx.lock().await;
+        tx.write_all(header.as_slice()).await?;
+
+        match tokio::io::copy(&mut payload.take(len), &mut *tx).await {
+            Ok(written) if written == len => Ok(()),
+            res => {
+                let _ = tx.shutdown().await;
+                Err(res.err().unwrap_or_else(|| {
+                    tokio::io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Payload ended before the announced length",
+                    )
+                }))
+            }
+        }
+    }
+}
+
+/// Whether `message` is a `ProtocolError` after which the node closes the connection
+fn is_fatal_protocol_error(message: &Message) -> bool {
+    message
+        .message_type()
+        .is_specific_node_message(ProtocolError)
+        && message
+            .fields()
+            .get("Fatal")
+            .is_some_and(|fatal| fatal.value().eq_ignore_ascii_case("true"))
+}
+
+/// Asks for the status of `request` after a reconnect if the node keeps it across connections
+fn reattach_persistent<R: FCPRequest>(request: &R) -> Option<Message> {
+    if request.persistence() == Persistence::Connection {
+        return None;
+    }
+
+    let get_request_status = GetRequestStatusMessage {
+        identifier: request.identifier().clone(),
+        global: false,
+        only_data: false,
+    };
+    Some(get_request_status.into())
+}
+
+/// Why [FCPConnector::next_answer] returned without an answer
+pub(super) enum NoAnswer {
+    /// The connection stopped before the query was answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+pub struct Listener {
+    filters: Vec<Box<MessageFilter>>,
+    priority: i8,
+    notify: UnboundedSender<Message>,
+    marked_for_deletion: bool,
+}
+
+impl Listener {
+    pub const DEFAULT_PRIORITY: i8 = 0;
+
+    pub fn new(
+        filters: Vec<Box<MessageFilter>>,
+        priority: i8,
+    ) -> (Self, UnboundedReceiver<Message>) {
+        let (notify, receiver) = unbounded_channel();
+        (
+            Self {
+                filters,
+                priority,
+                notify,
+                marked_for_deletion: false,
+            },
+            receiver,
+        )
+    }
+
+    pub fn priority(&self) -> i8 {
+        self.priority
+    }
+
+    pub fn filter(&self, message: &Message) -> bool {
+        !self.marked_for_deletion
+            && self.filters.iter().filter(|e| (*e)(message)).count() == self.filters.len()
+    }
+
+    pub fn action(&mut self, message: Message) {
+        let _ = self.notify.send(message);
+    }
+
+    pub fn is_marked_for_delete(&self) -> bool {
+        self.notify.is_closed()
+    }
+}
+
+pub mod filters {
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::{MessageType, NodeMessageType};
+    use crate::model::unique_identifier::UniqueIdentifier;
+
+    pub type MessageFilter = dyn (Fn(&Message) -> bool) + Send;
+
+    pub fn identity_filter(identity: UniqueIdentifier) -> Box<MessageFilter> {
+        Box::new(move |message| {
+            message
+                .fields()
+                .get("Identifier")
+                .and_then(|identity_field| identity_field.value().try_into().ok())
+                .map(|identifier: UniqueIdentifier| identifier == identity)
+                .unwrap_or(false)
+        })
+    }
+
+    pub fn type_filter(aim_message_type: NodeMessageType) -> Box<MessageFilter> {
+        Box::new(move |message| {
+            if let MessageType::Node(message_type) = message.message_type() {
+                message_type == aim_message_type
+            } else {
+                false
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::progress::{Progress, ProgressEvent};
+    use crate::fcp_connector::reconnect::{ConnectionState, ReconnectPolicy};
+    use crate::fcp_connector::request::{FCPRequest, RequestError};
+    use crate::fcp_connector::transport::BoxedTransport;
+    use crate::fcp_connector::FCPConnector;
+    use crate::messages::client_get::{ClientGetMessage, StreamedClientGetMessage};
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::messages::generate_ssk::GenerateSSKMessage;
+    use crate::mock_node::fault::{MockBehaviour, MockFault};
+    use crate::mock_node::MockNode;
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::ClientMessageType;
+    use crate::model::message_type_identifier::MessageType::{Client, Node};
+    use crate::model::message_type_identifier::NodeMessageType::DataFound;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::protocol_error_code::ProtocolErrorCode;
+    use crate::model::return_type::ReturnType;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::verbosity::Verbosity;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Sent by the node before it closes the connection
+    const FATAL_PROTOCOL_ERROR: &[u8] = b"ProtocolError\nCode=1\n\
+        CodeDescription=Client hello must be first\nFatal=true\nGlobal=false\nEndMessage\n";
+
+    /// Connects to `node` again whenever the connection is lost, each attempt takes at least
+    /// `connect_delay`
+    async fn reconnecting_connector(node: &MockNode, connect_delay: Duration) -> Arc<FCPConnector> {
+        let address = node.address();
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(100),
+            max_attempts: Some(10),
+            ..ReconnectPolicy::new(Box::new(move || {
+                Box::pin(async move {
+                    tokio::time::sleep(connect_delay).await;
+                    let stream = TcpStream::connect(address).await?;
+                    Ok(Box::new(stream) as BoxedTransport)
+                })
+            }))
+        };
+        let connector = Arc::new(
+            FCPConnector::new_reconnecting("Reconnect test", policy)
+                .await
+                .unwrap(),
+        );
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+        connector
+    }
+
+    async fn drop_gets(node: &MockNode) {
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientGet,
+            uri: None,
+            behaviour: MockBehaviour::Drop,
+            times: None,
+        })
+        .await;
+    }
+
+    async fn wait_for_removed(node: &MockNode, identifier: &UniqueIdentifier) -> bool {
+        let identifier: Box<str> = identifier.into();
+        for _ in 0..50 {
+            if node.removed_requests().await.contains(&identifier) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    /// Waits for a full round trip, so the node has seen everything sent before
+    async fn generate_ssk(connector: &FCPConnector) {
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector.request(&generate, None).await.unwrap();
+        assert!(response.await.is_ok());
+    }
+
+    fn client_get(uri: &str) -> ClientGetMessage {
+        ClientGetMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+            uri: uri.try_into().unwrap(),
+            verbosity: Verbosity::default(),
+            return_type: ReturnType::Direct,
+            max_size: None,
+            max_temp_size: None,
+            max_retries: 0,
+            priority: PriorityClass::High,
+            persistence: Persistence::Connection,
+            ignore_data_store: false,
+            data_store_only: false,
+            real_time: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_success_with_intermediate() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("KSK@request-test", b"Hello World".as_slice())
+            .await;
+        let connector = node.connector("Request test").await;
+
+        let (response, mut intermediate) = connector
+            .request(&client_get("KSK@request-test"), None)
+            .await
+            .unwrap();
+
+        let all_data = response.await.unwrap();
+        assert_eq!(&*all_data.data, b"Hello World");
+        assert_eq!(
+            intermediate.recv().await.unwrap().message_type(),
+            Node(DataFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_failure() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+
+        let (response, _) = connector
+            .request(&client_get("KSK@request-missing"), None)
+            .await
+            .unwrap();
+
+        assert!(matches!(response.await, Err(RequestError::Failed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_request_protocol_error() {
+        let node = MockNode::start().await.unwrap();
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::GenerateSSK,
+            uri: None,
+            behaviour: MockBehaviour::Fail {
+                code: ProtocolErrorCode::NotSupported.into(),
+            },
+            times: Some(1),
+        })
+        .await;
+        let connector = node.connector("Request test").await;
+        let mut protocol_errors = connector.protocol_errors();
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector.request(&generate, None).await.unwrap();
+        let Err(RequestError::ProtocolError(protocol_error)) = response.await else {
+            panic!("Expected a ProtocolError");
+        };
+        assert_eq!(protocol_error.code, ProtocolErrorCode::NotSupported);
+        assert_eq!(protocol_error.identifier, Some(generate.identifier));
+        assert!(protocol_errors.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_protocol_error_without_identifier() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+        let mut protocol_errors = connector.protocol_errors();
+
+        let message = Message::new(
+            Client(ClientMessageType::TestDDAResponse),
+            Vec::new().into(),
+            None,
+        );
+        connector.send(message).await.unwrap();
+
+        let protocol_error = protocol_errors.recv().await.unwrap();
+        assert_eq!(protocol_error.code, ProtocolErrorCode::MissingField);
+        assert_eq!(protocol_error.identifier, None);
+        assert!(!protocol_error.fatal);
+    }
+
+    #[tokio::test]
+    async fn test_unread_responses_do_not_block() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("KSK@request-test", b"Hello World".as_slice())
+            .await;
+        let connector = node.connector("Request test").await;
+
+        // Neither the responses nor the intermediate messages of these are ever read
+        let mut unread = Vec::new();
+        for _ in 0..8 {
+            unread.push(
+                connector
+                    .request(&client_get("KSK@request-test"), None)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector.request(&generate, None).await.unwrap();
+        let keypair = response.await.unwrap();
+        assert_ne!(keypair.insert_uri, keypair.request_uri);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_identifier_is_rejected() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (_response, _) = connector.request(&generate, None).await.unwrap();
+        assert!(connector.request(&generate, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_limits_requests() {
+        let node = MockNode::start().await.unwrap();
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientGet,
+            uri: None,
+            behaviour: MockBehaviour::Delay(Duration::from_millis(100)),
+            times: None,
+        })
+        .await;
+        let connector = node.connector("Request test").await;
+        connector
+            .scheduler()
+            .set_limit(ClientMessageType::ClientGet, 1);
+
+        let (first, _) = connector
+            .request(&client_get("KSK@request-first"), None)
+            .await
+            .unwrap();
+        let queued_connector = connector.clone();
+        let second = tokio::spawn(async move {
+            let (response, _) = queued_connector
+                .request_as("poller", &client_get("KSK@request-second"), None)
+                .await
+                .unwrap();
+            response.await
+        });
+        while connector.scheduler().metrics().total_queued() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(
+            connector
+                .scheduler()
+                .metrics()
+                .queued_by_caller
+                .get("poller"),
+            Some(&1)
+        );
+
+        assert!(matches!(first.await, Err(RequestError::Failed(_))));
+        assert!(matches!(
+            second.await.unwrap(),
+            Err(RequestError::Failed(_))
+        ));
+        assert_eq!(connector.scheduler().metrics().total_running(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queued_request_times_out() {
+        let node = MockNode::start().await.unwrap();
+        drop_gets(&node).await;
+        let connector = node.connector("Request test").await;
+        connector
+            .scheduler()
+            .set_limit(ClientMessageType::ClientGet, 1);
+
+        let (_running, _) = connector
+            .request(&client_get("KSK@request-running"), None)
+            .await
+            .unwrap();
+        let queued = client_get("KSK@request-queued");
+        let (response, _) = connector
+            .request(&queued, Some(Duration::from_millis(50)))
+            .await
+            .unwrap();
+        assert!(matches!(response.await, Err(RequestError::TimedOut)));
+
+        let metrics = connector.scheduler().metrics();
+        assert_eq!(metrics.total_queued(), 0);
+        assert_eq!(metrics.total_running(), 1);
+        assert!(!wait_for_removed(&node, &queued.identifier).await);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_removes_request() {
+        let node = MockNode::start().await.unwrap();
+        drop_gets(&node).await;
+        let connector = node.connector("Request test").await;
+
+        let client_get = client_get("KSK@request-never");
+        let (response, _) = connector
+            .request(&client_get, Some(Duration::from_millis(50)))
+            .await
+            .unwrap();
+
+        assert!(matches!(response.await, Err(RequestError::TimedOut)));
+        assert!(wait_for_removed(&node, &client_get.identifier).await);
+    }
+
+    #[tokio::test]
+    async fn test_drop_removes_request() {
+        let node = MockNode::start().await.unwrap();
+        drop_gets(&node).await;
+        let connector = node.connector("Request test").await;
+
+        let client_get = client_get("KSK@request-never");
+        let (response, _) = connector.request(&client_get, None).await.unwrap();
+        drop(response);
+
+        assert!(wait_for_removed(&node, &client_get.identifier).await);
+    }
+
+    #[tokio::test]
+    async fn test_finished_request_is_not_removed() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector
+            .request(&generate, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        response.await.unwrap();
+
+        assert!(!wait_for_removed(&node, &generate.identifier).await);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_node_restart() {
+        let node = MockNode::start().await.unwrap();
+        let connector = reconnecting_connector(&node, Duration::ZERO).await;
+        let mut state = connector.connection_state();
+        state.borrow_and_update();
+
+        generate_ssk(&connector).await;
+
+        drop_gets(&node).await;
+        let (in_flight, _) = connector
+            .request(&client_get("KSK@request-never"), None)
+            .await
+            .unwrap();
+
+        node.disconnect_all().await;
+        assert!(matches!(
+            in_flight.await,
+            Err(RequestError::ConnectionClosed)
+        ));
+        state.changed().await.unwrap();
+        state
+            .wait_for(|e| e == &ConnectionState::Connected)
+            .await
+            .unwrap();
+
+        generate_ssk(&connector).await;
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_picks_up_persistent_request() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("KSK@request-persistent", b"Persistent".as_slice())
+            .await;
+        // Answered once the connection is gone, the node keeps the answer for the request
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientGet,
+            uri: None,
+            behaviour: MockBehaviour::Delay(Duration::from_millis(20)),
+            times: Some(1),
+        })
+        .await;
+        let connector = reconnecting_connector(&node, Duration::from_millis(100)).await;
+
+        let mut get = client_get("KSK@request-persistent");
+        get.identifier = UniqueIdentifier::new("Persistent test").unwrap();
+        get.persistence = Persistence::Forever;
+        let (response, _) = connector
+            .request(&get, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        generate_ssk(&connector).await;
+        node.disconnect_all().await;
+
+        let all_data = response.await.unwrap();
+        assert_eq!(&*all_data.data, b"Persistent");
+    }
+
+    #[tokio::test]
+    async fn test_request_waits_for_reconnect() {
+        let node = MockNode::start().await.unwrap();
+        let connector = reconnecting_connector(&node, Duration::from_millis(100)).await;
+        let mut state = connector.connection_state();
+        generate_ssk(&connector).await;
+
+        node.disconnect_all().await;
+        state
+            .wait_for(|e| matches!(e, ConnectionState::Reconnecting { .. }))
+            .await
+            .unwrap();
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector
+            .request(&generate, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert!(response.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_client_name_closes() {
+        let node = MockNode::start().await.unwrap();
+        let first = node.connector("Request test").await;
+        let mut state = first.connection_state();
+        generate_ssk(&first).await;
+
+        let _second = node.connector("Request test").await;
+
+        let closed = state
+            .wait_for(|e| matches!(e, ConnectionState::Closed { .. }))
+            .await;
+        assert!(closed.is_ok());
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        assert!(first.request(&generate, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fatal_protocol_error_closes() {
+        let (transport, mut node) = tokio::io::duplex(4096);
+        let connector = Arc::new(FCPConnector::new(transport, "Fatal test").await.unwrap());
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+        let mut state = connector.connection_state();
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector.request(&generate, None).await.unwrap();
+        node.write_all(FATAL_PROTOCOL_ERROR).await.unwrap();
+
+        assert!(matches!(
+            response.await,
+            Err(RequestError::ConnectionClosed)
+        ));
+        let closed = state
+            .wait_for(|e| matches!(e, ConnectionState::Closed { .. }))
+            .await;
+        assert!(closed.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_fatal_protocol_error_reconnects() {
+        let node = MockNode::start().await.unwrap();
+        let (transport, mut fake_node) = tokio::io::duplex(4096);
+        // The first connection is the fake node, reconnects go to the mock node
+        let first = std::sync::Mutex::new(Some(Box::new(transport) as BoxedTransport));
+        let address = node.address();
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..ReconnectPolicy::new(Box::new(move || {
+                let first = first.lock().unwrap().take();
+                Box::pin(async move {
+                    match first {
+                        Some(transport) => Ok(transport),
+                        None => Ok(Box::new(TcpStream::connect(address).await?) as BoxedTransport),
+                    }
+                })
+            }))
+        };
+        let connector = Arc::new(
+            FCPConnector::new_reconnecting("Fatal test", policy)
+                .await
+                .unwrap(),
+        );
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+        };
+        let (response, _) = connector.request(&generate, None).await.unwrap();
+        fake_node.write_all(FATAL_PROTOCOL_ERROR).await.unwrap();
+        assert!(matches!(
+            response.await,
+            Err(RequestError::ConnectionClosed)
+        ));
+
+        generate_ssk(&connector).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_transport() {
+        let node = MockNode::start().await.unwrap();
+        let connector = Arc::new(
+            FCPConnector::new(node.connect_in_memory(), "In memory test")
+                .await
+                .unwrap(),
+        );
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+
+        generate_ssk(&connector).await;
+    }
+
+    #[tokio::test]
+    async fn test_separate_halves() {
+        let node = MockNode::start().await.unwrap();
+        let (rx, tx) = node.connect().await.unwrap().into_split();
+        let connector = Arc::new(
+            FCPConnector::from_halves(rx, tx, "Halves test")
+                .await
+                .unwrap(),
+        );
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+
+        generate_ssk(&connector).await;
+    }
+
+    fn large_data() -> Vec<u8> {
+        (0..1024 * 1024).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_streamed_get() {
+        let node = MockNode::start().await.unwrap();
+        let data = large_data();
+        node.insert("KSK@stream-test", data.as_slice()).await;
+        let connector = node.connector("Request test").await;
+
+        let get = StreamedClientGetMessage(client_get("KSK@stream-test"));
+        let (response, _) = connector.request(&get, None).await.unwrap();
+        let mut all_data = response.await.unwrap();
+        assert_eq!(all_data.data.data_length(), data.len());
+
+        let mut received = Vec::new();
+        all_data.data.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, data);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_stream_is_skipped() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("KSK@stream-test", large_data()).await;
+        let connector = node.connector("Request test").await;
+
+        let get = StreamedClientGetMessage(client_get("KSK@stream-test"));
+        let (response, _) = connector.request(&get, None).await.unwrap();
+        drop(response.await.unwrap());
+
+        generate_ssk(&connector).await;
+    }
+
+    #[tokio::test]
+    async fn test_unread_stream_does_not_block() {
+        let node = MockNode::start().await.unwrap();
+        let data = large_data();
+        node.insert("KSK@stream-test", data.as_slice()).await;
+        let connector = node.connector("Request test").await;
+
+        let get = StreamedClientGetMessage(client_get("KSK@stream-test"));
+        let (response, _) = connector.request(&get, None).await.unwrap();
+        let mut all_data = response.await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), generate_ssk(&connector))
+            .await
+            .unwrap();
+
+        let mut received = Vec::new();
+        all_data.data.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, data);
+    }
+
+    #[tokio::test]
+    async fn test_request_with_payload() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+        let data = large_data();
+
+        let put = ClientPutMessage {
+            uri: "KSK@stream-put".try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Stream,
+            is_binary_blob: false,
+            real_time: true,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        };
+        let (response, _) = connector
+            .request_with_payload(&put, data.as_slice(), data.len() as u64, None)
+            .await
+            .unwrap();
+        response.await.unwrap();
+
+        assert_eq!(node.get("KSK@stream-put").await.unwrap().as_ref(), data);
+    }
+
+    #[tokio::test]
+    async fn test_put_progress() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Request test").await;
+
+        let data = vec![7; 3000];
+        let put = ClientPutMessage {
+            uri: "KSK@progress-put".try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Request test").unwrap(),
+            verbosity: Verbosity::all(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: false,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Stream,
+            is_binary_blob: false,
+            real_time: true,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        };
+        let (response, intermediate) = connector
+            .request_with_payload(&put, data.as_slice(), data.len() as u64, None)
+            .await
+            .unwrap();
+        response.await.unwrap();
+
+        let mut progress = Progress::new(intermediate);
+        let mut events = Vec::new();
+        while let Some(event) = progress.next().await {
+            assert_eq!(event.identifier(), put.identifier());
+            events.push(event);
+        }
+
+        assert!(matches!(events[0], ProgressEvent::StartedCompression(_)));
+        assert!(matches!(
+            &events[1],
+            ProgressEvent::FinishedCompression(e) if e.original_size == 3000
+        ));
+        let ProgressEvent::SimpleProgress(simple_progress) = &events[2] else {
+            panic!("Expected SimpleProgress, got {:?}", events[2]);
+        };
+        assert_eq!(simple_progress.total, 3);
+        assert_eq!(simple_progress.fraction(), 1.0);
+        assert!(matches!(events[3], ProgressEvent::SendingToNetwork(_)));
+        assert_eq!(events.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_get_progress() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("KSK@progress-get", b"Hello World".as_slice())
+            .await;
+        let connector = node.connector("Request test").await;
+
+        let mut get = client_get("KSK@progress-get");
+        get.verbosity = Verbosity::all();
+        let (response, intermediate) = connector.request(&get, None).await.unwrap();
+        response.await.unwrap();
+
+        let mut progress = Progress::new(intermediate);
+        assert!(matches!(
+            progress.next().await,
+            Some(ProgressEvent::ExpectedMIME(_))
+        ));
+        assert!(matches!(
+            progress.next().await,
+            Some(ProgressEvent::ExpectedDataLength(e)) if e.data_length == 11
+        ));
+        assert!(matches!(
+            progress.next().await,
+            Some(ProgressEvent::SimpleProgress(_))
+        ));
+        // DataFound is skipped
+        assert_eq!(progress.next().await, None);
+    }
+}
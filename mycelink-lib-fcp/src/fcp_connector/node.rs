@@ -0,0 +1,392 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::filters::{identity_filter, MessageFilter};
+use crate::fcp_connector::{FCPConnector, NoAnswer};
+use crate::messages::config_data::ConfigDataMessage;
+use crate::messages::get_config::GetConfigMessage;
+use crate::messages::get_node::GetNodeMessage;
+use crate::messages::modify_config::ModifyConfigMessage;
+use crate::messages::node_data::NodeDataMessage;
+use crate::messages::node_hello::NodeHelloMessage;
+use crate::messages::probe_error::ProbeErrorMessage;
+use crate::messages::probe_request::{ProbeRequestMessage, ProbeType};
+use crate::messages::probe_result::{estimate_network_size, ProbeResult, ProbeResultMessage};
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    ConfigData, NodeData, ProbeBandwidth, ProbeBuild, ProbeError, ProbeIdentifier,
+    ProbeLinkLengths, ProbeLocation, ProbeOverallBulkOutputCapacityUsage, ProbeRefused,
+    ProbeRejectStats, ProbeStoreSize, ProbeUptime, ProtocolError,
+};
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+const PROBE_QUERY_NAME: &str = "Probe";
+
+const PROBE_RESULTS: &[NodeMessageType] = &[
+    ProbeBandwidth,
+    ProbeBuild,
+    ProbeIdentifier,
+    ProbeLinkLengths,
+    ProbeLocation,
+    ProbeStoreSize,
+    ProbeUptime,
+    ProbeRejectStats,
+    ProbeOverallBulkOutputCapacityUsage,
+];
+
+impl FCPConnector {
+    /// The `NodeHello` of the current connection, [None] until [FCPConnector::listen] received it
+    ///
+    /// Changes if the connector reconnects, possibly to a node that was updated meanwhile.
+    pub fn node_hello(&self) -> watch::Receiver<Option<NodeHelloMessage>> {
+        self.node_hello.subscribe()
+    }
+
+    pub(super) fn receive_node_hello(&self, message: Message) {
+        match NodeHelloMessage::try_from(message) {
+            Ok(node_hello) => {
+                log::info!(
+                    "Connected to {} build {}",
+                    node_hello.node,
+                    node_hello.build.unwrap_or_default()
+                );
+                self.node_hello.send_replace(Some(node_hello));
+            }
+            Err(err) => log::warn!("Received malformed NodeHello {err}"),
+        }
+    }
+
+    /// Describes the node, with its private keys and statistics if asked for
+    pub async fn get_node(
+        &self,
+        get_node: &GetNodeMessage,
+        timeout: Option<Duration>,
+    ) -> Result<NodeDataMessage, NodeError> {
+        let message = self
+            .query_node(
+                get_node.identifier.clone(),
+                &[NodeData],
+                get_node.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    pub async fn get_config(
+        &self,
+        get_config: &GetConfigMessage,
+        timeout: Option<Duration>,
+    ) -> Result<ConfigDataMessage, NodeError> {
+        let message = self
+            .query_node(
+                get_config.identifier.clone(),
+                &[ConfigData],
+                get_config.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Changes options of the node and returns their current values afterwards, which keep their
+    /// old value if the node refused the new one
+    pub async fn modify_config(
+        &self,
+        modify_config: &ModifyConfigMessage,
+        timeout: Option<Duration>,
+    ) -> Result<ConfigDataMessage, NodeError> {
+        let message = self
+            .query_node(
+                modify_config.identifier.clone(),
+                &[ConfigData],
+                modify_config.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Asks a random node of the network, fails with [NodeError::ProbeFailed] or
+    /// [NodeError::ProbeRefused] if it could not be reached or did not answer
+    pub async fn probe(
+        &self,
+        probe_request: &ProbeRequestMessage,
+        timeout: Option<Duration>,
+    ) -> Result<ProbeResult, NodeError> {
+        let message = self
+            .query_node(
+                probe_request.identifier.clone(),
+                PROBE_RESULTS,
+                probe_request.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(ProbeResultMessage::try_from(message)?.result)
+    }
+
+    /// Estimates the number of nodes in the network from `samples` identifier probes sent one
+    /// after another, see [estimate_network_size]
+    ///
+    /// Probes that fail are not counted. [None] if too few nodes answered more than once, more
+    /// samples are needed then.
+    pub async fn estimate_network_size(
+        &self,
+        samples: usize,
+        hops_to_live: u8,
+        timeout: Option<Duration>,
+    ) -> Result<Option<f64>, NodeError> {
+        let mut identifiers = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let probe_request = ProbeRequestMessage {
+                identifier: UniqueIdentifier::new(PROBE_QUERY_NAME)
+                    .map_err(tokio::io::Error::from)?,
+                probe_type: ProbeType::Identifier,
+                hops_to_live,
+            };
+            match self.probe(&probe_request, timeout).await {
+                Ok(ProbeResult::Identifier { identifier, .. }) => identifiers.push(identifier),
+                Ok(result) => log::warn!("Identifier probe answered with {result:?}"),
+                Err(NodeError::ProbeFailed { error_type, .. }) => {
+                    log::debug!("Identifier probe failed with {error_type}")
+                }
+                Err(NodeError::ProbeRefused) => log::debug!("Identifier probe refused"),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(estimate_network_size(&identifiers))
+    }
+
+    /// Sends `message` and waits for the answer of one of the `message_types` carrying
+    /// `identifier`
+    async fn query_node(
+        &self,
+        identifier: UniqueIdentifier,
+        message_types: &'static [NodeMessageType],
+        message: Message,
+        timeout: Option<Duration>,
+    ) -> Result<Message, NodeError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let type_filter: Box<MessageFilter> =
+            Box::new(move |message| match message.message_type() {
+                MessageType::Node(message_type) => {
+                    matches!(message_type, ProtocolError | ProbeError | ProbeRefused)
+                        || message_types.contains(&message_type)
+                }
+                _ => false,
+            });
+        let mut answers = self
+            .query(vec![identity_filter(identifier), type_filter], message)
+            .await?;
+
+        let message = self.next_answer(&mut answers, deadline).await?;
+        match message.message_type() {
+            MessageType::Node(ProbeError) => {
+                let probe_error = ProbeErrorMessage::try_from(message)?;
+                Err(NodeError::ProbeFailed {
+                    error_type: probe_error.error_type,
+                    local: probe_error.local,
+                })
+            }
+            MessageType::Node(ProbeRefused) => Err(NodeError::ProbeRefused),
+            MessageType::Node(ProtocolError) => Err(NodeError::ProtocolError(
+                ProtocolErrorMessage::try_from(message)?,
+            )),
+            _ => Ok(message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NodeError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node refused the message, e.g. as the client lacks the permission for it
+    ProtocolError(ProtocolErrorMessage),
+    /// A probe got lost on its way through the network
+    ProbeFailed {
+        error_type: Box<str>,
+        local: bool,
+    },
+    /// The node the probe ended at did not want to answer it
+    ProbeRefused,
+    /// The connection stopped before the node answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for NodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            NodeError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            NodeError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            NodeError::ProbeFailed { error_type, local } => {
+                write!(f, "Probe failed with {error_type} (local: {local})")
+            }
+            NodeError::ProbeRefused => write!(f, "Probe refused"),
+            NodeError::ConnectionClosed => {
+                write!(f, "Connection closed before the node answered")
+            }
+            NodeError::TimedOut => write!(f, "Node query timed out"),
+        }
+    }
+}
+
+impl Error for NodeError {}
+
+impl From<tokio::io::Error> for NodeError {
+    fn from(value: tokio::io::Error) -> Self {
+        NodeError::TokioIo(value)
+    }
+}
+
+impl From<NoAnswer> for NodeError {
+    fn from(value: NoAnswer) -> Self {
+        match value {
+            NoAnswer::ConnectionClosed => NodeError::ConnectionClosed,
+            NoAnswer::TimedOut => NodeError::TimedOut,
+        }
+    }
+}
+
+impl From<DecodeError> for NodeError {
+    fn from(value: DecodeError) -> Self {
+        NodeError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::node::NodeError;
+    use crate::messages::get_config::GetConfigMessage;
+    use crate::messages::get_node::GetNodeMessage;
+    use crate::messages::modify_config::ModifyConfigMessage;
+    use crate::messages::probe_request::{ProbeRequestMessage, ProbeType};
+    use crate::messages::probe_result::ProbeResult;
+    use crate::mock_node::MockNode;
+    use crate::model::field_set::FieldSet;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    #[tokio::test]
+    async fn test_node_hello_and_data() {
+        let node = MockNode::start().await.unwrap();
+        node.insert("CHK@stored", b"0123456789".as_slice()).await;
+        let connector = node.connector("Node test").await;
+
+        let mut node_hello = connector.node_hello();
+        let node_hello = node_hello.wait_for(Option::is_some).await.unwrap().clone();
+        let node_hello = node_hello.unwrap();
+        assert_eq!(node_hello.build, Some(1497));
+        assert!(!node_hello.testnet);
+        assert_eq!(node_hello.compression_codecs.len(), 3);
+
+        let get_node = GetNodeMessage {
+            identifier: UniqueIdentifier::new("Node").unwrap(),
+            with_private: false,
+            with_volatile: true,
+            give_opennet_ref: false,
+        };
+        let node_data = connector.get_node(&get_node, TIMEOUT).await.unwrap();
+        assert_eq!(node_data.node_ref.name(), Some("Mock node"));
+        assert_eq!(node_data.private, None);
+        let datastore = node_data.datastore().unwrap().unwrap();
+        assert_eq!(datastore.store_keys, 1);
+        assert_eq!(datastore.store_size, 10);
+        assert_eq!(
+            node_data.bandwidth().unwrap().unwrap().total_output_bytes,
+            0
+        );
+
+        let get_node = GetNodeMessage {
+            identifier: UniqueIdentifier::new("Node").unwrap(),
+            with_private: true,
+            with_volatile: false,
+            give_opennet_ref: true,
+        };
+        let node_data = connector.get_node(&get_node, TIMEOUT).await.unwrap();
+        assert!(node_data.node_ref.opennet());
+        assert_eq!(node_data.node_ref.fields().get("dsaPrivKey.x"), None);
+        assert_eq!(node_data.datastore().unwrap(), None);
+        assert!(node_data.private.unwrap().get("dsaPrivKey.x").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_modify_config() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Node test").await;
+
+        let get_config = GetConfigMessage::everything(UniqueIdentifier::new("Config").unwrap());
+        let config = connector.get_config(&get_config, TIMEOUT).await.unwrap();
+        let limit = config.option("node.outputBandwidthLimit").unwrap();
+        assert_eq!(limit.current.as_deref(), Some("32768"));
+        assert_eq!(limit.data_type.as_deref(), Some("number"));
+
+        let mut values = FieldSet::new();
+        values.set("node.outputBandwidthLimit", "65536");
+        values.set("node.storeSize", "large");
+        let modify_config = ModifyConfigMessage {
+            identifier: UniqueIdentifier::new("Config").unwrap(),
+            values,
+        };
+        let config = connector
+            .modify_config(&modify_config, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(config.current("node.outputBandwidthLimit"), Some("65536"));
+        assert_eq!(config.current("node.storeSize"), Some("1073741824"));
+        assert_eq!(config.option("node.storeSize").unwrap().default, None);
+    }
+
+    #[tokio::test]
+    async fn test_probes() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Node test").await;
+
+        let probe = |probe_type, hops_to_live| ProbeRequestMessage {
+            identifier: UniqueIdentifier::new("Probe").unwrap(),
+            probe_type,
+            hops_to_live,
+        };
+        assert_eq!(
+            connector
+                .probe(&probe(ProbeType::Build, 25), TIMEOUT)
+                .await
+                .unwrap(),
+            ProbeResult::Build(1497)
+        );
+        assert!(matches!(
+            connector
+                .probe(&probe(ProbeType::Build, 100), TIMEOUT)
+                .await,
+            Err(NodeError::ProbeRefused)
+        ));
+
+        // The mock network has 100 nodes
+        let estimate = connector
+            .estimate_network_size(200, 25, TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!((50.0..200.0).contains(&estimate), "{estimate}");
+    }
+}
@@ -0,0 +1,145 @@
+use bytes::{Buf, Bytes};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, ErrorKind, ReadBuf};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Payload bytes buffered for a [PayloadReader] before it is failed for falling behind
+pub const MAX_BUFFERED_PAYLOAD: usize = 16 * 1024 * 1024;
+
+/// Payload of a node message, read from the connection while it is consumed
+///
+/// The connector keeps reading further messages while the payload is buffered for the reader. A
+/// reader more than [MAX_BUFFERED_PAYLOAD] bytes behind fails with an error, like a dropped reader
+/// the rest of its payload is then skipped.
+#[derive(Debug)]
+pub struct PayloadReader {
+    chunks: UnboundedReceiver<std::io::Result<Bytes>>,
+    /// Bytes sent to `chunks` but not received yet, shared with the [PayloadSender]
+    buffered: Arc<AtomicUsize>,
+    current: Bytes,
+    /// Bytes not yet received from the connection
+    remaining: usize,
+    data_length: usize,
+}
+
+impl PayloadReader {
+    /// Size of the whole payload as announced by the node
+    pub fn data_length(&self) -> usize {
+        self.data_length
+    }
+}
+
+impl AsyncRead for PayloadReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+
+        while this.current.is_empty() {
+            if this.remaining == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            match ready!(this.chunks.poll_recv(cx)) {
+                Some(Ok(chunk)) => {
+                    this.buffered.fetch_sub(chunk.len(), Ordering::Relaxed);
+                    this.remaining -= chunk.len();
+                    this.current = chunk;
+                }
+                Some(Err(err)) => return Poll::Ready(Err(err)),
+                None => {
+                    return Poll::Ready(Err(std::io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Connection lost before the payload was complete",
+                    )))
+                }
+            }
+        }
+
+        let len = this.current.len().min(buf.remaining());
+        buf.put_slice(&this.current[..len]);
+        this.current.advance(len);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Hands the chunks of a payload read from the connection to its [PayloadReader] without waiting
+/// for the reader
+pub(super) struct PayloadSender {
+    /// Unset once the reader was dropped or fell behind
+    chunks: Option<UnboundedSender<std::io::Result<Bytes>>>,
+    buffered: Arc<AtomicUsize>,
+}
+
+impl PayloadSender {
+    /// Buffers `chunk` for the reader, the chunks of a reader that is gone or fell behind are
+    /// skipped
+    ///
+    /// Returns false if the reader fell behind with this chunk.
+    pub(super) fn send(&mut self, chunk: Bytes) -> bool {
+        let Some(chunks) = self.chunks.take_if(|e| !e.is_closed()) else {
+            return true;
+        };
+
+        let len = chunk.len();
+        let buffered = self.buffered.fetch_add(len, Ordering::Relaxed) + len;
+        if buffered > MAX_BUFFERED_PAYLOAD {
+            let _ = chunks.send(Err(std::io::Error::other(format!(
+                "Payload was not read while more than {MAX_BUFFERED_PAYLOAD} bytes arrived"
+            ))));
+            return false;
+        }
+
+        let _ = chunks.send(Ok(chunk));
+        self.chunks = Some(chunks);
+        true
+    }
+}
+
+pub(super) fn payload_channel(data_length: usize) -> (PayloadSender, PayloadReader) {
+    let (tx, rx) = unbounded_channel();
+    let buffered = Arc::new(AtomicUsize::new(0));
+    let sender = PayloadSender {
+        chunks: Some(tx),
+        buffered: buffered.clone(),
+    };
+    let reader = PayloadReader {
+        chunks: rx,
+        buffered,
+        current: Bytes::new(),
+        remaining: data_length,
+        data_length,
+    };
+
+    (sender, reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fcp_connector::payload::{payload_channel, MAX_BUFFERED_PAYLOAD};
+    use bytes::Bytes;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_reader_falling_behind_fails() {
+        let chunk = Bytes::from(vec![7; MAX_BUFFERED_PAYLOAD / 2 + 1]);
+        let (mut sender, mut reader) = payload_channel(4 * chunk.len());
+
+        assert!(sender.send(chunk.clone()));
+        let mut first = [0; 4];
+        reader.read_exact(&mut first).await.unwrap();
+        assert!(sender.send(chunk.clone()));
+        assert!(!sender.send(chunk.clone()));
+        assert!(sender.send(chunk.clone()));
+
+        let mut received = Vec::new();
+        assert!(reader.read_to_end(&mut received).await.is_err());
+        assert_eq!(received.len(), 2 * chunk.len() - 4);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::filters::{identity_filter, MessageFilter};
+use crate::fcp_connector::{FCPConnector, NoAnswer};
+use crate::messages::add_peer::AddPeerMessage;
+use crate::messages::list_peer::ListPeerMessage;
+use crate::messages::list_peer_notes::ListPeerNotesMessage;
+use crate::messages::list_peers::ListPeersMessage;
+use crate::messages::modify_peer::ModifyPeerMessage;
+use crate::messages::modify_peer_note::ModifyPeerNoteMessage;
+use crate::messages::peer::PeerMessage;
+use crate::messages::peer_note::PeerNoteMessage;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::messages::remove_peer::RemovePeerMessage;
+use crate::messages::unknown_node_identifier::UnknownNodeIdentifierMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    EndListPeerNotes, EndListPeers, Peer, PeerNote, PeerRemoved, ProtocolError,
+    UnknownNodeIdentifier,
+};
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::Instant;
+
+const PEER_QUERY_NAME: &str = "Peers";
+
+impl FCPConnector {
+    /// Describes all peers of the node, with their `metadata.*` and `volatile.*` fields if asked
+    /// for
+    pub async fn list_peers(
+        &self,
+        with_metadata: bool,
+        with_volatile: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<PeerMessage>, PeerError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let identifier = UniqueIdentifier::new(PEER_QUERY_NAME).map_err(tokio::io::Error::from)?;
+        let list_peers = ListPeersMessage {
+            identifier: identifier.clone(),
+            with_metadata,
+            with_volatile,
+        };
+        let mut answers = self
+            .query_peers(identifier, &[Peer, EndListPeers], list_peers.into())
+            .await?;
+
+        let mut peers = Vec::new();
+        loop {
+            let message = self.next_peer_answer(&mut answers, deadline).await?;
+            if message
+                .message_type()
+                .is_specific_node_message(EndListPeers)
+            {
+                return Ok(peers);
+            }
+            peers.push(message.try_into()?);
+        }
+    }
+
+    /// Describes the peer with the name, identity or `host:port` `node_identifier`
+    pub async fn list_peer(
+        &self,
+        node_identifier: &str,
+        with_metadata: bool,
+        with_volatile: bool,
+        timeout: Option<Duration>,
+    ) -> Result<PeerMessage, PeerError> {
+        let identifier = UniqueIdentifier::new(PEER_QUERY_NAME).map_err(tokio::io::Error::from)?;
+        let list_peer = ListPeerMessage {
+            identifier: identifier.clone(),
+            node_identifier: node_identifier.into(),
+            with_metadata,
+            with_volatile,
+        };
+        let message = self
+            .query_peer(identifier, &[Peer], list_peer.into(), timeout)
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Adds a darknet peer and returns it as the node sees it
+    pub async fn add_peer(
+        &self,
+        add_peer: &AddPeerMessage,
+        timeout: Option<Duration>,
+    ) -> Result<PeerMessage, PeerError> {
+        let message = self
+            .query_peer(
+                add_peer.identifier.clone(),
+                &[Peer],
+                add_peer.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Changes the settings of a peer and returns it as the node sees it afterwards
+    pub async fn modify_peer(
+        &self,
+        modify_peer: &ModifyPeerMessage,
+        timeout: Option<Duration>,
+    ) -> Result<PeerMessage, PeerError> {
+        let message = self
+            .query_peer(
+                modify_peer.identifier.clone(),
+                &[Peer],
+                modify_peer.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    pub async fn remove_peer(
+        &self,
+        node_identifier: &str,
+        timeout: Option<Duration>,
+    ) -> Result<(), PeerError> {
+        let identifier = UniqueIdentifier::new(PEER_QUERY_NAME).map_err(tokio::io::Error::from)?;
+        let remove_peer = RemovePeerMessage {
+            identifier: identifier.clone(),
+            node_identifier: node_identifier.into(),
+        };
+        self.query_peer(identifier, &[PeerRemoved], remove_peer.into(), timeout)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_peer_notes(
+        &self,
+        node_identifier: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<PeerNoteMessage>, PeerError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let identifier = UniqueIdentifier::new(PEER_QUERY_NAME).map_err(tokio::io::Error::from)?;
+        let list_peer_notes = ListPeerNotesMessage {
+            identifier: identifier.clone(),
+            node_identifier: node_identifier.into(),
+        };
+        let mut answers = self
+            .query_peers(
+                identifier,
+                &[PeerNote, EndListPeerNotes],
+                list_peer_notes.into(),
+            )
+            .await?;
+
+        let mut notes = Vec::new();
+        loop {
+            let message = self.next_peer_answer(&mut answers, deadline).await?;
+            if message
+                .message_type()
+                .is_specific_node_message(EndListPeerNotes)
+            {
+                return Ok(notes);
+            }
+            notes.push(message.try_into()?);
+        }
+    }
+
+    /// Replaces the private comment about a darknet peer
+    pub async fn modify_peer_note(
+        &self,
+        modify_peer_note: &ModifyPeerNoteMessage,
+        timeout: Option<Duration>,
+    ) -> Result<PeerNoteMessage, PeerError> {
+        let message = self
+            .query_peer(
+                modify_peer_note.identifier.clone(),
+                &[PeerNote],
+                modify_peer_note.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Sends `message` and waits for the single answer of one of the `message_types`
+    async fn query_peer(
+        &self,
+        identifier: UniqueIdentifier,
+        message_types: &'static [NodeMessageType],
+        message: Message,
+        timeout: Option<Duration>,
+    ) -> Result<Message, PeerError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut answers = self.query_peers(identifier, message_types, message).await?;
+        self.next_peer_answer(&mut answers, deadline).await
+    }
+
+    /// Sends `message` and returns its answers of one of the `message_types`, as well as the
+    /// errors about it, carrying `identifier`
+    async fn query_peers(
+        &self,
+        identifier: UniqueIdentifier,
+        message_types: &'static [NodeMessageType],
+        message: Message,
+    ) -> Result<UnboundedReceiver<Message>, PeerError> {
+        let type_filter: Box<MessageFilter> =
+            Box::new(move |message| match message.message_type() {
+                MessageType::Node(message_type) => {
+                    matches!(message_type, ProtocolError | UnknownNodeIdentifier)
+                        || message_types.contains(&message_type)
+                }
+                _ => false,
+            });
+
+        Ok(self
+            .query(vec![identity_filter(identifier), type_filter], message)
+            .await?)
+    }
+
+    async fn next_peer_answer(
+        &self,
+        answers: &mut UnboundedReceiver<Message>,
+        deadline: Option<Instant>,
+    ) -> Result<Message, PeerError> {
+        let message = self.next_answer(answers, deadline).await?;
+        match message.message_type() {
+            MessageType::Node(UnknownNodeIdentifier) => Err(PeerError::UnknownNodeIdentifier(
+                UnknownNodeIdentifierMessage::try_from(message)?.node_identifier,
+            )),
+            MessageType::Node(ProtocolError) => Err(PeerError::ProtocolError(
+                ProtocolErrorMessage::try_from(message)?,
+            )),
+            _ => Ok(message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PeerError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node has no peer with this name, identity or address
+    UnknownNodeIdentifier(Box<str>),
+    /// The node refused the message, e.g. as the reference of a new peer is invalid or known
+    ProtocolError(ProtocolErrorMessage),
+    /// The connection stopped before the node answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for PeerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            PeerError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            PeerError::UnknownNodeIdentifier(inner) => write!(f, "Unknown peer {inner}"),
+            PeerError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            PeerError::ConnectionClosed => {
+                write!(f, "Connection closed before the node answered")
+            }
+            PeerError::TimedOut => write!(f, "Peer query timed out"),
+        }
+    }
+}
+
+impl Error for PeerError {}
+
+impl From<tokio::io::Error> for PeerError {
+    fn from(value: tokio::io::Error) -> Self {
+        PeerError::TokioIo(value)
+    }
+}
+
+impl From<NoAnswer> for PeerError {
+    fn from(value: NoAnswer) -> Self {
+        match value {
+            NoAnswer::ConnectionClosed => PeerError::ConnectionClosed,
+            NoAnswer::TimedOut => PeerError::TimedOut,
+        }
+    }
+}
+
+impl From<DecodeError> for PeerError {
+    fn from(value: DecodeError) -> Self {
+        PeerError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::peers::PeerError;
+    use crate::messages::add_peer::{AddPeerMessage, AddPeerSource};
+    use crate::messages::modify_peer::ModifyPeerMessage;
+    use crate::messages::modify_peer_note::ModifyPeerNoteMessage;
+    use crate::messages::protocol_error::ProtocolErrorMessage;
+    use crate::mock_node::MockNode;
+    use crate::model::node_ref::NodeRef;
+    use crate::model::peer_status::PeerStatus;
+    use crate::model::peer_trust::PeerTrust;
+    use crate::model::peer_visibility::PeerVisibility;
+    use crate::model::protocol_error_code::ProtocolErrorCode;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn add_peer(name: &str) -> AddPeerMessage {
+        let node_ref = format!(
+            "identity={name}-identity\nmyName={name}\nopennet=false\n\
+             physical.udp=192.0.2.1:4000\nark.number=3\nEnd\n"
+        );
+        AddPeerMessage {
+            identifier: UniqueIdentifier::new("Add peer").unwrap(),
+            trust: PeerTrust::High,
+            visibility: PeerVisibility::NameOnly,
+            node_ref: AddPeerSource::NodeRef(node_ref.parse().unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_peer_lifecycle() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Peer test").await;
+
+        let added = connector.add_peer(&add_peer("Bob"), TIMEOUT).await.unwrap();
+        assert_eq!(added.node_ref.name(), Some("Bob"));
+        assert!(added.is_darknet());
+        connector
+            .add_peer(&add_peer("Carol"), TIMEOUT)
+            .await
+            .unwrap();
+
+        let peers = connector.list_peers(false, true, TIMEOUT).await.unwrap();
+        let names: Vec<_> = peers.iter().map(|e| e.node_ref.name().unwrap()).collect();
+        assert_eq!(names, ["Bob", "Carol"]);
+        assert_eq!(peers[0].status(), Some(PeerStatus::NeverConnected));
+        assert_eq!(peers[0].metadata, None);
+        assert_eq!(peers[0].node_ref.ark_number().unwrap(), Some(3));
+
+        let modify = ModifyPeerMessage {
+            identifier: UniqueIdentifier::new("Modify peer").unwrap(),
+            node_identifier: "Bob".into(),
+            allow_local_addresses: None,
+            is_disabled: Some(true),
+            is_listen_only: None,
+            is_burst_only: None,
+            ignore_source_port: None,
+            trust: None,
+            visibility: None,
+        };
+        let modified = connector.modify_peer(&modify, TIMEOUT).await.unwrap();
+        assert_eq!(modified.status(), Some(PeerStatus::Disabled));
+        assert!(!modified.status().unwrap().is_connected());
+
+        let note = ModifyPeerNoteMessage {
+            identifier: UniqueIdentifier::new("Peer note").unwrap(),
+            node_identifier: "Bob-identity".into(),
+            note_text: "Met at the meetup\nSwapped refs in person".into(),
+        };
+        connector.modify_peer_note(&note, TIMEOUT).await.unwrap();
+        let notes = connector.list_peer_notes("Bob", TIMEOUT).await.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].note_text, note.note_text);
+
+        connector.remove_peer("Bob", TIMEOUT).await.unwrap();
+        assert!(matches!(
+            connector.list_peer("Bob", false, false, TIMEOUT).await,
+            Err(PeerError::UnknownNodeIdentifier(node_identifier)) if &*node_identifier == "Bob"
+        ));
+        let carol = connector
+            .list_peer("192.0.2.1:4000", true, false, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(carol.node_ref.name(), Some("Carol"));
+        assert_eq!(carol.metadata.unwrap().get("isDisabled"), Some("false"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_peer_is_refused() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Peer test").await;
+
+        connector.add_peer(&add_peer("Bob"), TIMEOUT).await.unwrap();
+        assert!(matches!(
+            connector.add_peer(&add_peer("Bob"), TIMEOUT).await,
+            Err(PeerError::ProtocolError(ProtocolErrorMessage {
+                code: ProtocolErrorCode::DuplicatePeerRef,
+                ..
+            }))
+        ));
+        let text = connector.list_peers(false, false, TIMEOUT).await.unwrap()[0]
+            .node_ref
+            .to_string();
+        assert_eq!(text.parse::<NodeRef>().unwrap().identity(), "Bob-identity");
+    }
+}
@@ -0,0 +1,467 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::filters::{identity_filter, MessageFilter};
+use crate::fcp_connector::{FCPConnector, NoAnswer};
+use crate::messages::get_request_status::GetRequestStatusMessage;
+use crate::messages::list_persistent_requests::ListPersistentRequestsMessage;
+use crate::messages::modify_persistent_request::ModifyPersistentRequestMessage;
+use crate::messages::persistent_get::PersistentGetMessage;
+use crate::messages::persistent_put::PersistentPutMessage;
+use crate::messages::persistent_request_modified::PersistentRequestModifiedMessage;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::messages::remove_request::RemoveRequestMessage;
+use crate::messages::watch_global::WatchGlobalMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    EndListPersistentRequests, PersistentGet, PersistentPut, PersistentRequestModified,
+    PersistentRequestRemoved, ProtocolError,
+};
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+use crate::model::verbosity::Verbosity;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A request on the persistent queue of the node as described by it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PersistentRequest {
+    Get(Box<PersistentGetMessage>),
+    Put(Box<PersistentPutMessage>),
+}
+
+impl PersistentRequest {
+    pub fn identifier(&self) -> &str {
+        match self {
+            PersistentRequest::Get(inner) => &inner.identifier,
+            PersistentRequest::Put(inner) => &inner.identifier,
+        }
+    }
+
+    pub fn uri(&self) -> &URI {
+        match self {
+            PersistentRequest::Get(inner) => &inner.uri,
+            PersistentRequest::Put(inner) => &inner.uri,
+        }
+    }
+
+    pub fn priority(&self) -> PriorityClass {
+        match self {
+            PersistentRequest::Get(inner) => inner.priority,
+            PersistentRequest::Put(inner) => inner.priority,
+        }
+    }
+
+    pub fn global(&self) -> bool {
+        match self {
+            PersistentRequest::Get(inner) => inner.global,
+            PersistentRequest::Put(inner) => inner.global,
+        }
+    }
+}
+
+impl TryFrom<Message> for PersistentRequest {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        match value.message_type() {
+            MessageType::Node(PersistentPut) => {
+                Ok(PersistentRequest::Put(Box::new(value.try_into()?)))
+            }
+            _ => Ok(PersistentRequest::Get(Box::new(value.try_into()?))),
+        }
+    }
+}
+
+/// Node messages about persistent requests that are handed to [Listener]s before the
+/// [crate::fcp_connector::request::Response] of a request with the same `Identifier`
+pub(super) fn is_persistent_request_notification(message_type: &MessageType) -> bool {
+    matches!(
+        message_type,
+        MessageType::Node(
+            PersistentGet
+                | PersistentPut
+                | PersistentRequestModified
+                | PersistentRequestRemoved
+                | EndListPersistentRequests
+        )
+    )
+}
+
+impl FCPConnector {
+    /// Lists the persistent requests of this client and, while [FCPConnector::watch_global] is
+    /// enabled, those on the global queue
+    ///
+    /// Requests the node describes in a way this library can't decode, e.g. ones of other clients,
+    /// are left out.
+    pub async fn list_persistent_requests(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<PersistentRequest>, PersistentRequestError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let _query = self.persistent_query.lock().await;
+
+        let filter: Box<MessageFilter> = Box::new(|message| {
+            matches!(
+                message.message_type(),
+                MessageType::Node(PersistentGet | PersistentPut | EndListPersistentRequests)
+            )
+        });
+        let mut answers = self
+            .query(vec![filter], ListPersistentRequestsMessage.into())
+            .await?;
+
+        let mut requests = Vec::new();
+        loop {
+            let message = self.next_answer(&mut answers, deadline).await?;
+            if message
+                .message_type()
+                .is_specific_node_message(EndListPersistentRequests)
+            {
+                return Ok(requests);
+            }
+            let identifier: Option<Box<str>> =
+                message.fields().get("Identifier").map(|e| e.value().into());
+            match PersistentRequest::try_from(message) {
+                Ok(request) => requests.push(request),
+                Err(err) => log::warn!("Skipping persistent request {identifier:?} {err}"),
+            }
+        }
+    }
+
+    /// Describes a single persistent request
+    pub async fn persistent_request_status(
+        &self,
+        identifier: UniqueIdentifier,
+        global: bool,
+        timeout: Option<Duration>,
+    ) -> Result<PersistentRequest, PersistentRequestError> {
+        let get_request_status = GetRequestStatusMessage {
+            identifier: identifier.clone(),
+            global,
+            only_data: false,
+        };
+        let message = self
+            .query_identified(
+                identifier,
+                &[PersistentGet, PersistentPut],
+                get_request_status.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Changes the priority or client token of a persistent request
+    pub async fn modify_persistent_request(
+        &self,
+        modify: &ModifyPersistentRequestMessage,
+        timeout: Option<Duration>,
+    ) -> Result<PersistentRequestModifiedMessage, PersistentRequestError> {
+        let message = self
+            .query_identified(
+                modify.identifier.clone(),
+                &[PersistentRequestModified],
+                modify.into(),
+                timeout,
+            )
+            .await?;
+
+        Ok(message.try_into()?)
+    }
+
+    /// Removes a persistent request from the node, cancelling it if it still runs
+    pub async fn remove_persistent_request(
+        &self,
+        identifier: UniqueIdentifier,
+        global: bool,
+        timeout: Option<Duration>,
+    ) -> Result<(), PersistentRequestError> {
+        let remove_request = RemoveRequestMessage {
+            identifier: identifier.clone(),
+            global,
+        };
+        self.query_identified(
+            identifier,
+            &[PersistentRequestRemoved],
+            remove_request.into(),
+            timeout,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Enables or disables receiving the messages of requests on the global queue, e.g. ones
+    /// started by other clients or the web interface
+    pub async fn watch_global(
+        &self,
+        enabled: bool,
+        verbosity_mask: Verbosity,
+    ) -> Result<(), tokio::io::Error> {
+        self.send(WatchGlobalMessage {
+            enabled,
+            verbosity_mask,
+        })
+        .await
+    }
+
+    /// Sends `message` and waits for the answer of one of the `message_types`, or a
+    /// `ProtocolError`, carrying `identifier`
+    async fn query_identified(
+        &self,
+        identifier: UniqueIdentifier,
+        message_types: &'static [NodeMessageType],
+        message: Message,
+        timeout: Option<Duration>,
+    ) -> Result<Message, PersistentRequestError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let _query = self.persistent_query.lock().await;
+
+        let type_filter: Box<MessageFilter> =
+            Box::new(move |message| match message.message_type() {
+                MessageType::Node(message_type) => {
+                    message_type == ProtocolError || message_types.contains(&message_type)
+                }
+                _ => false,
+            });
+        let mut answers = self
+            .query(vec![identity_filter(identifier), type_filter], message)
+            .await?;
+
+        let message = self.next_answer(&mut answers, deadline).await?;
+        if message
+            .message_type()
+            .is_specific_node_message(ProtocolError)
+        {
+            return Err(PersistentRequestError::ProtocolError(
+                ProtocolErrorMessage::try_from(message)?,
+            ));
+        }
+
+        Ok(message)
+    }
+}
+
+#[derive(Debug)]
+pub enum PersistentRequestError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node refused the query, e.g. as it knows no request with the identifier
+    ProtocolError(ProtocolErrorMessage),
+    /// The connection stopped before the query was answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for PersistentRequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistentRequestError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            PersistentRequestError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            PersistentRequestError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            PersistentRequestError::ConnectionClosed => {
+                write!(f, "Connection closed before the query was answered")
+            }
+            PersistentRequestError::TimedOut => write!(f, "Query timed out"),
+        }
+    }
+}
+
+impl Error for PersistentRequestError {}
+
+impl From<tokio::io::Error> for PersistentRequestError {
+    fn from(value: tokio::io::Error) -> Self {
+        PersistentRequestError::TokioIo(value)
+    }
+}
+
+impl From<NoAnswer> for PersistentRequestError {
+    fn from(value: NoAnswer) -> Self {
+        match value {
+            NoAnswer::ConnectionClosed => PersistentRequestError::ConnectionClosed,
+            NoAnswer::TimedOut => PersistentRequestError::TimedOut,
+        }
+    }
+}
+
+impl From<DecodeError> for PersistentRequestError {
+    fn from(value: DecodeError) -> Self {
+        PersistentRequestError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::persistent::{PersistentRequest, PersistentRequestError};
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::messages::modify_persistent_request::ModifyPersistentRequestMessage;
+    use crate::messages::protocol_error::ProtocolErrorMessage;
+    use crate::mock_node::fault::{MockBehaviour, MockFault};
+    use crate::mock_node::MockNode;
+    use crate::model::message_type_identifier::ClientMessageType;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::protocol_error_code::ProtocolErrorCode;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::verbosity::Verbosity;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn persistent_put(identifier: &UniqueIdentifier) -> ClientPutMessage {
+        ClientPutMessage {
+            uri: "KSK@persistent".try_into().unwrap(),
+            content_type: None,
+            identifier: identifier.clone(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Forever,
+            target_filename: None,
+            upload_from: UploadType::Direct {
+                data: b"Hello World".as_slice().into(),
+            },
+            is_binary_blob: false,
+            real_time: false,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persistent_put_after_restart() {
+        let node = MockNode::start().await.unwrap();
+        let identifier = UniqueIdentifier::new("Persistent put").unwrap();
+        {
+            let connector = node.connector("Persistent test").await;
+            let (response, _) = connector
+                .request(&persistent_put(&identifier), None)
+                .await
+                .unwrap();
+            response.await.unwrap();
+        }
+
+        let connector = node.connector("Persistent test").await;
+        let requests = connector.list_persistent_requests(TIMEOUT).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let PersistentRequest::Put(put) = &requests[0] else {
+            panic!("Expected PersistentPut, got {:?}", requests[0]);
+        };
+        assert_eq!(put.identifier, Box::<str>::from(&identifier));
+        assert_eq!(put.persistence, Persistence::Forever);
+        assert_eq!(put.data_length, Some(11));
+
+        let modify = ModifyPersistentRequestMessage {
+            identifier: identifier.clone(),
+            global: false,
+            client_token: Some("attachment".into()),
+            priority: Some(PriorityClass::Low),
+        };
+        let modified = connector
+            .modify_persistent_request(&modify, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(modified.priority, Some(PriorityClass::Low));
+
+        let status = connector
+            .persistent_request_status(identifier.clone(), false, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(status.priority(), PriorityClass::Low);
+
+        let (response, _) = connector
+            .resume_request::<ClientPutMessage>(identifier.clone(), false, TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(response.await.unwrap().identifier, identifier);
+
+        connector
+            .remove_persistent_request(identifier.clone(), false, TIMEOUT)
+            .await
+            .unwrap();
+        assert!(connector
+            .list_persistent_requests(TIMEOUT)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            connector
+                .persistent_request_status(identifier, false, TIMEOUT)
+                .await,
+            Err(PersistentRequestError::ProtocolError(
+                ProtocolErrorMessage {
+                    code: ProtocolErrorCode::NoSuchIdentifier,
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_persistent_response_keeps_request() {
+        let node = MockNode::start().await.unwrap();
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientPut,
+            uri: None,
+            behaviour: MockBehaviour::Delay(Duration::from_millis(50)),
+            times: None,
+        })
+        .await;
+        let connector = node.connector("Persistent test").await;
+
+        let identifier = UniqueIdentifier::new("Persistent put").unwrap();
+        let (response, _) = connector
+            .request(&persistent_put(&identifier), None)
+            .await
+            .unwrap();
+        drop(response);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(node.removed_requests().await.is_empty());
+        assert_eq!(
+            node.get("KSK@persistent").await.as_deref(),
+            Some(b"Hello World".as_slice())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_global_requests_of_other_clients() {
+        let node = MockNode::start().await.unwrap();
+        node.add_global_get("FProxy:file", "KSK@fproxy", "-1").await;
+        node.add_global_get("FProxy:mask", "KSK@fproxy", "1048577")
+            .await;
+        node.add_global_get("FProxy:broken", "no URI", "0").await;
+        let connector = node.connector("Persistent test").await;
+        connector
+            .watch_global(true, Verbosity::all())
+            .await
+            .unwrap();
+
+        let requests = connector.list_persistent_requests(TIMEOUT).await.unwrap();
+        let identifiers: Vec<_> = requests.iter().map(|e| e.identifier()).collect();
+        assert_eq!(identifiers, ["FProxy:file", "FProxy:mask"]);
+        let PersistentRequest::Get(get) = &requests[0] else {
+            panic!("Expected PersistentGet, got {:?}", requests[0]);
+        };
+        assert_eq!(get.verbosity, Verbosity::all());
+        assert!(get.global);
+        let PersistentRequest::Get(get) = &requests[1] else {
+            panic!("Expected PersistentGet, got {:?}", requests[1]);
+        };
+        assert!(get.verbosity.simple_progress);
+        assert!(!get.verbosity.compression);
+    }
+}
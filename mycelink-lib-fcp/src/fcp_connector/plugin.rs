@@ -0,0 +1,179 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::filters::{identity_filter, MessageFilter};
+use crate::fcp_connector::{FCPConnector, NoAnswer};
+use crate::messages::fcp_plugin_message::FCPPluginMessage;
+use crate::messages::fcp_plugin_reply::FCPPluginReplyMessage;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::{FCPPluginReply, ProtocolError};
+use crate::model::protocol_error_code::ProtocolErrorCode;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio::time::Instant;
+
+impl FCPConnector {
+    /// Sends a message to a plugin of the node and waits for its reply
+    ///
+    /// Replies reporting a failure end in [PluginError::Failed], the reply is only returned if
+    /// the plugin handled the message.
+    pub async fn plugin_message(
+        &self,
+        plugin_message: &FCPPluginMessage,
+        timeout: Option<Duration>,
+    ) -> Result<FCPPluginReplyMessage, PluginError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let type_filter: Box<MessageFilter> = Box::new(|message| {
+            matches!(
+                message.message_type(),
+                MessageType::Node(FCPPluginReply | ProtocolError)
+            )
+        });
+        let mut answers = self
+            .query(
+                vec![
+                    identity_filter(plugin_message.identifier.clone()),
+                    type_filter,
+                ],
+                plugin_message.into(),
+            )
+            .await?;
+
+        let message = self.next_answer(&mut answers, deadline).await?;
+        if message
+            .message_type()
+            .is_specific_node_message(ProtocolError)
+        {
+            let protocol_error = ProtocolErrorMessage::try_from(message)?;
+            return Err(match protocol_error.code {
+                ProtocolErrorCode::NoSuchPlugin => {
+                    PluginError::NoSuchPlugin(plugin_message.plugin_name.clone())
+                }
+                _ => PluginError::ProtocolError(protocol_error),
+            });
+        }
+
+        let reply = FCPPluginReplyMessage::try_from(message)?;
+        if !reply.success {
+            return Err(PluginError::Failed {
+                code: reply.error_code.unwrap_or_default(),
+                message: reply.error_message.unwrap_or_default(),
+            });
+        }
+        Ok(reply)
+    }
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node has no plugin with this name loaded
+    NoSuchPlugin(Box<str>),
+    ProtocolError(ProtocolErrorMessage),
+    /// The plugin received the message but could not handle it, codes are defined by the plugin
+    Failed {
+        code: Box<str>,
+        message: Box<str>,
+    },
+    /// The connection stopped before the plugin answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            PluginError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            PluginError::NoSuchPlugin(inner) => write!(f, "Plugin {inner} is not loaded"),
+            PluginError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            PluginError::Failed { code, message } => {
+                write!(f, "Plugin failed with {code}: {message}")
+            }
+            PluginError::ConnectionClosed => {
+                write!(f, "Connection closed before the plugin answered")
+            }
+            PluginError::TimedOut => write!(f, "Plugin message timed out"),
+        }
+    }
+}
+
+impl Error for PluginError {}
+
+impl From<tokio::io::Error> for PluginError {
+    fn from(value: tokio::io::Error) -> Self {
+        PluginError::TokioIo(value)
+    }
+}
+
+impl From<NoAnswer> for PluginError {
+    fn from(value: NoAnswer) -> Self {
+        match value {
+            NoAnswer::ConnectionClosed => PluginError::ConnectionClosed,
+            NoAnswer::TimedOut => PluginError::TimedOut,
+        }
+    }
+}
+
+impl From<DecodeError> for PluginError {
+    fn from(value: DecodeError) -> Self {
+        PluginError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::plugin::PluginError;
+    use crate::messages::fcp_plugin_message::FCPPluginMessage;
+    use crate::mock_node::{MockNode, MOCK_ECHO_PLUGIN};
+    use crate::model::field_set::FieldSet;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn plugin_message(plugin_name: &str, params: FieldSet) -> FCPPluginMessage {
+        FCPPluginMessage {
+            identifier: UniqueIdentifier::new("Plugin").unwrap(),
+            plugin_name: plugin_name.into(),
+            params,
+            data: Some(b"payload".as_slice().into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_reply() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Plugin test").await;
+
+        let mut params = FieldSet::new();
+        params.set("Message", "GetIdentity");
+        params.set("Identity.0", "abc");
+        let reply = connector
+            .plugin_message(&plugin_message(MOCK_ECHO_PLUGIN, params.clone()), TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(reply.replies, params);
+        assert_eq!(reply.data.as_deref(), Some(b"payload".as_slice()));
+
+        params.set("Refuse", "true");
+        assert!(matches!(
+            connector
+                .plugin_message(&plugin_message(MOCK_ECHO_PLUGIN, params), TIMEOUT)
+                .await,
+            Err(PluginError::Failed { code, .. }) if &*code == "Refused"
+        ));
+        assert!(matches!(
+            connector
+                .plugin_message(&plugin_message("plugins.Missing", FieldSet::new()), TIMEOUT)
+                .await,
+            Err(PluginError::NoSuchPlugin(name)) if &*name == "plugins.Missing"
+        ));
+    }
+}
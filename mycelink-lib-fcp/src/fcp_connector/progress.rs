@@ -0,0 +1,124 @@
+use crate::decode_error::DecodeError;
+use crate::messages::expected_data_length::ExpectedDataLengthMessage;
+use crate::messages::expected_hashes::ExpectedHashesMessage;
+use crate::messages::expected_mime::ExpectedMIMEMessage;
+use crate::messages::finished_compression::FinishedCompressionMessage;
+use crate::messages::sending_to_network::SendingToNetworkMessage;
+use crate::messages::simple_progress::SimpleProgressMessage;
+use crate::messages::started_compression::StartedCompressionMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// Progress of a running insert or fetch, which events are sent depends on the `Verbosity` of
+/// the request
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgressEvent {
+    SimpleProgress(SimpleProgressMessage),
+    StartedCompression(StartedCompressionMessage),
+    FinishedCompression(FinishedCompressionMessage),
+    ExpectedHashes(ExpectedHashesMessage),
+    ExpectedMIME(ExpectedMIMEMessage),
+    ExpectedDataLength(ExpectedDataLengthMessage),
+    SendingToNetwork(SendingToNetworkMessage),
+}
+
+impl ProgressEvent {
+    pub fn is_progress_event(message_type: &MessageType) -> bool {
+        matches!(
+            message_type,
+            MessageType::Node(
+                NodeMessageType::SimpleProgress
+                    | NodeMessageType::StartedCompression
+                    | NodeMessageType::FinishedCompression
+                    | NodeMessageType::ExpectedHashes
+                    | NodeMessageType::ExpectedMIME
+                    | NodeMessageType::ExpectedDataLength
+                    | NodeMessageType::SendingToNetwork
+            )
+        )
+    }
+
+    pub fn identifier(&self) -> &UniqueIdentifier {
+        match self {
+            ProgressEvent::SimpleProgress(inner) => &inner.identifier,
+            ProgressEvent::StartedCompression(inner) => &inner.identifier,
+            ProgressEvent::FinishedCompression(inner) => &inner.identifier,
+            ProgressEvent::ExpectedHashes(inner) => &inner.identifier,
+            ProgressEvent::ExpectedMIME(inner) => &inner.identifier,
+            ProgressEvent::ExpectedDataLength(inner) => &inner.identifier,
+            ProgressEvent::SendingToNetwork(inner) => &inner.identifier,
+        }
+    }
+}
+
+impl TryFrom<Message> for ProgressEvent {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        Ok(match value.message_type() {
+            MessageType::Node(NodeMessageType::SimpleProgress) => {
+                ProgressEvent::SimpleProgress(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::StartedCompression) => {
+                ProgressEvent::StartedCompression(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::FinishedCompression) => {
+                ProgressEvent::FinishedCompression(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::ExpectedHashes) => {
+                ProgressEvent::ExpectedHashes(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::ExpectedMIME) => {
+                ProgressEvent::ExpectedMIME(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::ExpectedDataLength) => {
+                ProgressEvent::ExpectedDataLength(value.try_into()?)
+            }
+            MessageType::Node(NodeMessageType::SendingToNetwork) => {
+                ProgressEvent::SendingToNetwork(value.try_into()?)
+            }
+            got => {
+                return Err(DecodeError::ProtocolBreak(
+                    format!("'{}' is no progress event", got.name()).into(),
+                ))
+            }
+        })
+    }
+}
+
+/// The progress events among the intermediate messages of a request
+///
+/// Ends once the request finished or the connection closed.
+pub struct Progress {
+    intermediate: UnboundedReceiver<Message>,
+}
+
+impl Progress {
+    pub fn new(intermediate: UnboundedReceiver<Message>) -> Self {
+        Self { intermediate }
+    }
+
+    /// Waits for the next progress event, skipping all other intermediate messages
+    pub async fn next(&mut self) -> Option<ProgressEvent> {
+        loop {
+            let message = self.intermediate.recv().await?;
+            if !ProgressEvent::is_progress_event(&message.message_type()) {
+                continue;
+            }
+
+            match ProgressEvent::try_from(message) {
+                Ok(event) => return Some(event),
+                Err(err) => log::warn!("Skipping malformed progress event: {err}"),
+            }
+        }
+    }
+}
+
+impl From<UnboundedReceiver<Message>> for Progress {
+    fn from(value: UnboundedReceiver<Message>) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::fcp_connector::transport::BoxedTransport;
+use std::future::Future;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+pub type ConnectFuture =
+    Pin<Box<dyn Future<Output = Result<BoxedTransport, tokio::io::Error>> + Send>>;
+
+/// Opens a new connection to the node
+pub type ConnectFn = dyn Fn() -> ConnectFuture + Send + Sync;
+
+/// How a [super::FCPConnector] reestablishes a lost connection
+pub struct ReconnectPolicy {
+    pub connect: Box<ConnectFn>,
+    /// Wait before the first reconnect attempt, doubled after every failed attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Gives up after this many failed attempts in a row, retries forever if [None]
+    pub max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+    /// Reconnects forever with the default backoff
+    pub fn new(connect: Box<ConnectFn>) -> Self {
+        Self {
+            connect,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+            max_attempts: None,
+        }
+    }
+
+    pub fn tcp(address: SocketAddr) -> Self {
+        Self::new(Box::new(move || {
+            Box::pin(async move {
+                let stream = TcpStream::connect(address).await?;
+                Ok(Box::new(stream) as BoxedTransport)
+            })
+        }))
+    }
+
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self::new(Box::new(move || {
+            let path = path.clone();
+            Box::pin(async move {
+                let stream = UnixStream::connect(path).await?;
+                Ok(Box::new(stream) as BoxedTransport)
+            })
+        }))
+    }
+
+    pub(super) fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConnectionState {
+    Connected,
+    /// The connection was lost, `attempt` reconnects have failed so far
+    Reconnecting {
+        attempt: u32,
+    },
+    /// The connector stopped listening and will not reconnect
+    Closed {
+        reason: Box<str>,
+    },
+}
@@ -0,0 +1,319 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::dda::DiskAccess;
+use crate::fcp_connector::payload::PayloadReader;
+use crate::fcp_connector::scheduler::SchedulerPermit;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::messages::remove_request::RemoveRequestMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::ProtocolError;
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+use tokio::time::{Instant, Sleep};
+
+/// How a node message relates to the request it answers
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResponseKind {
+    /// Ends the request successfully
+    Success,
+    /// Ends the request with an error
+    Failure,
+    /// Informs about the request without ending it (e.g. `DataFound` before `AllData`)
+    Intermediate,
+}
+
+/// A client message the node answers with node messages carrying the same `Identifier`
+pub trait FCPRequest {
+    /// Terminal message if the request succeeded
+    type Success: TerminalMessage;
+    /// Terminal message if the request failed
+    type Failure: TerminalMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier;
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind;
+
+    /// Requests that are not scoped to the connection keep running on the node when their
+    /// [Response] is dropped or times out
+    fn persistence(&self) -> Persistence {
+        Persistence::Connection
+    }
+
+    /// Directories the node reads or writes directly, which are negotiated with
+    /// [crate::fcp_connector::FCPConnector::test_dda] before the request is sent
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        Vec::new()
+    }
+
+    /// Order in which the [crate::fcp_connector::scheduler::RequestScheduler] starts waiting
+    /// requests
+    fn priority(&self) -> PriorityClass {
+        PriorityClass::Medium
+    }
+
+    /// The message of a request that sends data directly, and that data borrowed from the request
+    ///
+    /// Sending the data straight from the request saves copying it into the [Message]. Requests
+    /// returning [None] are sent as the [Message] they convert into.
+    fn split_payload(&self) -> Option<(Message, &[u8])> {
+        None
+    }
+}
+
+/// A node message ending a request
+pub trait TerminalMessage: Sized {
+    /// Whether the payload is handed over as [PayloadReader] instead of being read into memory
+    const STREAMS_PAYLOAD: bool = false;
+
+    fn from_terminal(message: Message, payload: Option<PayloadReader>)
+        -> Result<Self, DecodeError>;
+}
+
+impl<T: TryFrom<Message, Error = DecodeError>> TerminalMessage for T {
+    fn from_terminal(message: Message, _: Option<PayloadReader>) -> Result<Self, DecodeError> {
+        T::try_from(message)
+    }
+}
+
+/// [FCPRequest::response_kind], except that a `ProtocolError` about the request ends it
+pub(super) fn terminal_response_kind<R: FCPRequest>(message_type: &MessageType) -> ResponseKind {
+    match message_type {
+        MessageType::Node(ProtocolError) => ResponseKind::Failure,
+        message_type => R::response_kind(message_type),
+    }
+}
+
+/// Failure type of requests the node only ever answers successfully
+#[derive(Debug)]
+pub enum NoFailure {}
+
+impl TryFrom<Message> for NoFailure {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        Err(DecodeError::ProtocolBreak(
+            format!("'{}' is not a failure", value.message_type().name()).into(),
+        ))
+    }
+}
+
+/// Routing entry of a request that has not been answered with a terminal message yet
+pub(super) struct PendingRequest {
+    pub(super) response_kind: fn(&MessageType) -> ResponseKind,
+    /// Whether the payload of the success message is streamed
+    pub(super) streams_payload: bool,
+    pub(super) terminal: oneshot::Sender<(Message, Option<PayloadReader>)>,
+    pub(super) intermediate: UnboundedSender<Message>,
+    /// Sent again after a reconnect to pick the request up on the new connection, only set for
+    /// requests the node keeps across connections
+    pub(super) reattach: Option<Message>,
+}
+
+impl PendingRequest {
+    pub(super) fn streams_payload(&self, message_type: &MessageType) -> bool {
+        self.streams_payload && (self.response_kind)(message_type) == ResponseKind::Success
+    }
+
+    /// Forwards the message and hands the request back if it is not finished yet
+    pub(super) fn dispatch(self, message: Message, payload: Option<PayloadReader>) -> Option<Self> {
+        match (self.response_kind)(&message.message_type()) {
+            ResponseKind::Intermediate => {
+                let _ = self.intermediate.send(message);
+                Some(self)
+            }
+            ResponseKind::Success | ResponseKind::Failure => {
+                let _ = self.terminal.send((message, payload));
+                None
+            }
+        }
+    }
+}
+
+/// A [Response] or subscription that stopped waiting for its request
+pub(super) struct Cancellation {
+    pub(super) identifier: UniqueIdentifier,
+    /// Sent to the node to stop the request there as well
+    pub(super) stop: Option<Message>,
+}
+
+/// Resolves to the terminal message of a request sent with
+/// [crate::fcp_connector::FCPConnector::request]
+///
+/// Dropping it before the request finished cancels the request on the node, unless the request
+/// is persistent.
+pub struct Response<R: FCPRequest> {
+    identifier: UniqueIdentifier,
+    /// Not set if the deadline passed before the request could be sent
+    terminal: Option<oneshot::Receiver<(Message, Option<PayloadReader>)>>,
+    deadline: Option<Pin<Box<Sleep>>>,
+    /// Set while the request is still running on the node
+    cancel: Option<UnboundedSender<Cancellation>>,
+    persistent: bool,
+    /// Slot of the request in the scheduler, freed once the request finished or is cancelled
+    permit: Option<SchedulerPermit>,
+    request: PhantomData<fn() -> R>,
+}
+
+impl<R: FCPRequest> Response<R> {
+    pub(super) fn new(
+        identifier: UniqueIdentifier,
+        terminal: oneshot::Receiver<(Message, Option<PayloadReader>)>,
+        deadline: Option<Instant>,
+        cancel: UnboundedSender<Cancellation>,
+        persistent: bool,
+        permit: Option<SchedulerPermit>,
+    ) -> Self {
+        Self {
+            identifier,
+            terminal: Some(terminal),
+            deadline: deadline.map(|deadline| Box::pin(tokio::time::sleep_until(deadline))),
+            cancel: Some(cancel),
+            persistent,
+            permit,
+            request: PhantomData,
+        }
+    }
+
+    /// Resolves to [RequestError::TimedOut] right away, for a request that was never sent
+    pub(super) fn timed_out(identifier: UniqueIdentifier) -> Self {
+        Self {
+            identifier,
+            terminal: None,
+            deadline: None,
+            cancel: None,
+            persistent: false,
+            permit: None,
+            request: PhantomData,
+        }
+    }
+
+    pub fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn cancel(&mut self) {
+        self.permit = None;
+        if let Some(cancel) = self.cancel.take() {
+            let stop = (!self.persistent).then(|| {
+                RemoveRequestMessage {
+                    identifier: self.identifier.clone(),
+                    global: false,
+                }
+                .into()
+            });
+            let _ = cancel.send(Cancellation {
+                identifier: self.identifier.clone(),
+                stop,
+            });
+        }
+    }
+}
+
+impl<R: FCPRequest> Drop for Response<R> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl<R: FCPRequest> Future for Response<R> {
+    type Output = Result<R::Success, RequestError<R::Failure>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let Some(terminal) = this.terminal.as_mut() else {
+            return Poll::Ready(Err(RequestError::TimedOut));
+        };
+
+        let (message, payload) = match Pin::new(terminal).poll(cx) {
+            Poll::Ready(Ok(terminal)) => terminal,
+            Poll::Ready(Err(_)) => {
+                this.permit = None;
+                return Poll::Ready(Err(RequestError::ConnectionClosed));
+            }
+            Poll::Pending => {
+                let timed_out = this
+                    .deadline
+                    .as_mut()
+                    .map(|deadline| deadline.as_mut().poll(cx).is_ready())
+                    .unwrap_or(false);
+
+                if timed_out {
+                    this.cancel();
+                    return Poll::Ready(Err(RequestError::TimedOut));
+                }
+                return Poll::Pending;
+            }
+        };
+        this.cancel = None;
+        this.permit = None;
+
+        let res = match message.message_type() {
+            MessageType::Node(ProtocolError) => match ProtocolErrorMessage::try_from(message) {
+                Ok(error) => Err(RequestError::ProtocolError(error)),
+                Err(err) => Err(RequestError::DecodeError(err)),
+            },
+            message_type => match R::response_kind(&message_type) {
+                ResponseKind::Success => {
+                    R::Success::from_terminal(message, payload).map_err(RequestError::from)
+                }
+                _ => match R::Failure::from_terminal(message, payload) {
+                    Ok(failure) => Err(RequestError::Failed(failure)),
+                    Err(err) => Err(RequestError::DecodeError(err)),
+                },
+            },
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+#[derive(Debug)]
+pub enum RequestError<F> {
+    /// The node answered with the failure message of the request
+    Failed(F),
+    /// The node refused the request, e.g. as a field was missing
+    ProtocolError(ProtocolErrorMessage),
+    DecodeError(DecodeError),
+    /// The connection stopped before the request was answered
+    ConnectionClosed,
+    /// The request was not answered before its deadline and has been cancelled, persistent
+    /// requests keep running on the node
+    TimedOut,
+}
+
+impl<F: Debug> Display for RequestError<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestError::Failed(inner) => write!(f, "Request failed: {inner:?}"),
+            RequestError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            RequestError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            RequestError::ConnectionClosed => {
+                write!(f, "Connection closed before the request was answered")
+            }
+            RequestError::TimedOut => write!(f, "Request timed out"),
+        }
+    }
+}
+
+impl<F: Debug> Error for RequestError<F> {}
+
+impl<F> From<DecodeError> for RequestError<F> {
+    fn from(value: DecodeError) -> Self {
+        RequestError::DecodeError(value)
+    }
+}
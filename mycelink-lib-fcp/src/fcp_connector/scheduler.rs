@@ -0,0 +1,320 @@
+//! Limits how many requests of a type run on the node at once
+//!
+//! Waiting requests are started by [PriorityClass], callers with fewer running requests first
+//! within the same class and in the order they were queued otherwise.
+
+use crate::model::message_type_identifier::ClientMessageType;
+use crate::model::priority_class::PriorityClass;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Caller of requests that were not started for a specific caller
+pub const DEFAULT_CALLER: &str = "default";
+
+/// Hands out slots to run requests, cloning it shares the slots
+#[derive(Clone, Default)]
+pub struct RequestScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    /// Types without a limit run as many requests as asked for
+    limits: HashMap<ClientMessageType, usize>,
+    running: HashMap<ClientMessageType, usize>,
+    running_by_caller: HashMap<Box<str>, usize>,
+    queue: Vec<Waiting>,
+    next_sequence: u64,
+}
+
+struct Waiting {
+    message_type: ClientMessageType,
+    priority: PriorityClass,
+    caller: Box<str>,
+    sequence: u64,
+    slot: oneshot::Sender<SchedulerPermit>,
+}
+
+/// Queue depth and running requests at one point in time
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SchedulerMetrics {
+    pub queued: HashMap<ClientMessageType, usize>,
+    pub running: HashMap<ClientMessageType, usize>,
+    pub queued_by_caller: HashMap<Box<str>, usize>,
+    pub running_by_caller: HashMap<Box<str>, usize>,
+}
+
+impl SchedulerMetrics {
+    pub fn total_queued(&self) -> usize {
+        self.queued.values().sum()
+    }
+
+    pub fn total_running(&self) -> usize {
+        self.running.values().sum()
+    }
+}
+
+/// A running request, frees its slot when dropped
+#[must_use]
+pub struct SchedulerPermit {
+    scheduler: RequestScheduler,
+    message_type: ClientMessageType,
+    caller: Box<str>,
+}
+
+impl RequestScheduler {
+    /// A scheduler without limits, see [RequestScheduler::set_limit]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs at most `limit` requests of `message_type` at once, queued requests are started if
+    /// the limit was raised
+    pub fn set_limit(&self, message_type: ClientMessageType, limit: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.limits.insert(message_type, limit);
+        let refused = state.start_queued(self);
+        drop(state);
+        drop(refused);
+    }
+
+    pub fn remove_limit(&self, message_type: ClientMessageType) {
+        let mut state = self.state.lock().unwrap();
+        state.limits.remove(&message_type);
+        let refused = state.start_queued(self);
+        drop(state);
+        drop(refused);
+    }
+
+    pub fn limit(&self, message_type: ClientMessageType) -> Option<usize> {
+        self.state
+            .lock()
+            .unwrap()
+            .limits
+            .get(&message_type)
+            .copied()
+    }
+
+    /// Waits until a request of `message_type` may run for `caller`
+    pub async fn acquire(
+        &self,
+        message_type: ClientMessageType,
+        priority: PriorityClass,
+        caller: &str,
+    ) -> SchedulerPermit {
+        let slot = {
+            let mut state = self.state.lock().unwrap();
+            let queued = state.queue.iter().any(|e| e.message_type == message_type);
+            if !queued && state.has_free_slot(message_type) {
+                return state.start(self, message_type, caller.into());
+            }
+
+            let (slot_tx, slot_rx) = oneshot::channel();
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.queue.push(Waiting {
+                message_type,
+                priority,
+                caller: caller.into(),
+                sequence,
+                slot: slot_tx,
+            });
+            slot_rx
+        };
+
+        // The sender is only dropped with the scheduler, which is kept alive by `self`
+        slot.await.expect("Scheduler dropped a queued request")
+    }
+
+    pub fn metrics(&self) -> SchedulerMetrics {
+        let state = self.state.lock().unwrap();
+        let mut metrics = SchedulerMetrics {
+            running: state.running.clone(),
+            running_by_caller: state.running_by_caller.clone(),
+            ..SchedulerMetrics::default()
+        };
+        for waiting in state.queue.iter().filter(|e| !e.slot.is_closed()) {
+            *metrics.queued.entry(waiting.message_type).or_default() += 1;
+            *metrics
+                .queued_by_caller
+                .entry(waiting.caller.clone())
+                .or_default() += 1;
+        }
+        metrics.running.retain(|_, running| *running > 0);
+        metrics
+    }
+
+    fn release(&self, message_type: ClientMessageType, caller: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(running) = state.running.get_mut(&message_type) {
+            *running -= 1;
+        }
+        if let Some(running) = state.running_by_caller.get_mut(caller) {
+            *running -= 1;
+            if *running == 0 {
+                state.running_by_caller.remove(caller);
+            }
+        }
+        let refused = state.start_queued(self);
+        drop(state);
+        drop(refused);
+    }
+}
+
+impl SchedulerState {
+    fn has_free_slot(&self, message_type: ClientMessageType) -> bool {
+        match self.limits.get(&message_type) {
+            None => true,
+            Some(limit) => self.running.get(&message_type).copied().unwrap_or(0) < *limit,
+        }
+    }
+
+    fn start(
+        &mut self,
+        scheduler: &RequestScheduler,
+        message_type: ClientMessageType,
+        caller: Box<str>,
+    ) -> SchedulerPermit {
+        *self.running.entry(message_type).or_default() += 1;
+        *self.running_by_caller.entry(caller.clone()).or_default() += 1;
+        SchedulerPermit {
+            scheduler: scheduler.clone(),
+            message_type,
+            caller,
+        }
+    }
+
+    /// Starts waiting requests while there are free slots
+    ///
+    /// Permits of callers that stopped waiting are returned, they must be dropped after the state
+    /// is unlocked as dropping them frees their slot again.
+    fn start_queued(&mut self, scheduler: &RequestScheduler) -> Vec<SchedulerPermit> {
+        let mut refused = Vec::new();
+        loop {
+            self.queue.retain(|e| !e.slot.is_closed());
+            let Some(index) = self.next_index() else {
+                return refused;
+            };
+            let waiting = self.queue.swap_remove(index);
+            let permit = self.start(scheduler, waiting.message_type, waiting.caller);
+            if let Err(permit) = waiting.slot.send(permit) {
+                refused.push(permit);
+            }
+        }
+    }
+
+    /// Waiting request that goes next among those of types with a free slot
+    fn next_index(&self) -> Option<usize> {
+        self.queue
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| self.has_free_slot(e.message_type))
+            .min_by_key(|(_, e)| {
+                (
+                    std::cmp::Reverse(e.priority),
+                    self.running_by_caller.get(&e.caller).copied().unwrap_or(0),
+                    e.sequence,
+                )
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(self.message_type, &self.caller);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fcp_connector::scheduler::{RequestScheduler, SchedulerPermit};
+    use crate::model::message_type_identifier::ClientMessageType::{ClientGet, ClientPut};
+    use crate::model::priority_class::PriorityClass;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    /// Queues a `ClientGet` for each caller, started ones are sent back with their caller
+    async fn queue_gets(
+        scheduler: &RequestScheduler,
+        callers: &[(&'static str, PriorityClass)],
+    ) -> UnboundedReceiver<(&'static str, SchedulerPermit)> {
+        let (started_tx, started_rx) = unbounded_channel();
+        for (caller, priority) in callers.iter().copied() {
+            let scheduler = scheduler.clone();
+            let started_tx = started_tx.clone();
+            tokio::spawn(async move {
+                let permit = scheduler.acquire(ClientGet, priority, caller).await;
+                let _ = started_tx.send((caller, permit));
+            });
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(scheduler.metrics().total_queued(), callers.len());
+        started_rx
+    }
+
+    #[tokio::test]
+    async fn test_limit_and_priority() {
+        let scheduler = RequestScheduler::new();
+        scheduler.set_limit(ClientGet, 1);
+        let running = scheduler
+            .acquire(ClientGet, PriorityClass::Medium, "first")
+            .await;
+
+        let mut started = queue_gets(
+            &scheduler,
+            &[("low", PriorityClass::Low), ("high", PriorityClass::High)],
+        )
+        .await;
+        assert_eq!(scheduler.metrics().running.get(&ClientGet), Some(&1));
+
+        drop(running);
+        let (caller, running) = started.recv().await.unwrap();
+        assert_eq!(caller, "high");
+        assert!(started.try_recv().is_err());
+
+        drop(running);
+        assert_eq!(started.recv().await.unwrap().0, "low");
+    }
+
+    #[tokio::test]
+    async fn test_fair_sharing() {
+        let scheduler = RequestScheduler::new();
+        scheduler.set_limit(ClientGet, 1);
+        let _transfer = scheduler
+            .acquire(ClientPut, PriorityClass::Medium, "media")
+            .await;
+        let running = scheduler
+            .acquire(ClientGet, PriorityClass::Medium, "media")
+            .await;
+
+        let mut started = queue_gets(
+            &scheduler,
+            &[
+                ("media", PriorityClass::Medium),
+                ("poller", PriorityClass::Medium),
+            ],
+        )
+        .await;
+        assert_eq!(scheduler.metrics().queued_by_caller.get("poller"), Some(&1));
+
+        drop(running);
+        assert_eq!(started.recv().await.unwrap().0, "poller");
+    }
+
+    #[tokio::test]
+    async fn test_raised_limit_starts_queued() {
+        let scheduler = RequestScheduler::new();
+        scheduler.set_limit(ClientGet, 0);
+        let mut started = queue_gets(&scheduler, &[("poller", PriorityClass::Medium)]).await;
+
+        scheduler.remove_limit(ClientGet);
+        let (_, running) = started.recv().await.unwrap();
+        let metrics = scheduler.metrics();
+        assert_eq!(metrics.total_queued(), 0);
+        assert_eq!(metrics.total_running(), 1);
+
+        drop(running);
+        assert!(scheduler.metrics().running_by_caller.is_empty());
+    }
+}
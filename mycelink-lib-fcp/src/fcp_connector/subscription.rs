@@ -0,0 +1,437 @@
+use crate::decode_error::DecodeError;
+use crate::fcp_connector::request::{Cancellation, PendingRequest, ResponseKind};
+use crate::fcp_connector::FCPConnector;
+use crate::messages::protocol_error::ProtocolErrorMessage;
+use crate::messages::subscribe_usk::SubscribeUSKMessage;
+use crate::messages::subscribed_usk::SubscribedUSKMessage;
+use crate::messages::subscribed_usk_round_finished::SubscribedUSKRoundFinishedMessage;
+use crate::messages::subscribed_usk_sending_to_network::SubscribedUSKSendingToNetworkMessage;
+use crate::messages::subscribed_usk_update::SubscribedUSKUpdateMessage;
+use crate::messages::unsubscribe_usk::UnsubscribeUSKMessage;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    ProtocolError, SubscribedUSK, SubscribedUSKRoundFinished, SubscribedUSKSendingToNetwork,
+    SubscribedUSKUpdate,
+};
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+/// What the node found out about a subscribed USK
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum USKEvent {
+    Update(SubscribedUSKUpdateMessage),
+    RoundFinished(SubscribedUSKRoundFinishedMessage),
+    SendingToNetwork(SubscribedUSKSendingToNetworkMessage),
+}
+
+impl TryFrom<Message> for USKEvent {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        Ok(match value.message_type() {
+            MessageType::Node(SubscribedUSKUpdate) => USKEvent::Update(value.try_into()?),
+            MessageType::Node(SubscribedUSKRoundFinished) => {
+                USKEvent::RoundFinished(value.try_into()?)
+            }
+            MessageType::Node(SubscribedUSKSendingToNetwork) => {
+                USKEvent::SendingToNetwork(value.try_into()?)
+            }
+            got => {
+                return Err(DecodeError::ProtocolBreak(
+                    format!("'{}' is no USK subscription event", got.name()).into(),
+                ))
+            }
+        })
+    }
+}
+
+/// A running `SubscribeUSK`, unsubscribes when dropped
+///
+/// The USK is subscribed again after a reconnect, which may report editions found before once
+/// more. Ends once the connection closed for good.
+pub struct USKSubscription {
+    identifier: UniqueIdentifier,
+    uri: URI,
+    events: UnboundedReceiver<Message>,
+    cancel: UnboundedSender<Cancellation>,
+}
+
+impl USKSubscription {
+    pub fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    /// The subscribed USK as confirmed by the node
+    pub fn uri(&self) -> &URI {
+        &self.uri
+    }
+
+    /// Waits for the next event, skipping all other messages about the subscription
+    pub async fn next(&mut self) -> Option<USKEvent> {
+        loop {
+            let message = self.events.recv().await?;
+            // Confirms the subscription again after a reconnect
+            if message
+                .message_type()
+                .is_specific_node_message(SubscribedUSK)
+            {
+                continue;
+            }
+            match USKEvent::try_from(message) {
+                Ok(event) => return Some(event),
+                Err(err) => log::warn!("Skipping message of USK subscription: {err}"),
+            }
+        }
+    }
+}
+
+impl Drop for USKSubscription {
+    fn drop(&mut self) {
+        let unsubscribe = UnsubscribeUSKMessage {
+            identifier: self.identifier.clone(),
+        };
+        let _ = self.cancel.send(Cancellation {
+            identifier: self.identifier.clone(),
+            stop: Some(unsubscribe.into()),
+        });
+    }
+}
+
+impl FCPConnector {
+    /// Subscribes to a USK and waits until the node confirmed the subscription
+    ///
+    /// Editions older than the one in the `uri` of `subscribe` are not reported.
+    pub async fn subscribe_usk(
+        &self,
+        subscribe: SubscribeUSKMessage,
+        timeout: Option<Duration>,
+    ) -> Result<USKSubscription, SubscriptionError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let identifier = subscribe.identifier.clone();
+        let uri = subscribe.uri.clone();
+
+        // A subscription never ends on its own, so the terminal channel is never used
+        let (terminal, _) = oneshot::channel();
+        let (intermediate, events) = unbounded_channel();
+        let subscribe: Message = subscribe.into();
+        let pending = PendingRequest {
+            response_kind: |_| ResponseKind::Intermediate,
+            streams_payload: false,
+            terminal,
+            intermediate,
+            reattach: Some(subscribe.clone()),
+        };
+        self.insert_request(&identifier, pending, self.send(subscribe))
+            .await?;
+
+        let mut subscription = USKSubscription {
+            identifier,
+            uri,
+            events,
+            cancel: self.cancel_tx.clone(),
+        };
+
+        let confirmation = async { subscription.events.recv().await };
+        let confirmation = match deadline {
+            None => confirmation.await,
+            Some(deadline) => tokio::time::timeout_at(deadline, confirmation)
+                .await
+                .map_err(|_| SubscriptionError::TimedOut)?,
+        };
+        let message = confirmation.ok_or(SubscriptionError::ConnectionClosed)?;
+
+        match message.message_type() {
+            MessageType::Node(SubscribedUSK) => {
+                let subscribed: SubscribedUSKMessage = message.try_into()?;
+                subscription.uri = subscribed.uri;
+                Ok(subscription)
+            }
+            MessageType::Node(ProtocolError) => Err(SubscriptionError::ProtocolError(
+                ProtocolErrorMessage::try_from(message)?,
+            )),
+            got => Err(DecodeError::ExpectedDifferentMessageType {
+                expected: MessageType::Node(SubscribedUSK),
+                got,
+            }
+            .into()),
+        }
+    }
+
+    /// Looks up the latest edition of a USK known after one polling round, `None` if no
+    /// edition at least as new as the one in `uri` was found
+    pub async fn latest_usk_edition(
+        &self,
+        uri: URI,
+        timeout: Option<Duration>,
+    ) -> Result<Option<i64>, SubscriptionError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let subscribe = SubscribeUSKMessage {
+            uri,
+            dont_poll: false,
+            identifier: UniqueIdentifier::new("Latest USK edition")
+                .map_err(tokio::io::Error::from)?,
+            priority_class: PriorityClass::High,
+            real_time: true,
+            sparse_poll: false,
+            ignore_usk_datehints: false,
+        };
+        let mut subscription = self.subscribe_usk(subscribe, timeout).await?;
+
+        let mut latest = None;
+        loop {
+            let event = match deadline {
+                None => subscription.next().await,
+                Some(deadline) => tokio::time::timeout_at(deadline, subscription.next())
+                    .await
+                    .map_err(|_| SubscriptionError::TimedOut)?,
+            };
+
+            match event.ok_or(SubscriptionError::ConnectionClosed)? {
+                USKEvent::Update(update) => latest = latest.max(Some(update.edition)),
+                USKEvent::RoundFinished(_) => return Ok(latest),
+                USKEvent::SendingToNetwork(_) => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SubscriptionError {
+    TokioIo(tokio::io::Error),
+    DecodeError(DecodeError),
+    /// The node refused the subscription, e.g. as the URI is no USK
+    ProtocolError(ProtocolErrorMessage),
+    /// The connection stopped before the node answered
+    ConnectionClosed,
+    TimedOut,
+}
+
+impl Display for SubscriptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionError::TokioIo(inner) => write!(f, "TokioIoError: {inner}"),
+            SubscriptionError::DecodeError(inner) => write!(f, "DecodeError: {inner}"),
+            SubscriptionError::ProtocolError(inner) => write!(
+                f,
+                "ProtocolError {}: {}",
+                u32::from(inner.code),
+                inner.code_description
+            ),
+            SubscriptionError::ConnectionClosed => {
+                write!(f, "Connection closed before the node answered")
+            }
+            SubscriptionError::TimedOut => write!(f, "Subscription timed out"),
+        }
+    }
+}
+
+impl Error for SubscriptionError {}
+
+impl From<tokio::io::Error> for SubscriptionError {
+    fn from(value: tokio::io::Error) -> Self {
+        SubscriptionError::TokioIo(value)
+    }
+}
+
+impl From<DecodeError> for SubscriptionError {
+    fn from(value: DecodeError) -> Self {
+        SubscriptionError::DecodeError(value)
+    }
+}
+
+#[cfg(all(test, feature = "mock_node"))]
+mod tests {
+    use crate::fcp_connector::reconnect::ReconnectPolicy;
+    use crate::fcp_connector::subscription::USKEvent;
+    use crate::fcp_connector::FCPConnector;
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::messages::subscribe_usk::SubscribeUSKMessage;
+    use crate::mock_node::{random_key, MockNode};
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::uri::URI;
+    use crate::model::verbosity::Verbosity;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn usk(edition: i64) -> (String, URI) {
+        let base = format!("USK@{},{},AQACAAE/profile", random_key(), random_key());
+        let uri = format!("{base}/{edition}").as_str().try_into().unwrap();
+        (base, uri)
+    }
+
+    fn subscribe(uri: URI) -> SubscribeUSKMessage {
+        SubscribeUSKMessage {
+            uri,
+            dont_poll: false,
+            identifier: UniqueIdentifier::new("Subscription test").unwrap(),
+            priority_class: PriorityClass::Medium,
+            real_time: true,
+            sparse_poll: false,
+            ignore_usk_datehints: false,
+        }
+    }
+
+    fn put(uri: &str) -> ClientPutMessage {
+        ClientPutMessage {
+            uri: uri.try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Subscription insert").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::Medium,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Direct {
+                data: b"Edition".as_slice().into(),
+            },
+            is_binary_blob: false,
+            real_time: false,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_updates() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Subscription test").await;
+        let (base, uri) = usk(0);
+
+        let mut subscription = connector
+            .subscribe_usk(subscribe(uri), TIMEOUT)
+            .await
+            .unwrap();
+        assert!(matches!(
+            subscription.next().await,
+            Some(USKEvent::RoundFinished(_))
+        ));
+
+        for edition in [0, 1] {
+            let (response, _) = connector
+                .request(&put(&format!("{base}/{edition}")), None)
+                .await
+                .unwrap();
+            response.await.unwrap();
+        }
+
+        for edition in [0, 1] {
+            let Some(USKEvent::Update(update)) = subscription.next().await else {
+                panic!("Expected SubscribedUSKUpdate");
+            };
+            assert_eq!(update.edition, edition);
+            assert_eq!(update.uri.edition(), Some(edition));
+            assert_eq!(update.identifier, *subscription.identifier());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_on_drop() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Subscription test").await;
+        let (_, uri) = usk(0);
+
+        let subscription = connector
+            .subscribe_usk(subscribe(uri), TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(node.subscriptions().await.len(), 1);
+
+        drop(subscription);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !node.subscriptions().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_latest_usk_edition() {
+        let node = MockNode::start().await.unwrap();
+        let connector = node.connector("Subscription test").await;
+        let (base, uri) = usk(0);
+
+        assert_eq!(
+            connector
+                .latest_usk_edition(uri.clone(), TIMEOUT)
+                .await
+                .unwrap(),
+            None
+        );
+
+        for edition in 0..3 {
+            node.insert(&format!("{base}/{edition}"), b"Profile".as_slice())
+                .await;
+        }
+        assert_eq!(
+            connector.latest_usk_edition(uri, TIMEOUT).await.unwrap(),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscription_survives_reconnect() {
+        let node = MockNode::start().await.unwrap();
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(10),
+            max_attempts: Some(10),
+            ..ReconnectPolicy::tcp(node.address())
+        };
+        let connector = Arc::new(
+            FCPConnector::new_reconnecting("Subscription test", policy)
+                .await
+                .unwrap(),
+        );
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+        let (base, uri) = usk(0);
+
+        let mut subscription = connector
+            .subscribe_usk(subscribe(uri), TIMEOUT)
+            .await
+            .unwrap();
+        assert!(matches!(
+            subscription.next().await,
+            Some(USKEvent::RoundFinished(_))
+        ));
+
+        node.disconnect_all().await;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while node.subscriptions().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            subscription.next().await,
+            Some(USKEvent::RoundFinished(_))
+        ));
+
+        let (response, _) = connector
+            .request(&put(&format!("{base}/0")), None)
+            .await
+            .unwrap();
+        response.await.unwrap();
+        let Some(USKEvent::Update(update)) = subscription.next().await else {
+            panic!("Expected SubscribedUSKUpdate");
+        };
+        assert_eq!(update.edition, 0);
+    }
+}
@@ -0,0 +1,16 @@
+use tokio::io::{split, AsyncRead, AsyncWrite};
+
+/// A bidirectional byte stream FCP can be spoken over (TCP, Unix sockets, TLS, in-memory pipes…)
+pub trait FCPTransport: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> FCPTransport for T {}
+
+pub type BoxedTransport = Box<dyn FCPTransport>;
+
+pub type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+pub type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+pub(super) fn split_transport(transport: impl FCPTransport) -> (BoxedReader, BoxedWriter) {
+    let (reader, writer) = split(transport);
+    (Box::new(reader), Box::new(writer))
+}
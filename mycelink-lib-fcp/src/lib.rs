@@ -1,5 +1,9 @@
+pub mod codec;
 pub mod decode_error;
+pub mod encode_error;
 pub mod fcp_connector;
+pub mod message_reader;
 pub mod messages;
+#[cfg(feature = "mock_node")]
+pub mod mock_node;
 pub mod model;
-pub mod peekable_reader;
@@ -0,0 +1,59 @@
+use crate::codec::{FCPCodec, Frame};
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads [Message]s from a tokio stream using [FCPCodec]
+pub struct MessageReader<T: AsyncRead> {
+    inner: T,
+    buf: BytesMut,
+    codec: FCPCodec,
+}
+
+impl<T: AsyncRead + Unpin> MessageReader<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            buf: BytesMut::new(),
+            codec: FCPCodec::new(),
+        }
+    }
+
+    /// Reads the next message
+    ///
+    /// This is cancel safe, a partially read message is continued by the next call.
+    pub async fn read_message(&mut self) -> Result<Message, DecodeError> {
+        loop {
+            if let Some(message) = self.codec.decode(&mut self.buf)? {
+                return Ok(message);
+            }
+
+            self.buf.reserve(READ_CHUNK_SIZE);
+            if self.inner.read_buf(&mut self.buf).await? == 0 {
+                return self
+                    .codec
+                    .decode_eof(&mut self.buf)?
+                    .ok_or(DecodeError::UnexpectedEOF);
+            }
+        }
+    }
+
+    /// Reads the next [Frame], see [FCPCodec::decode_frame]
+    ///
+    /// This is cancel safe as well.
+    pub async fn read_frame(&mut self) -> Result<Frame, DecodeError> {
+        loop {
+            if let Some(frame) = self.codec.decode_frame(&mut self.buf)? {
+                return Ok(frame);
+            }
+
+            self.buf.reserve(READ_CHUNK_SIZE);
+            if self.inner.read_buf(&mut self.buf).await? == 0 {
+                return Err(DecodeError::UnexpectedEOF);
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::AddPeer;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::node_ref::NodeRef;
+use crate::model::peer_trust::PeerTrust;
+use crate::model::peer_visibility::PeerVisibility;
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::path::Path;
+
+/// Adds a darknet peer, answered with its `Peer`
+pub struct AddPeerMessage {
+    pub identifier: UniqueIdentifier,
+    pub trust: PeerTrust,
+    pub visibility: PeerVisibility,
+    pub node_ref: AddPeerSource,
+}
+
+/// Where the node gets the reference of the new peer from
+pub enum AddPeerSource {
+    NodeRef(NodeRef),
+    /// A file with the reference readable by the node
+    File(Box<Path>),
+    /// A URL the node downloads the reference from
+    URL(Box<str>),
+}
+
+impl From<&AddPeerMessage> for Message {
+    fn from(value: &AddPeerMessage) -> Self {
+        let mut fields = match &value.node_ref {
+            AddPeerSource::NodeRef(node_ref) => node_ref.fields().clone(),
+            AddPeerSource::File(path) => {
+                let mut fields = FieldSet::new();
+                fields.set("File", path.to_string_lossy());
+                fields
+            }
+            AddPeerSource::URL(url) => {
+                let mut fields = FieldSet::new();
+                fields.set("URL", url.clone());
+                fields
+            }
+        };
+        fields.set("Identifier", &value.identifier);
+        fields.set("Trust", &value.trust);
+        fields.set("Visibility", &value.visibility);
+
+        Self::new(Client(AddPeer), (&fields).into(), None)
+    }
+}
@@ -1,4 +1,6 @@
 use crate::decode_error::DecodeError;
+use crate::fcp_connector::payload::PayloadReader;
+use crate::fcp_connector::request::TerminalMessage;
 use crate::model::content_type::ContentType;
 use crate::model::message::Message;
 use crate::model::message_type_identifier::NodeMessageType;
@@ -36,6 +38,42 @@ impl TryFrom<Message> for AllDataMessage {
     }
 }
 
+/// An [AllDataMessage] whose data is read from the connection while it is consumed
+#[derive(Debug)]
+pub struct AllDataStream {
+    pub identifier: UniqueIdentifier,
+    pub content_type: ContentType,
+
+    pub data: PayloadReader,
+}
+
+impl TerminalMessage for AllDataStream {
+    const STREAMS_PAYLOAD: bool = true;
+
+    fn from_terminal(
+        message: Message,
+        payload: Option<PayloadReader>,
+    ) -> Result<Self, DecodeError> {
+        message
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::AllData)?;
+
+        Ok(Self {
+            identifier: message
+                .fields()
+                .get_or_err("Identifier")?
+                .value()
+                .try_into()?,
+            content_type: message
+                .fields()
+                .get_or_err("Metadata.ContentType")?
+                .value()
+                .parse()?,
+            data: payload.ok_or(DecodeError::MissingPayload)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::messages::all_data::AllDataMessage;
@@ -49,13 +87,13 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let identifier = UniqueIdentifier::new("Test");
+        let identifier = UniqueIdentifier::new("Test").unwrap();
 
         let message = Message::new(
             Node(AllData),
             vec![
-                Field::new("Identifier".into(), identifier.to_string().into()),
-                Field::new(
+                Field::unvalidated("Identifier".into(), identifier.to_string().into()),
+                Field::unvalidated(
                     "Metadata.ContentType".into(),
                     "text/plain;charset=utf8".into(),
                 ),
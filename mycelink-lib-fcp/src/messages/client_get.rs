@@ -1,13 +1,21 @@
+use crate::fcp_connector::dda::DiskAccess;
+use crate::fcp_connector::request::{FCPRequest, ResponseKind};
+use crate::messages::all_data::{AllDataMessage, AllDataStream};
+use crate::messages::data_found::DataFoundMessage;
+use crate::messages::get_failed::GetFailedMessage;
 use crate::model::fields::Field;
 use crate::model::message::Message;
 use crate::model::message_type_identifier::ClientMessageType::ClientGet;
-use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::MessageType::{Client, Node};
+use crate::model::message_type_identifier::NodeMessageType::{AllData, DataFound, GetFailed};
 use crate::model::persistence::Persistence;
 use crate::model::priority_class::PriorityClass;
 use crate::model::return_type::ReturnType;
 use crate::model::unique_identifier::UniqueIdentifier;
 use crate::model::uri::URI;
 use crate::model::verbosity::Verbosity;
+use std::path::Path;
 
 pub struct ClientGetMessage {
     pub identifier: UniqueIdentifier,
@@ -29,31 +37,37 @@ pub struct ClientGetMessage {
 impl From<&ClientGetMessage> for Message {
     fn from(value: &ClientGetMessage) -> Self {
         let mut fields = vec![
-            Field::new("Identifier".into(), (&value.identifier).into()),
-            Field::new("URI".into(), (&value.uri).into()),
-            Field::new("Verbosity".into(), (&value.verbosity).into()),
-            Field::new("ReturnType".into(), (&value.return_type).into()),
-            Field::new("MaxRetries".into(), value.max_retries.to_string().into()),
-            Field::new("Priority".into(), (&value.priority).into()),
-            Field::new("Persistence".into(), (&value.persistence).into()),
-            Field::new(
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("URI".into(), (&value.uri).into()),
+            Field::unvalidated("Verbosity".into(), (&value.verbosity).into()),
+            Field::unvalidated("ReturnType".into(), (&value.return_type).into()),
+            Field::unvalidated("MaxRetries".into(), value.max_retries.to_string().into()),
+            Field::unvalidated("PriorityClass".into(), (&value.priority).into()),
+            Field::unvalidated("Persistence".into(), (&value.persistence).into()),
+            Field::unvalidated(
                 "IgnoreDS".into(),
                 value.ignore_data_store.to_string().into(),
             ),
-            Field::new("DSonly".into(), value.data_store_only.to_string().into()),
-            Field::new("RealTimeFlag".into(), value.real_time.to_string().into()),
+            Field::unvalidated("DSonly".into(), value.data_store_only.to_string().into()),
+            Field::unvalidated("RealTimeFlag".into(), value.real_time.to_string().into()),
             #[cfg(feature = "local_only")]
-            Field::new("DSonly".into(), true.to_string().into()),
+            Field::unvalidated("DSonly".into(), true.to_string().into()),
         ];
 
         if let ReturnType::Disk { path } = &value.return_type {
-            fields.push(Field::new("Filename".into(), path.to_string_lossy().into()));
+            fields.push(Field::unvalidated(
+                "Filename".into(),
+                path.to_string_lossy().into(),
+            ));
         }
         if let Some(max_size) = value.max_size {
-            fields.push(Field::new("MaxSize".into(), max_size.to_string().into()));
+            fields.push(Field::unvalidated(
+                "MaxSize".into(),
+                max_size.to_string().into(),
+            ));
         }
         if let Some(max_temp_size) = value.max_temp_size {
-            fields.push(Field::new(
+            fields.push(Field::unvalidated(
                 "MaxTempSize".into(),
                 max_temp_size.to_string().into(),
             ));
@@ -62,3 +76,109 @@ impl From<&ClientGetMessage> for Message {
         Message::new(Client(ClientGet), fields.into(), None)
     }
 }
+
+/// A [ClientGetMessage] with [ReturnType::Disk] or [ReturnType::None], which ends with `DataFound`
+/// as the data is not sent over the connection
+pub struct DiskClientGetMessage(pub ClientGetMessage);
+
+impl From<&DiskClientGetMessage> for Message {
+    fn from(value: &DiskClientGetMessage) -> Self {
+        (&value.0).into()
+    }
+}
+
+/// A [ClientGetMessage] whose data is streamed from the connection instead of being read into
+/// memory first
+pub struct StreamedClientGetMessage(pub ClientGetMessage);
+
+impl From<&StreamedClientGetMessage> for Message {
+    fn from(value: &StreamedClientGetMessage) -> Self {
+        (&value.0).into()
+    }
+}
+
+impl FCPRequest for ClientGetMessage {
+    type Success = AllDataMessage;
+    type Failure = GetFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        match message_type {
+            Node(AllData) => ResponseKind::Success,
+            Node(GetFailed) => ResponseKind::Failure,
+            _ => ResponseKind::Intermediate,
+        }
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        match &self.return_type {
+            ReturnType::Disk { path } => path
+                .parent()
+                .map(|e| (e, DiskAccess::WRITE))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.priority
+    }
+}
+
+impl FCPRequest for StreamedClientGetMessage {
+    type Success = AllDataStream;
+    type Failure = GetFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.0.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        ClientGetMessage::response_kind(message_type)
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.0.persistence
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.0.priority
+    }
+}
+
+impl FCPRequest for DiskClientGetMessage {
+    type Success = DataFoundMessage;
+    type Failure = GetFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.0.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        match message_type {
+            Node(DataFound) => ResponseKind::Success,
+            Node(GetFailed) => ResponseKind::Failure,
+            _ => ResponseKind::Intermediate,
+        }
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.0.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        self.0.disk_access()
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.0.priority
+    }
+}
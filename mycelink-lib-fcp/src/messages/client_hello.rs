@@ -17,8 +17,8 @@ impl From<ClientHelloMessage> for Message {
         Message::new(
             MESSAGE_TYPE,
             vec![
-                Field::new("Name".into(), value.name),
-                Field::new("ExpectedVersion".into(), value.version.name().into()),
+                Field::unvalidated("Name".into(), value.name),
+                Field::unvalidated("ExpectedVersion".into(), value.version.name().into()),
             ]
             .into(),
             None,
@@ -38,7 +38,7 @@ mod tests {
             name: "Encode-Test".into(),
         };
 
-        let encoded = client_hello.to_message().encode();
+        let encoded = client_hello.to_message().encode().unwrap();
 
         assert_eq!(
             encoded.as_slice(),
@@ -1,15 +1,24 @@
+use crate::fcp_connector::dda::DiskAccess;
+use crate::fcp_connector::request::{FCPRequest, ResponseKind};
+use crate::messages::put_failed::PutFailedMessage;
+use crate::messages::put_successful::PutSuccessfulMessage;
+use crate::model::compatibility_mode::CompatibilityMode;
 use crate::model::content_type::ContentType;
 use crate::model::fields::Field;
 use crate::model::message::{Message, MessagePayload};
 use crate::model::message_type_identifier::ClientMessageType::ClientPut;
-use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::MessageType::{Client, Node};
+use crate::model::message_type_identifier::NodeMessageType::{PutFailed, PutSuccessful};
 use crate::model::persistence::Persistence;
 use crate::model::priority_class::PriorityClass;
 use crate::model::unique_identifier::UniqueIdentifier;
 use crate::model::upload_type::UploadType;
 use crate::model::uri::URI;
 use crate::model::verbosity::Verbosity;
+use std::path::Path;
 
+#[derive(Debug)]
 pub struct ClientPutMessage {
     pub uri: URI,
     pub content_type: Option<ContentType>,
@@ -24,53 +33,235 @@ pub struct ClientPutMessage {
     pub upload_from: UploadType,
     pub is_binary_blob: bool,
     pub real_time: bool,
+    /// Pins how the data is split and encrypted, so node updates do not change its CHK
+    pub compatibility_mode: Option<CompatibilityMode>,
+    /// Key the blocks of a splitfile are encrypted with, chosen by the node if [None]
+    pub override_splitfile_crypto_key: Option<[u8; 32]>,
 }
 
+/// A [ClientPutMessage] the node only computes the URI of, nothing is inserted
+pub struct CHKOnlyClientPutMessage<'a>(pub &'a ClientPutMessage);
+
 impl From<&ClientPutMessage> for Message {
     fn from(value: &ClientPutMessage) -> Self {
-        let mut fields = vec![
-            Field::new("Identifier".into(), (&value.identifier).into()),
-            Field::new("URI".into(), (&value.uri).into()),
-            Field::new("Verbosity".into(), (&value.verbosity).into()),
-            Field::new("MaxRetries".into(), value.max_retries.to_string().into()),
-            Field::new("PriorityClass".into(), (&value.priority).into()),
-            Field::new("GetCHKOnly".into(), value.get_only_chk.to_string().into()),
-            Field::new(
-                "DontCompress".into(),
-                value.dont_compress.to_string().into(),
-            ),
-            Field::new("Persistence".into(), (&value.persistence).into()),
-            Field::new("UploadFrom".into(), (&value.upload_from).into()),
-            Field::new("BinaryBlob".into(), value.is_binary_blob.to_string().into()),
-            Field::new("RealTimeFlag".into(), value.real_time.to_string().into()),
-            #[cfg(feature = "local_only")]
-            Field::new("LocalRequestOnly".into(), true.to_string().into()),
-        ];
-
-        if let Some(content_type) = &value.content_type {
-            fields.push(Field::new(
-                "Metadata.ContentType".into(),
-                content_type.into(),
-            ));
-        }
-        if let Some(filename) = &value.target_filename {
-            fields.push(Field::new("TargetFilename".into(), filename.clone()))
-        }
-        if let UploadType::Disk { path } = &value.upload_from {
-            fields.push(Field::new("Filename".into(), path.to_string_lossy().into()))
+        with_direct_data(encode(value, value.get_only_chk), value)
+    }
+}
+
+impl From<&CHKOnlyClientPutMessage<'_>> for Message {
+    fn from(value: &CHKOnlyClientPutMessage<'_>) -> Self {
+        with_direct_data(encode(value.0, true), value.0)
+    }
+}
+
+/// Copies the data of [UploadType::Direct] into the payload of `message`
+fn with_direct_data(message: Message, value: &ClientPutMessage) -> Message {
+    match &value.upload_from {
+        UploadType::Direct { data } => message.with_payload(MessagePayload {
+            data: data.clone(),
+            data_len_identifier: "DataLength".into(),
+        }),
+        _ => message,
+    }
+}
+
+/// Encodes everything but the data of [UploadType::Direct]
+fn encode(value: &ClientPutMessage, get_only_chk: bool) -> Message {
+    let mut fields = vec![
+        Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+        Field::unvalidated("URI".into(), (&value.uri).into()),
+        Field::unvalidated("Verbosity".into(), (&value.verbosity).into()),
+        Field::unvalidated("MaxRetries".into(), value.max_retries.to_string().into()),
+        Field::unvalidated("PriorityClass".into(), (&value.priority).into()),
+        Field::unvalidated("GetCHKOnly".into(), get_only_chk.to_string().into()),
+        Field::unvalidated(
+            "DontCompress".into(),
+            value.dont_compress.to_string().into(),
+        ),
+        Field::unvalidated("Persistence".into(), (&value.persistence).into()),
+        Field::unvalidated("UploadFrom".into(), (&value.upload_from).into()),
+        Field::unvalidated("BinaryBlob".into(), value.is_binary_blob.to_string().into()),
+        Field::unvalidated("RealTimeFlag".into(), value.real_time.to_string().into()),
+        #[cfg(feature = "local_only")]
+        Field::unvalidated("LocalRequestOnly".into(), true.to_string().into()),
+    ];
+
+    if let Some(content_type) = &value.content_type {
+        fields.push(Field::unvalidated(
+            "Metadata.ContentType".into(),
+            content_type.into(),
+        ));
+    }
+    if let Some(filename) = &value.target_filename {
+        fields.push(Field::unvalidated(
+            "TargetFilename".into(),
+            filename.clone(),
+        ))
+    }
+    if let UploadType::Disk { path } = &value.upload_from {
+        fields.push(Field::unvalidated(
+            "Filename".into(),
+            path.to_string_lossy().into(),
+        ))
+    }
+    if let UploadType::Redirect { target } = &value.upload_from {
+        fields.push(Field::unvalidated("TargetURI".into(), target.into()))
+    }
+    if let Some(compatibility_mode) = &value.compatibility_mode {
+        fields.push(Field::unvalidated(
+            "CompatibilityMode".into(),
+            compatibility_mode.into(),
+        ))
+    }
+    if let Some(crypto_key) = &value.override_splitfile_crypto_key {
+        let crypto_key: String = crypto_key.iter().map(|e| format!("{e:02x}")).collect();
+        fields.push(Field::unvalidated(
+            "OverrideSplitfileCryptoKey".into(),
+            crypto_key.into(),
+        ))
+    }
+
+    Message::new(Client(ClientPut), fields.into(), None)
+}
+
+/// The data of [UploadType::Direct] borrowed from `value`
+fn direct_data(value: &ClientPutMessage) -> Option<&[u8]> {
+    match &value.upload_from {
+        UploadType::Direct { data } => Some(data),
+        _ => None,
+    }
+}
+
+/// Moves the data of [UploadType::Direct] into the payload instead of copying it
+impl From<ClientPutMessage> for Message {
+    fn from(mut value: ClientPutMessage) -> Self {
+        let data = match &mut value.upload_from {
+            UploadType::Direct { data } => std::mem::take(data),
+            _ => return (&value).into(),
+        };
+        let message = encode(&value, value.get_only_chk);
+        message.with_payload(MessagePayload {
+            data,
+            data_len_identifier: "DataLength".into(),
+        })
+    }
+}
+
+impl FCPRequest for ClientPutMessage {
+    type Success = PutSuccessfulMessage;
+    type Failure = PutFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        match message_type {
+            Node(PutSuccessful) => ResponseKind::Success,
+            Node(PutFailed) => ResponseKind::Failure,
+            _ => ResponseKind::Intermediate,
         }
-        if let UploadType::Redirect { target } = &value.upload_from {
-            fields.push(Field::new("TargetURI".into(), target.into()))
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        match &self.upload_from {
+            UploadType::Disk { path } => path
+                .parent()
+                .map(|e| (e, DiskAccess::READ))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
         }
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.priority
+    }
+
+    fn split_payload(&self) -> Option<(Message, &[u8])> {
+        direct_data(self).map(|data| (encode(self, self.get_only_chk), data))
+    }
+}
+
+impl FCPRequest for CHKOnlyClientPutMessage<'_> {
+    type Success = PutSuccessfulMessage;
+    type Failure = PutFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.0.identifier
+    }
 
-        let payload = match &value.upload_from {
-            UploadType::Direct { data } => Some(MessagePayload {
-                data: data.clone(),
-                data_len_identifier: "DataLength".into(),
-            }),
-            _ => None,
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        ClientPutMessage::response_kind(message_type)
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.0.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        self.0.disk_access()
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.0.priority
+    }
+
+    fn split_payload(&self) -> Option<(Message, &[u8])> {
+        direct_data(self.0).map(|data| (encode(self.0, true), data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fcp_connector::request::FCPRequest;
+    use crate::messages::client_put::{CHKOnlyClientPutMessage, ClientPutMessage};
+    use crate::model::message::Message;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::verbosity::Verbosity;
+
+    #[test]
+    fn test_direct_data_is_borrowed() {
+        let put = ClientPutMessage {
+            uri: "CHK@".try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Borrowed put").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: false,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Direct {
+                data: b"Hello World".as_slice().into(),
+            },
+            is_binary_blob: false,
+            real_time: false,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
         };
+        let UploadType::Direct { data } = &put.upload_from else {
+            unreachable!()
+        };
+
+        let (message, payload) = put.split_payload().unwrap();
+        assert!(message.payload().is_none());
+        assert_eq!(payload.as_ptr(), data.as_ptr());
+
+        let chk_only = CHKOnlyClientPutMessage(&put);
+        let (message, payload) = chk_only.split_payload().unwrap();
+        assert_eq!(message.fields().get("GetCHKOnly").unwrap().value(), "true");
+        assert_eq!(payload.as_ptr(), data.as_ptr());
 
-        Message::new(Client(ClientPut), fields.into(), payload)
+        let copied = Message::from(&put);
+        assert_eq!(&*copied.payload().unwrap().data, b"Hello World");
     }
 }
@@ -0,0 +1,227 @@
+use crate::fcp_connector::dda::DiskAccess;
+use crate::fcp_connector::request::{FCPRequest, ResponseKind};
+use crate::messages::put_failed::PutFailedMessage;
+use crate::messages::put_successful::PutSuccessfulMessage;
+use crate::model::content_type::ContentType;
+use crate::model::field_set::FieldSet;
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::ClientMessageType::ClientPutComplexDir;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::MessageType::{Client, Node};
+use crate::model::message_type_identifier::NodeMessageType::{PutFailed, PutSuccessful};
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+use crate::model::verbosity::Verbosity;
+use std::path::Path;
+
+/// Inserts a manifest of files given one by one, each from memory, disk or as redirect
+pub struct ClientPutComplexDirMessage {
+    pub uri: URI,
+    pub identifier: UniqueIdentifier,
+    pub verbosity: Verbosity,
+    pub max_retries: i32,
+    pub priority: PriorityClass,
+    pub get_only_chk: bool,
+    pub dont_compress: bool,
+    pub persistence: Persistence,
+    /// File returned when the manifest itself is fetched
+    pub default_name: Option<Box<str>>,
+    pub files: Vec<DirectoryEntry>,
+    pub real_time: bool,
+}
+
+/// A file of a [ClientPutComplexDirMessage]
+pub struct DirectoryEntry {
+    /// Path of the file in the manifest, e.g. `css/style.css`
+    pub name: Box<str>,
+    pub content_type: Option<ContentType>,
+    pub source: DirectoryEntrySource,
+}
+
+pub enum DirectoryEntrySource {
+    /// Sent as part of the payload of the message
+    Direct {
+        data: Box<[u8]>,
+    },
+    Disk {
+        path: Box<Path>,
+    },
+    Redirect {
+        target: URI,
+    },
+}
+
+impl From<&DirectoryEntrySource> for &str {
+    fn from(value: &DirectoryEntrySource) -> Self {
+        match value {
+            DirectoryEntrySource::Direct { .. } => "direct",
+            DirectoryEntrySource::Disk { .. } => "disk",
+            DirectoryEntrySource::Redirect { .. } => "redirect",
+        }
+    }
+}
+
+impl From<&DirectoryEntry> for FieldSet {
+    fn from(value: &DirectoryEntry) -> Self {
+        let mut fields = FieldSet::new();
+        fields.set("Name", value.name.clone());
+        fields.set("UploadFrom", Into::<&str>::into(&value.source));
+        if let Some(content_type) = &value.content_type {
+            fields.set("Metadata.ContentType", content_type);
+        }
+
+        match &value.source {
+            DirectoryEntrySource::Direct { data } => {
+                fields.set("DataLength", data.len().to_string())
+            }
+            DirectoryEntrySource::Disk { path } => fields.set("Filename", path.to_string_lossy()),
+            DirectoryEntrySource::Redirect { target } => fields.set("TargetURI", target),
+        }
+        fields
+    }
+}
+
+/// The data of the direct files is sent as one payload in the order of the files. Its
+/// `DataLength` is their sum, the node itself only reads the `DataLength` of each file.
+impl From<&ClientPutComplexDirMessage> for Message {
+    fn from(value: &ClientPutComplexDirMessage) -> Self {
+        let mut fields = FieldSet::new();
+        fields.set("Identifier", &value.identifier);
+        fields.set("URI", &value.uri);
+        fields.set("Verbosity", &value.verbosity);
+        fields.set("MaxRetries", value.max_retries.to_string());
+        fields.set("PriorityClass", &value.priority);
+        fields.set("GetCHKOnly", value.get_only_chk.to_string());
+        fields.set("DontCompress", value.dont_compress.to_string());
+        fields.set("Persistence", &value.persistence);
+        fields.set("RealTimeFlag", value.real_time.to_string());
+        #[cfg(feature = "local_only")]
+        fields.set("LocalRequestOnly", true.to_string());
+
+        if let Some(default_name) = &value.default_name {
+            fields.set("DefaultName", default_name.clone());
+        }
+        for file in &value.files {
+            fields.push("Files", file.into());
+        }
+
+        let data: Vec<u8> = value
+            .files
+            .iter()
+            .filter_map(|e| match &e.source {
+                DirectoryEntrySource::Direct { data } => Some(data.iter().copied()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let payload = (!data.is_empty()).then(|| MessagePayload {
+            data: data.into(),
+            data_len_identifier: "DataLength".into(),
+        });
+
+        Message::new(Client(ClientPutComplexDir), (&fields).into(), payload)
+    }
+}
+
+impl FCPRequest for ClientPutComplexDirMessage {
+    type Success = PutSuccessfulMessage;
+    type Failure = PutFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        match message_type {
+            Node(PutSuccessful) => ResponseKind::Success,
+            Node(PutFailed) => ResponseKind::Failure,
+            _ => ResponseKind::Intermediate,
+        }
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        self.files
+            .iter()
+            .filter_map(|e| match &e.source {
+                DirectoryEntrySource::Disk { path } => path.parent(),
+                _ => None,
+            })
+            .map(|e| (e, DiskAccess::READ))
+            .collect()
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.priority
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::client_put_complex_dir::{
+        ClientPutComplexDirMessage, DirectoryEntry, DirectoryEntrySource,
+    };
+    use crate::model::message::Message;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::verbosity::Verbosity;
+    use std::path::Path;
+
+    #[test]
+    fn test_files_are_numbered_with_joined_payload() {
+        let message = ClientPutComplexDirMessage {
+            uri: "CHK@".try_into().unwrap(),
+            identifier: UniqueIdentifier::new("Site").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: false,
+            persistence: Persistence::Connection,
+            default_name: Some("index.html".into()),
+            files: vec![
+                DirectoryEntry {
+                    name: "index.html".into(),
+                    content_type: Some("text/html".parse().unwrap()),
+                    source: DirectoryEntrySource::Direct {
+                        data: b"<html/>".as_slice().into(),
+                    },
+                },
+                DirectoryEntry {
+                    name: "logo.png".into(),
+                    content_type: None,
+                    source: DirectoryEntrySource::Disk {
+                        path: Path::new("/tmp/logo.png").into(),
+                    },
+                },
+                DirectoryEntry {
+                    name: "notes.txt".into(),
+                    content_type: None,
+                    source: DirectoryEntrySource::Direct {
+                        data: b"hi".as_slice().into(),
+                    },
+                },
+            ],
+            real_time: false,
+        };
+
+        let message: Message = (&message).into();
+        let fields = message.fields().field_set();
+        assert_eq!(fields.get("DefaultName"), Some("index.html"));
+        assert_eq!(fields.get("Files.0.DataLength"), Some("7"));
+        assert_eq!(
+            fields.get("Files.0.Metadata.ContentType"),
+            Some("text/html")
+        );
+        assert_eq!(fields.get("Files.1.UploadFrom"), Some("disk"));
+        assert_eq!(fields.get("Files.1.Filename"), Some("/tmp/logo.png"));
+        assert_eq!(fields.get("Files.2.Name"), Some("notes.txt"));
+        assert_eq!(&*message.payload().unwrap().data, b"<html/>hi");
+    }
+}
@@ -0,0 +1,103 @@
+use crate::fcp_connector::dda::DiskAccess;
+use crate::fcp_connector::request::{FCPRequest, ResponseKind};
+use crate::messages::put_failed::PutFailedMessage;
+use crate::messages::put_successful::PutSuccessfulMessage;
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ClientPutDiskDir;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::MessageType::{Client, Node};
+use crate::model::message_type_identifier::NodeMessageType::{PutFailed, PutSuccessful};
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+use crate::model::verbosity::Verbosity;
+use std::path::Path;
+
+/// Inserts a manifest of all files the node finds in a directory, including subdirectories
+pub struct ClientPutDiskDirMessage {
+    pub uri: URI,
+    pub identifier: UniqueIdentifier,
+    pub verbosity: Verbosity,
+    pub max_retries: i32,
+    pub priority: PriorityClass,
+    pub get_only_chk: bool,
+    pub dont_compress: bool,
+    pub persistence: Persistence,
+    /// File returned when the manifest itself is fetched
+    pub default_name: Option<Box<str>>,
+    pub directory: Box<Path>,
+    /// Skips files the node cannot read instead of failing the insert
+    pub allow_unreadable_files: bool,
+    pub include_hidden_files: bool,
+    pub real_time: bool,
+}
+
+impl From<&ClientPutDiskDirMessage> for Message {
+    fn from(value: &ClientPutDiskDirMessage) -> Self {
+        let mut fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("URI".into(), (&value.uri).into()),
+            Field::unvalidated("Verbosity".into(), (&value.verbosity).into()),
+            Field::unvalidated("MaxRetries".into(), value.max_retries.to_string().into()),
+            Field::unvalidated("PriorityClass".into(), (&value.priority).into()),
+            Field::unvalidated("GetCHKOnly".into(), value.get_only_chk.to_string().into()),
+            Field::unvalidated(
+                "DontCompress".into(),
+                value.dont_compress.to_string().into(),
+            ),
+            Field::unvalidated("Persistence".into(), (&value.persistence).into()),
+            Field::unvalidated("Filename".into(), value.directory.to_string_lossy().into()),
+            Field::unvalidated(
+                "AllowUnreadableFiles".into(),
+                value.allow_unreadable_files.to_string().into(),
+            ),
+            Field::unvalidated(
+                "IncludeHiddenFiles".into(),
+                value.include_hidden_files.to_string().into(),
+            ),
+            Field::unvalidated("RealTimeFlag".into(), value.real_time.to_string().into()),
+            #[cfg(feature = "local_only")]
+            Field::unvalidated("LocalRequestOnly".into(), true.to_string().into()),
+        ];
+
+        if let Some(default_name) = &value.default_name {
+            fields.push(Field::unvalidated(
+                "DefaultName".into(),
+                default_name.clone(),
+            ));
+        }
+
+        Message::new(Client(ClientPutDiskDir), fields.into(), None)
+    }
+}
+
+impl FCPRequest for ClientPutDiskDirMessage {
+    type Success = PutSuccessfulMessage;
+    type Failure = PutFailedMessage;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        match message_type {
+            Node(PutSuccessful) => ResponseKind::Success,
+            Node(PutFailed) => ResponseKind::Failure,
+            _ => ResponseKind::Intermediate,
+        }
+    }
+
+    fn persistence(&self) -> Persistence {
+        self.persistence
+    }
+
+    fn disk_access(&self) -> Vec<(&Path, DiskAccess)> {
+        vec![(&self.directory, DiskAccess::READ)]
+    }
+
+    fn priority(&self) -> PriorityClass {
+        self.priority
+    }
+}
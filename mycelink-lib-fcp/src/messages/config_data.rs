@@ -0,0 +1,132 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// The configuration of the node, each option with what `GetConfig` asked for
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigDataMessage {
+    pub identifier: Option<UniqueIdentifier>,
+    /// In the order the node sent them
+    pub options: Vec<ConfigOption>,
+}
+
+/// An option like `node.outputBandwidthLimit`, parts not asked for are [None]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConfigOption {
+    pub name: Box<str>,
+    pub current: Option<Box<str>>,
+    pub default: Option<Box<str>>,
+    /// e.g. `number`, `boolean` or `string`
+    pub data_type: Option<Box<str>>,
+    /// Options only shown to users who asked for advanced settings
+    pub expert: Option<bool>,
+    /// Written to the config file even if they have their default value
+    pub force_write: Option<bool>,
+    pub short_description: Option<Box<str>>,
+    pub long_description: Option<Box<str>>,
+    pub sort_order: Option<i32>,
+}
+
+impl ConfigDataMessage {
+    pub fn option(&self, name: &str) -> Option<&ConfigOption> {
+        self.options.iter().find(|e| &*e.name == name)
+    }
+
+    pub fn current(&self, name: &str) -> Option<&str> {
+        self.option(name)?.current.as_deref()
+    }
+}
+
+impl TryFrom<Message> for ConfigDataMessage {
+    type Error = DecodeError;
+
+    /// The fields are `{part}.{option}`, e.g. `current.node.name`
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ConfigData)?;
+
+        let mut identifier = None;
+        let mut options: Vec<ConfigOption> = Vec::new();
+        for field in value.fields().iter() {
+            if field.key() == "Identifier" {
+                identifier = Some(field.value().try_into()?);
+                continue;
+            }
+            let Some((part, name)) = field.key().split_once('.') else {
+                log::warn!("Skipped config field {}", field.key());
+                continue;
+            };
+
+            let index = match options.iter().position(|e| &*e.name == name) {
+                Some(index) => index,
+                None => {
+                    options.push(ConfigOption {
+                        name: name.into(),
+                        ..ConfigOption::default()
+                    });
+                    options.len() - 1
+                }
+            };
+            let option = &mut options[index];
+            let value = field.value();
+            match part {
+                "current" => option.current = Some(value.into()),
+                "default" => option.default = Some(value.into()),
+                "dataType" => option.data_type = Some(value.into()),
+                "expertFlag" => option.expert = Some(value.parse()?),
+                "forceWriteFlag" => option.force_write = Some(value.parse()?),
+                "shortDescription" => option.short_description = Some(value.into()),
+                "longDescription" => option.long_description = Some(value.into()),
+                "sortOrder" => option.sort_order = Some(value.parse()?),
+                _ => log::debug!("Skipped config field {}", field.key()),
+            }
+        }
+
+        Ok(Self {
+            identifier,
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::config_data::ConfigDataMessage;
+    use crate::model::fields::Field;
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::MessageType::Node;
+    use crate::model::message_type_identifier::NodeMessageType::ConfigData;
+    use crate::model::unique_identifier::UniqueIdentifier;
+
+    #[test]
+    fn test_options_are_grouped_by_name() {
+        let identifier = UniqueIdentifier::new("Config").unwrap().to_string();
+        let fields: Vec<_> = [
+            ("Identifier", identifier.as_str()),
+            ("current.node.name", "Alice"),
+            ("current.fcp.port", "9481"),
+            ("default.node.name", ""),
+            ("expertFlag.fcp.port", "true"),
+            ("sortOrder.fcp.port", "3"),
+        ]
+        .into_iter()
+        .map(|(key, value)| Field::unvalidated(key.into(), value.into()))
+        .collect();
+        let message = Message::new(Node(ConfigData), fields.into(), None);
+
+        let config: ConfigDataMessage = message.try_into().unwrap();
+        assert_eq!(config.options.len(), 2);
+        assert_eq!(config.current("node.name"), Some("Alice"));
+        assert_eq!(
+            config.option("node.name").unwrap().default.as_deref(),
+            Some("")
+        );
+        let port = config.option("fcp.port").unwrap();
+        assert_eq!(port.current.as_deref(), Some("9481"));
+        assert_eq!(port.expert, Some(true));
+        assert_eq!(port.sort_order, Some(3));
+        assert_eq!(port.data_type, None);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndListPeersMessage {
+    pub identifier: Option<UniqueIdentifier>,
+}
+
+impl TryFrom<Message> for EndListPeersMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::EndListPeers)?;
+
+        Ok(Self {
+            identifier: match value.fields().get("Identifier") {
+                None => None,
+                Some(field) => Some(field.value().try_into()?),
+            },
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedDataLengthMessage {
+    pub identifier: UniqueIdentifier,
+    pub data_length: u64,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for ExpectedDataLengthMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ExpectedDataLength)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            data_length: fields.get_or_err("DataLength")?.value().parse()?,
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+const HASHES_PREFIX: &str = "Hashes.";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedHashesMessage {
+    pub identifier: UniqueIdentifier,
+    /// Algorithm (e.g. `SHA256`) and hex encoded hash of the final data
+    pub hashes: Vec<(Box<str>, Box<str>)>,
+    pub global: bool,
+}
+
+impl ExpectedHashesMessage {
+    pub fn hash(&self, algorithm: &str) -> Option<&str> {
+        self.hashes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(algorithm))
+            .map(|(_, hash)| hash.as_ref())
+    }
+}
+
+impl TryFrom<Message> for ExpectedHashesMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ExpectedHashes)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            hashes: fields
+                .iter()
+                .filter_map(|field| {
+                    let algorithm = field.key().strip_prefix(HASHES_PREFIX)?;
+                    Some((algorithm.into(), field.value().into()))
+                })
+                .collect(),
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -0,0 +1,32 @@
+use crate::decode_error::DecodeError;
+use crate::model::content_type::ContentType;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedMIMEMessage {
+    pub identifier: UniqueIdentifier,
+    pub content_type: ContentType,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for ExpectedMIMEMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ExpectedMIME)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            content_type: fields.get_or_err("Metadata.ContentType")?.value().parse()?,
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use crate::model::field_set::FieldSet;
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::ClientMessageType;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// A message to a plugin of the node, answered with `FCPPluginReply` carrying the same
+/// `Identifier`, or a `ProtocolError` if the plugin is not loaded
+pub struct FCPPluginMessage {
+    pub identifier: UniqueIdentifier,
+    /// Main class of the plugin, e.g. `plugins.WebOfTrust.WebOfTrust`
+    pub plugin_name: Box<str>,
+    /// Sent as `Param.*`, what the plugin expects is up to it
+    pub params: FieldSet,
+    pub data: Option<Box<[u8]>>,
+}
+
+impl From<&FCPPluginMessage> for Message {
+    fn from(value: &FCPPluginMessage) -> Self {
+        let mut fields = FieldSet::new();
+        fields.set("Identifier", &value.identifier);
+        fields.set("PluginName", value.plugin_name.clone());
+        if !value.params.is_empty() {
+            *fields.subset_mut("Param") = value.params.clone();
+        }
+
+        let payload = value.data.as_ref().map(|data| MessagePayload {
+            data: data.clone(),
+            data_len_identifier: "DataLength".into(),
+        });
+
+        Message::new(
+            Client(ClientMessageType::FCPPluginMessage),
+            (&fields).into(),
+            payload,
+        )
+    }
+}
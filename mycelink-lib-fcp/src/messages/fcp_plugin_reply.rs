@@ -0,0 +1,40 @@
+use crate::decode_error::DecodeError;
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// The answer of a plugin to an `FCPPluginMessage`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FCPPluginReplyMessage {
+    pub identifier: UniqueIdentifier,
+    pub plugin_name: Box<str>,
+    /// Sent as `Replies.*`
+    pub replies: FieldSet,
+    pub data: Option<Box<[u8]>>,
+    /// Only plugins using the newer plugin API report failures, older ones always succeed
+    pub success: bool,
+    pub error_code: Option<Box<str>>,
+    pub error_message: Option<Box<str>>,
+}
+
+impl TryFrom<Message> for FCPPluginReplyMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::FCPPluginReply)?;
+
+        let fields = value.fields().field_set();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.try_into()?,
+            plugin_name: fields.get_or_err("PluginName")?.into(),
+            replies: fields.subset("Replies").cloned().unwrap_or_default(),
+            data: value.payload().map(|e| e.data),
+            success: fields.get_bool("Success")?.unwrap_or(true),
+            error_code: fields.get("ErrorCode").map(Into::into),
+            error_message: fields.get("ErrorMessage").map(Into::into),
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinishedCompressionMessage {
+    pub identifier: UniqueIdentifier,
+    /// Compressor that was picked, the node sends its number (`-1` for none)
+    pub codec: Box<str>,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for FinishedCompressionMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::FinishedCompression)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            codec: fields.get_or_err("Codec")?.value().into(),
+            original_size: fields.get_or_err("OriginalSize")?.value().parse()?,
+            compressed_size: fields.get_or_err("CompressedSize")?.value().parse()?,
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -1,7 +1,10 @@
+use crate::fcp_connector::request::{FCPRequest, NoFailure, ResponseKind};
+use crate::messages::ssk_keypair::SSKKeypairMessage;
 use crate::model::fields::Field;
 use crate::model::message::Message;
 use crate::model::message_type_identifier::ClientMessageType::GenerateSSK;
 use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::SSKKeypair;
 use crate::model::unique_identifier::UniqueIdentifier;
 
 const MESSAGE_TYPE: MessageType = MessageType::Client(GenerateSSK);
@@ -12,10 +15,37 @@ pub struct GenerateSSKMessage {
 
 impl From<GenerateSSKMessage> for Message {
     fn from(value: GenerateSSKMessage) -> Self {
+        (&value).into()
+    }
+}
+
+impl From<&GenerateSSKMessage> for Message {
+    fn from(value: &GenerateSSKMessage) -> Self {
         Message::new(
             MESSAGE_TYPE,
-            vec![Field::new("Identifier".into(), (&value.identifier).into())].into(),
+            vec![Field::unvalidated(
+                "Identifier".into(),
+                (&value.identifier).into(),
+            )]
+            .into(),
             None,
         )
     }
 }
+
+impl FCPRequest for GenerateSSKMessage {
+    type Success = SSKKeypairMessage;
+    type Failure = NoFailure;
+
+    fn identifier(&self) -> &UniqueIdentifier {
+        &self.identifier
+    }
+
+    fn response_kind(message_type: &MessageType) -> ResponseKind {
+        if message_type.is_specific_node_message(SSKKeypair) {
+            ResponseKind::Success
+        } else {
+            ResponseKind::Intermediate
+        }
+    }
+}
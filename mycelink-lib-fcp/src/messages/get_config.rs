@@ -0,0 +1,61 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::GetConfig;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Describes the configuration of the node, answered with `ConfigData` containing what was asked
+/// for
+pub struct GetConfigMessage {
+    pub identifier: UniqueIdentifier,
+    pub with_current: bool,
+    pub with_defaults: bool,
+    pub with_sort_order: bool,
+    pub with_expert_flag: bool,
+    pub with_force_write_flag: bool,
+    pub with_short_description: bool,
+    pub with_long_description: bool,
+    pub with_data_types: bool,
+}
+
+impl GetConfigMessage {
+    /// Asks for everything the node knows about each option
+    pub fn everything(identifier: UniqueIdentifier) -> Self {
+        Self {
+            identifier,
+            with_current: true,
+            with_defaults: true,
+            with_sort_order: true,
+            with_expert_flag: true,
+            with_force_write_flag: true,
+            with_short_description: true,
+            with_long_description: true,
+            with_data_types: true,
+        }
+    }
+}
+
+impl From<&GetConfigMessage> for Message {
+    fn from(value: &GetConfigMessage) -> Self {
+        let mut fields = vec![Field::unvalidated(
+            "Identifier".into(),
+            (&value.identifier).into(),
+        )];
+
+        let flags = [
+            ("WithCurrent", value.with_current),
+            ("WithDefaults", value.with_defaults),
+            ("WithSortOrder", value.with_sort_order),
+            ("WithExpertFlag", value.with_expert_flag),
+            ("WithForceWriteFlag", value.with_force_write_flag),
+            ("WithShortDescription", value.with_short_description),
+            ("WithLongDescription", value.with_long_description),
+            ("WithDataTypes", value.with_data_types),
+        ];
+        for (key, flag) in flags {
+            fields.push(Field::unvalidated(key.into(), flag.to_string().into()));
+        }
+
+        Self::new(Client(GetConfig), fields.into(), None)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::GetNode;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Describes the node itself, answered with `NodeData`
+pub struct GetNodeMessage {
+    pub identifier: UniqueIdentifier,
+    /// Includes the private keys of the node, which must never leave the machine
+    pub with_private: bool,
+    /// Includes statistics like bandwidth use and datastore size
+    pub with_volatile: bool,
+    /// Describes the opennet identity of the node instead of the darknet one
+    pub give_opennet_ref: bool,
+}
+
+impl From<&GetNodeMessage> for Message {
+    fn from(value: &GetNodeMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("WithPrivate".into(), value.with_private.to_string().into()),
+            Field::unvalidated(
+                "WithVolatile".into(),
+                value.with_volatile.to_string().into(),
+            ),
+            Field::unvalidated(
+                "GiveOpennetRef".into(),
+                value.give_opennet_ref.to_string().into(),
+            ),
+        ];
+
+        Self::new(Client(GetNode), fields.into(), None)
+    }
+}
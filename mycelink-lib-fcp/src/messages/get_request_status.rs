@@ -0,0 +1,26 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::GetRequestStatus;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Asks the node to send the `PersistentGet` or `PersistentPut` of a persistent request again,
+/// followed by the messages describing its current state
+pub struct GetRequestStatusMessage {
+    pub identifier: UniqueIdentifier,
+    pub global: bool,
+    /// Only resend the `AllData` of a finished fetch
+    pub only_data: bool,
+}
+
+impl From<GetRequestStatusMessage> for Message {
+    fn from(value: GetRequestStatusMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("Global".into(), value.global.to_string().into()),
+            Field::unvalidated("OnlyData".into(), value.only_data.to_string().into()),
+        ];
+
+        Self::new(Client(GetRequestStatus), fields.into(), None)
+    }
+}
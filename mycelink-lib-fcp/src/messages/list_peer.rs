@@ -6,8 +6,11 @@ use crate::model::unique_identifier::UniqueIdentifier;
 
 const MESSAGE_TYPE: MessageType = MessageType::Client(ListPeer);
 
+/// Describes a single peer, answered with `Peer` or `UnknownNodeIdentifier`
 pub struct ListPeerMessage {
-    pub node_identifier: UniqueIdentifier,
+    pub identifier: UniqueIdentifier,
+    /// Name, identity or `host:port` of the peer
+    pub node_identifier: Box<str>,
     pub with_metadata: bool,
     pub with_volatile: bool,
 }
@@ -17,12 +20,13 @@ impl From<ListPeerMessage> for Message {
         Message::new(
             MESSAGE_TYPE,
             vec![
-                Field::new("NodeIdentifier".into(), (&value.node_identifier).into()),
-                Field::new(
+                Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+                Field::unvalidated("NodeIdentifier".into(), value.node_identifier),
+                Field::unvalidated(
                     "WithMetadata".into(),
                     value.with_metadata.to_string().into(),
                 ),
-                Field::new(
+                Field::unvalidated(
                     "WithVolatile".into(),
                     value.with_volatile.to_string().into(),
                 ),
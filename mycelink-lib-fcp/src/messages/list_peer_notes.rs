@@ -0,0 +1,23 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ListPeerNotes;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Lists the notes about a peer with a `PeerNote` each, followed by `EndListPeerNotes`
+pub struct ListPeerNotesMessage {
+    pub identifier: UniqueIdentifier,
+    /// Name, identity or `host:port` of the peer
+    pub node_identifier: Box<str>,
+}
+
+impl From<ListPeerNotesMessage> for Message {
+    fn from(value: ListPeerNotesMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("NodeIdentifier".into(), value.node_identifier),
+        ];
+
+        Self::new(Client(ListPeerNotes), fields.into(), None)
+    }
+}
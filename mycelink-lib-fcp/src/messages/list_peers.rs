@@ -0,0 +1,30 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ListPeers;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Describes all peers with a `Peer` each, followed by `EndListPeers`
+pub struct ListPeersMessage {
+    pub identifier: UniqueIdentifier,
+    pub with_metadata: bool,
+    pub with_volatile: bool,
+}
+
+impl From<ListPeersMessage> for Message {
+    fn from(value: ListPeersMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated(
+                "WithMetadata".into(),
+                value.with_metadata.to_string().into(),
+            ),
+            Field::unvalidated(
+                "WithVolatile".into(),
+                value.with_volatile.to_string().into(),
+            ),
+        ];
+
+        Self::new(Client(ListPeers), fields.into(), None)
+    }
+}
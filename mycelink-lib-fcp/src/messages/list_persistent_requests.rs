@@ -0,0 +1,13 @@
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ListPersistentRequests;
+use crate::model::message_type_identifier::MessageType::Client;
+
+/// Asks the node for a `PersistentGet` or `PersistentPut` for each persistent request of this
+/// client (and of the global queue if watched), followed by `EndListPersistentRequests`
+pub struct ListPersistentRequestsMessage;
+
+impl From<ListPersistentRequestsMessage> for Message {
+    fn from(_: ListPersistentRequestsMessage) -> Self {
+        Self::new(Client(ListPersistentRequests), Vec::new().into(), None)
+    }
+}
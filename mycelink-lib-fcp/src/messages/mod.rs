@@ -1,18 +1,64 @@
+pub mod add_peer;
 pub mod all_data;
 pub mod client_get;
 pub mod client_hello;
 pub mod client_put;
+pub mod client_put_complex_dir;
+pub mod client_put_disk_dir;
+pub mod config_data;
 pub mod data_found;
+pub mod end_list_peer_notes;
+pub mod end_list_peers;
+pub mod expected_data_length;
+pub mod expected_hashes;
+pub mod expected_mime;
+pub mod fcp_plugin_message;
+pub mod fcp_plugin_reply;
+pub mod finished_compression;
 pub mod generate_ssk;
+pub mod get_config;
 pub mod get_failed;
+pub mod get_node;
+pub mod get_request_status;
 pub mod list_peer;
+pub mod list_peer_notes;
+pub mod list_peers;
+pub mod list_persistent_requests;
+pub mod modify_config;
+pub mod modify_peer;
+pub mod modify_peer_note;
+pub mod modify_persistent_request;
+pub mod node_data;
 pub mod node_hello;
+pub mod peer;
+pub mod peer_note;
+pub mod peer_removed;
+pub mod persistent_get;
+pub mod persistent_put;
+pub mod persistent_request_modified;
+pub mod persistent_request_removed;
+pub mod probe_error;
+pub mod probe_request;
+pub mod probe_result;
+pub mod protocol_error;
 pub mod put_failed;
 pub mod put_successful;
+pub mod remove_peer;
+pub mod remove_request;
+pub mod sending_to_network;
+pub mod simple_progress;
 pub mod ssk_keypair;
+pub mod started_compression;
 pub mod subscribe_usk;
+pub mod subscribed_usk;
+pub mod subscribed_usk_round_finished;
+pub mod subscribed_usk_sending_to_network;
+pub mod subscribed_usk_update;
 pub mod test_dda_complete;
 pub mod test_dda_reply;
 pub mod test_dda_request;
 pub mod test_dda_response;
+pub mod unknown_node_identifier;
+pub mod unsubscribe_usk;
 pub mod uri_generated;
+pub mod watch_global;
@@ -0,0 +1,23 @@
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ModifyConfig;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Changes options of the node, answered with `ConfigData` listing the current values afterwards
+///
+/// Options are set by their full name, e.g. `node.outputBandwidthLimit`. The node ignores
+/// unknown options and keeps the old value of options it refuses.
+pub struct ModifyConfigMessage {
+    pub identifier: UniqueIdentifier,
+    pub values: FieldSet,
+}
+
+impl From<&ModifyConfigMessage> for Message {
+    fn from(value: &ModifyConfigMessage) -> Self {
+        let mut fields = value.values.clone();
+        fields.set("Identifier", &value.identifier);
+
+        Self::new(Client(ModifyConfig), (&fields).into(), None)
+    }
+}
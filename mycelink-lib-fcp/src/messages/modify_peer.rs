@@ -0,0 +1,52 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ModifyPeer;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::peer_trust::PeerTrust;
+use crate::model::peer_visibility::PeerVisibility;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Changes the settings of a peer, answered with its `Peer`. Settings left at [None] keep their
+/// value.
+pub struct ModifyPeerMessage {
+    pub identifier: UniqueIdentifier,
+    /// Name, identity or `host:port` of the peer
+    pub node_identifier: Box<str>,
+    pub allow_local_addresses: Option<bool>,
+    pub is_disabled: Option<bool>,
+    pub is_listen_only: Option<bool>,
+    pub is_burst_only: Option<bool>,
+    pub ignore_source_port: Option<bool>,
+    pub trust: Option<PeerTrust>,
+    pub visibility: Option<PeerVisibility>,
+}
+
+impl From<&ModifyPeerMessage> for Message {
+    fn from(value: &ModifyPeerMessage) -> Self {
+        let mut fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("NodeIdentifier".into(), value.node_identifier.clone()),
+        ];
+
+        let flags = [
+            ("AllowLocalAddresses", value.allow_local_addresses),
+            ("IsDisabled", value.is_disabled),
+            ("IsListenOnly", value.is_listen_only),
+            ("IsBurstOnly", value.is_burst_only),
+            ("IgnoreSourcePort", value.ignore_source_port),
+        ];
+        for (key, flag) in flags {
+            if let Some(flag) = flag {
+                fields.push(Field::unvalidated(key.into(), flag.to_string().into()));
+            }
+        }
+        if let Some(trust) = &value.trust {
+            fields.push(Field::unvalidated("Trust".into(), trust.into()));
+        }
+        if let Some(visibility) = &value.visibility {
+            fields.push(Field::unvalidated("Visibility".into(), visibility.into()));
+        }
+
+        Self::new(Client(ModifyPeer), fields.into(), None)
+    }
+}
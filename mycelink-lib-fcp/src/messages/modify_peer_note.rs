@@ -0,0 +1,36 @@
+use crate::messages::peer_note::PRIVATE_DARKNET_COMMENT;
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ModifyPeerNote;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::FREENET_BASE64;
+use base64::Engine;
+
+/// Replaces the private comment about a darknet peer, answered with the new `PeerNote`
+pub struct ModifyPeerNoteMessage {
+    pub identifier: UniqueIdentifier,
+    /// Name, identity or `host:port` of the peer
+    pub node_identifier: Box<str>,
+    pub note_text: Box<str>,
+}
+
+impl From<&ModifyPeerNoteMessage> for Message {
+    fn from(value: &ModifyPeerNoteMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("NodeIdentifier".into(), value.node_identifier.clone()),
+            Field::unvalidated(
+                "PeerNoteType".into(),
+                PRIVATE_DARKNET_COMMENT.to_string().into(),
+            ),
+            // Encoded, as the note may span multiple lines
+            Field::unvalidated(
+                "NoteText".into(),
+                FREENET_BASE64.encode(value.note_text.as_bytes()).into(),
+            ),
+        ];
+
+        Self::new(Client(ModifyPeerNote), fields.into(), None)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ModifyPersistentRequest;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Changes a persistent request, answered with `PersistentRequestModified`
+pub struct ModifyPersistentRequestMessage {
+    pub identifier: UniqueIdentifier,
+    pub global: bool,
+    /// Opaque value stored with the request, e.g. to recognise it after a restart
+    pub client_token: Option<Box<str>>,
+    pub priority: Option<PriorityClass>,
+}
+
+impl From<&ModifyPersistentRequestMessage> for Message {
+    fn from(value: &ModifyPersistentRequestMessage) -> Self {
+        let mut fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("Global".into(), value.global.to_string().into()),
+        ];
+
+        if let Some(client_token) = &value.client_token {
+            fields.push(Field::unvalidated(
+                "ClientToken".into(),
+                client_token.clone(),
+            ));
+        }
+        if let Some(priority) = &value.priority {
+            fields.push(Field::unvalidated("PriorityClass".into(), priority.into()));
+        }
+
+        Self::new(Client(ModifyPersistentRequest), fields.into(), None)
+    }
+}
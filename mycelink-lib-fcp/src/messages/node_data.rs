@@ -0,0 +1,129 @@
+use crate::decode_error::DecodeError;
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::node_ref::NodeRef;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Values of `NodeData` that are only sent if asked for and must never be shared
+const PRIVATE_VALUES: &[&str] = &["ark.privURI", "ecdsa.P256.pri", "clientNonce"];
+const PRIVATE_SUBSETS: &[&str] = &["dsaPrivKey"];
+
+/// The node itself, with its private keys and statistics if they were requested
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeDataMessage {
+    pub identifier: Option<UniqueIdentifier>,
+    /// The reference others need to peer with the node
+    pub node_ref: NodeRef,
+    pub private: Option<FieldSet>,
+    pub volatile: Option<FieldSet>,
+}
+
+/// Traffic of the node since it started, in bytes and bytes per second
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BandwidthStats {
+    pub total_input_bytes: u64,
+    pub total_output_bytes: u64,
+    pub total_input_rate: u64,
+    pub total_output_rate: u64,
+}
+
+/// Usage of the datastore, sizes in bytes
+///
+/// The store keeps what the node is responsible for, the cache what passed through it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DatastoreStats {
+    pub store_keys: u64,
+    pub store_size: u64,
+    pub store_hits: u64,
+    pub store_misses: u64,
+    pub cached_keys: u64,
+    pub cached_size: u64,
+    pub cached_store_hits: u64,
+    pub cached_store_misses: u64,
+}
+
+impl NodeDataMessage {
+    /// Only known if the volatile fields were requested
+    pub fn bandwidth(&self) -> Result<Option<BandwidthStats>, DecodeError> {
+        let Some(volatile) = &self.volatile else {
+            return Ok(None);
+        };
+
+        Ok(Some(BandwidthStats {
+            total_input_bytes: volatile.parse_or_err("totalInputBytes")?,
+            total_output_bytes: volatile.parse_or_err("totalOutputBytes")?,
+            total_input_rate: volatile.parse_or_err("totalInputRate")?,
+            total_output_rate: volatile.parse_or_err("totalOutputRate")?,
+        }))
+    }
+
+    /// Only known if the volatile fields were requested
+    pub fn datastore(&self) -> Result<Option<DatastoreStats>, DecodeError> {
+        let Some(volatile) = &self.volatile else {
+            return Ok(None);
+        };
+
+        Ok(Some(DatastoreStats {
+            store_keys: volatile.parse_or_err("storeKeys")?,
+            store_size: volatile.parse_or_err("storeSize")?,
+            store_hits: volatile.parse_or_err("storeHits")?,
+            store_misses: volatile.parse_or_err("storeMisses")?,
+            cached_keys: volatile.parse_or_err("cachedKeys")?,
+            cached_size: volatile.parse_or_err("cachedSize")?,
+            cached_store_hits: volatile.parse_or_err("cachedStoreHits")?,
+            cached_store_misses: volatile.parse_or_err("cachedStoreMisses")?,
+        }))
+    }
+
+    pub fn uptime_seconds(&self) -> Result<Option<u64>, DecodeError> {
+        match &self.volatile {
+            None => Ok(None),
+            Some(volatile) => volatile.parse("uptimeSeconds"),
+        }
+    }
+
+    /// Estimate of the node from the peers it has seen since it started
+    pub fn network_size_estimate(&self) -> Result<Option<u64>, DecodeError> {
+        match &self.volatile {
+            None => Ok(None),
+            Some(volatile) => volatile.parse("networkSizeEstimateSession"),
+        }
+    }
+}
+
+impl TryFrom<Message> for NodeDataMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::NodeData)?;
+
+        let mut fields = value.fields().field_set();
+        let identifier = match fields.remove("Identifier") {
+            None => None,
+            Some(identifier) => Some(identifier.as_ref().try_into()?),
+        };
+        let volatile = fields.remove_subset("volatile");
+
+        let mut private = FieldSet::new();
+        for path in PRIVATE_VALUES {
+            if let Some(value) = fields.remove(path) {
+                private.set(path, value);
+            }
+        }
+        for path in PRIVATE_SUBSETS {
+            if let Some(subset) = fields.remove_subset(path) {
+                *private.subset_mut(path) = subset;
+            }
+        }
+
+        Ok(Self {
+            identifier,
+            node_ref: NodeRef::new(fields)?,
+            private: (!private.is_empty()).then_some(private),
+            volatile,
+        })
+    }
+}
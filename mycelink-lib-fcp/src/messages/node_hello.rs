@@ -4,11 +4,57 @@ use crate::model::fcp_version::FCPVersion;
 use crate::model::message::Message;
 use crate::model::message_type_identifier::NodeMessageType;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NodeHelloMessage {
     pub fcp_version: FCPVersion,
     pub node: Box<str>,
     pub connection_identifier: ConnectionIdentifier,
+    /// e.g. `Fred,0.7,1.0,1497`
+    pub version: Option<Box<str>>,
+    pub build: Option<u32>,
+    /// Git revision the node was built from
+    pub revision: Option<Box<str>>,
+    /// Build of the freenet-ext library, only sent by old nodes
+    pub ext_build: Option<u32>,
+    pub ext_revision: Option<Box<str>>,
+    /// Testnet nodes are not anonymous, everything they do is logged
+    pub testnet: bool,
+    pub compression_codecs: Vec<CompressionCodec>,
+    pub node_language: Option<Box<str>>,
+}
+
+/// A compressor the node can insert with, in the order the node tries them
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompressionCodec {
+    pub name: Box<str>,
+    /// Used in the `Codecs` field of inserts
+    pub id: u8,
+}
+
+/// Reads e.g. `3 - GZIP(0), BZIP2(1), LZMA_NEW(2)`, the leading count is not needed
+fn parse_compression_codecs(value: &str) -> Result<Vec<CompressionCodec>, DecodeError> {
+    let codecs = match value.split_once(" - ") {
+        Some((_, codecs)) => codecs,
+        None => value,
+    };
+
+    codecs
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|codec| {
+            let (name, id) = codec
+                .strip_suffix(')')
+                .and_then(|e| e.split_once('('))
+                .ok_or_else(|| {
+                    DecodeError::ParseError(format!("Compression codec '{codec}'").into())
+                })?;
+            Ok(CompressionCodec {
+                name: name.into(),
+                id: id.parse()?,
+            })
+        })
+        .collect()
 }
 
 impl TryFrom<Message> for NodeHelloMessage {
@@ -19,6 +65,7 @@ impl TryFrom<Message> for NodeHelloMessage {
             .message_type()
             .expect_specific_node_message(NodeMessageType::NodeHello)?;
 
+        let fields = value.fields().field_set();
         Ok(Self {
             fcp_version: value.fields().get_or_err("FCPVersion")?.try_into()?,
             node: value.fields().get_or_err("Node")?.value().into(),
@@ -27,6 +74,58 @@ impl TryFrom<Message> for NodeHelloMessage {
                 .get_or_err("ConnectionIdentifier")?
                 .value()
                 .into(),
+            version: fields.get("Version").map(Into::into),
+            build: fields.parse("Build")?,
+            revision: fields.get("Revision").map(Into::into),
+            ext_build: fields.parse("ExtBuild")?,
+            ext_revision: fields.get("ExtRevision").map(Into::into),
+            testnet: fields.get_bool("Testnet")?.unwrap_or(false),
+            compression_codecs: match fields.get("CompressionCodecs") {
+                None => Vec::new(),
+                Some(codecs) => parse_compression_codecs(codecs)?,
+            },
+            node_language: fields.get("NodeLanguage").map(Into::into),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::node_hello::{CompressionCodec, NodeHelloMessage};
+    use crate::model::fields::Field;
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::MessageType::Node;
+    use crate::model::message_type_identifier::NodeMessageType::NodeHello;
+
+    #[test]
+    fn test_decode_build_and_codecs() {
+        let fields: Vec<_> = [
+            ("FCPVersion", "2.0"),
+            ("Node", "Fred"),
+            ("Version", "Fred,0.7,1.0,1497"),
+            ("Build", "1497"),
+            ("Revision", "build01497"),
+            ("Testnet", "false"),
+            ("CompressionCodecs", "3 - GZIP(0), BZIP2(1), LZMA_NEW(2)"),
+            ("ConnectionIdentifier", "abc"),
+            ("NodeLanguage", "ENGLISH"),
+        ]
+        .into_iter()
+        .map(|(key, value)| Field::unvalidated(key.into(), value.into()))
+        .collect();
+        let message = Message::new(Node(NodeHello), fields.into(), None);
+        let node_hello: NodeHelloMessage = message.try_into().unwrap();
+
+        assert_eq!(node_hello.build, Some(1497));
+        assert_eq!(node_hello.revision.as_deref(), Some("build01497"));
+        assert!(!node_hello.testnet);
+        assert_eq!(
+            node_hello.compression_codecs[2],
+            CompressionCodec {
+                name: "LZMA_NEW".into(),
+                id: 2
+            }
+        );
+        assert_eq!(node_hello.compression_codecs.len(), 3);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::decode_error::DecodeError;
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::node_ref::NodeRef;
+use crate::model::peer_status::PeerStatus;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// A peer of the node, with the `metadata.*` and `volatile.*` fields if they were requested
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeerMessage {
+    pub identifier: Option<UniqueIdentifier>,
+    pub node_ref: NodeRef,
+    pub metadata: Option<FieldSet>,
+    pub volatile: Option<FieldSet>,
+}
+
+impl PeerMessage {
+    /// Only known if the volatile fields were requested
+    pub fn status(&self) -> Option<PeerStatus> {
+        self.volatile.as_ref()?.get("status").map(PeerStatus::from)
+    }
+
+    pub fn is_darknet(&self) -> bool {
+        !self.node_ref.opennet()
+    }
+}
+
+impl TryFrom<Message> for PeerMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::Peer)?;
+
+        let mut fields = value.fields().field_set();
+        let identifier = match fields.remove("Identifier") {
+            None => None,
+            Some(identifier) => Some(identifier.as_ref().try_into()?),
+        };
+        let metadata = fields.remove_subset("metadata");
+        let volatile = fields.remove_subset("volatile");
+
+        Ok(Self {
+            identifier,
+            node_ref: NodeRef::new(fields)?,
+            metadata,
+            volatile,
+        })
+    }
+}
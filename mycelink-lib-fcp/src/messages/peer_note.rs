@@ -0,0 +1,48 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::FREENET_BASE64;
+use base64::Engine;
+
+/// The only type of peer notes, a comment about a darknet peer only shown to the node operator
+pub const PRIVATE_DARKNET_COMMENT: u32 = 1;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeerNoteMessage {
+    pub identifier: Option<UniqueIdentifier>,
+    pub node_identifier: Box<str>,
+    pub peer_note_type: u32,
+    /// Decoded from the base64 sent by the node
+    pub note_text: Box<str>,
+}
+
+impl TryFrom<Message> for PeerNoteMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::PeerNote)?;
+
+        let fields = value.fields();
+        let note_text = FREENET_BASE64
+            .decode(fields.get_or_err("NoteText")?.value())
+            .map_err(|err| {
+                DecodeError::ParseError(format!("NoteText is no base64: {err}").into())
+            })?;
+        let note_text = String::from_utf8(note_text).map_err(|err| {
+            DecodeError::ParseError(format!("NoteText is no UTF-8: {err}").into())
+        })?;
+
+        Ok(Self {
+            identifier: match fields.get("Identifier") {
+                None => None,
+                Some(field) => Some(field.value().try_into()?),
+            },
+            node_identifier: fields.get_or_err("NodeIdentifier")?.value().into(),
+            peer_note_type: fields.get_or_err("PeerNoteType")?.value().parse()?,
+            note_text: note_text.into(),
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeerRemovedMessage {
+    pub identifier: Option<UniqueIdentifier>,
+    pub node_identifier: Box<str>,
+}
+
+impl TryFrom<Message> for PeerRemovedMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::PeerRemoved)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: match fields.get("Identifier") {
+                None => None,
+                Some(field) => Some(field.value().try_into()?),
+            },
+            node_identifier: fields.get_or_err("NodeIdentifier")?.value().into(),
+        })
+    }
+}
@@ -0,0 +1,64 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::return_type::ReturnType;
+use crate::model::uri::URI;
+use crate::model::verbosity::Verbosity;
+use std::path::Path;
+
+/// Describes a persistent fetch, sent when listing persistent requests or asking for its status
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PersistentGetMessage {
+    /// Kept as sent, requests of other clients on the global queue don't use the format of a
+    /// [crate::model::unique_identifier::UniqueIdentifier]
+    pub identifier: Box<str>,
+    pub uri: URI,
+    pub verbosity: Verbosity,
+    pub return_type: ReturnType,
+    pub priority: PriorityClass,
+    pub persistence: Persistence,
+    pub global: bool,
+    pub max_retries: i32,
+    pub client_token: Option<Box<str>>,
+    pub real_time: bool,
+}
+
+impl TryFrom<Message> for PersistentGetMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::PersistentGet)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().into(),
+            uri: fields.get_or_err("URI")?.value().parse()?,
+            verbosity: Verbosity::parse_lenient(fields.get_or_err("Verbosity")?.value()),
+            return_type: match fields.get_or_err("ReturnType")?.value() {
+                "direct" => ReturnType::Direct,
+                "none" => ReturnType::None,
+                "disk" => ReturnType::Disk {
+                    path: Path::new(fields.get_or_err("Filename")?.value()).into(),
+                },
+                other => {
+                    return Err(DecodeError::ParseError(
+                        format!("Unknown return type {other}").into(),
+                    ))
+                }
+            },
+            priority: fields.get_or_err("PriorityClass")?.value().parse()?,
+            persistence: fields.get_or_err("PersistenceType")?.value().parse()?,
+            global: fields.get_or_err("Global")?.value().parse()?,
+            max_retries: fields.get_or_err("MaxRetries")?.value().parse()?,
+            client_token: fields.get("ClientToken").map(|e| e.value().into()),
+            real_time: match fields.get("RealTime") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use crate::decode_error::DecodeError;
+use crate::model::content_type::ContentType;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::persistence::Persistence;
+use crate::model::priority_class::PriorityClass;
+use crate::model::uri::URI;
+use crate::model::verbosity::Verbosity;
+use std::path::Path;
+
+/// Describes a persistent insert, sent when listing persistent requests or asking for its status
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PersistentPutMessage {
+    /// Kept as sent, requests of other clients on the global queue don't use the format of a
+    /// [crate::model::unique_identifier::UniqueIdentifier]
+    pub identifier: Box<str>,
+    pub uri: URI,
+    pub verbosity: Verbosity,
+    pub priority: PriorityClass,
+    pub persistence: Persistence,
+    pub global: bool,
+    pub max_retries: i32,
+    /// `direct`, `disk` or `redirect` like in `ClientPut`
+    pub upload_from: Box<str>,
+    /// Set if uploaded from disk
+    pub filename: Option<Box<Path>>,
+    /// Set if uploaded as redirect
+    pub target_uri: Option<URI>,
+    /// Set if uploaded directly
+    pub data_length: Option<u64>,
+    pub content_type: Option<ContentType>,
+    pub dont_compress: bool,
+    pub client_token: Option<Box<str>>,
+    pub real_time: bool,
+}
+
+impl TryFrom<Message> for PersistentPutMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::PersistentPut)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().into(),
+            uri: fields.get_or_err("URI")?.value().parse()?,
+            verbosity: Verbosity::parse_lenient(fields.get_or_err("Verbosity")?.value()),
+            priority: fields.get_or_err("PriorityClass")?.value().parse()?,
+            persistence: fields.get_or_err("PersistenceType")?.value().parse()?,
+            global: fields.get_or_err("Global")?.value().parse()?,
+            max_retries: fields.get_or_err("MaxRetries")?.value().parse()?,
+            upload_from: fields.get_or_err("UploadFrom")?.value().into(),
+            filename: fields.get("Filename").map(|e| Path::new(e.value()).into()),
+            target_uri: match fields.get("TargetURI") {
+                None => None,
+                Some(field) => Some(field.value().parse()?),
+            },
+            data_length: match fields.get("DataLength") {
+                None => None,
+                Some(field) => Some(field.value().parse()?),
+            },
+            content_type: match fields.get("Metadata.ContentType") {
+                None => None,
+                Some(field) => Some(field.value().parse()?),
+            },
+            dont_compress: match fields.get("DontCompress") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+            client_token: fields.get("ClientToken").map(|e| e.value().into()),
+            real_time: match fields.get("RealTime") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
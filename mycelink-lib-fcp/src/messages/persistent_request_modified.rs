@@ -0,0 +1,38 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::priority_class::PriorityClass;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Only contains the values that were changed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PersistentRequestModifiedMessage {
+    pub identifier: UniqueIdentifier,
+    pub global: bool,
+    pub client_token: Option<Box<str>>,
+    pub priority: Option<PriorityClass>,
+}
+
+impl TryFrom<Message> for PersistentRequestModifiedMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::PersistentRequestModified)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+            client_token: fields.get("ClientToken").map(|e| e.value().into()),
+            priority: match fields.get("PriorityClass") {
+                None => None,
+                Some(field) => Some(field.value().parse()?),
+            },
+        })
+    }
+}
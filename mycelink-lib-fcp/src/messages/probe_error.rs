@@ -0,0 +1,31 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// A probe got no answer, e.g. as a node on the way disconnected or was overloaded
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProbeErrorMessage {
+    pub identifier: UniqueIdentifier,
+    /// e.g. `DISCONNECTED`, `OVERLOAD`, `TIMEOUT` or `CANNOT_FORWARD`
+    pub error_type: Box<str>,
+    /// Whether the error happened at the node the client is connected to
+    pub local: bool,
+}
+
+impl TryFrom<Message> for ProbeErrorMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ProbeError)?;
+
+        let fields = value.fields().field_set();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.try_into()?,
+            error_type: fields.get_or_err("Type")?.into(),
+            local: fields.get_bool("Local")?.unwrap_or(false),
+        })
+    }
+}
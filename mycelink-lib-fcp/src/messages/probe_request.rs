@@ -0,0 +1,92 @@
+use crate::decode_error::DecodeError;
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::ProbeRequest;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::str::FromStr;
+
+/// Hops probes take by default, enough to end at a random node of the network
+pub const DEFAULT_PROBE_HOPS_TO_LIVE: u8 = 25;
+
+/// Asks a random node of the network for one piece of information, answered with the `Probe*`
+/// message matching the [ProbeType], `ProbeError` or `ProbeRefused`
+pub struct ProbeRequestMessage {
+    pub identifier: UniqueIdentifier,
+    pub probe_type: ProbeType,
+    /// At most 70, larger values are refused by the node
+    pub hops_to_live: u8,
+}
+
+/// What a probe asks for, the answers are made imprecise by the responding node
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProbeType {
+    Bandwidth,
+    Build,
+    /// A random identifier that stays the same for a node, with its uptime
+    Identifier,
+    LinkLengths,
+    Location,
+    StoreSize,
+    Uptime48h,
+    Uptime7d,
+    RejectStats,
+    OverallBulkOutputCapacityUsage,
+}
+
+impl From<&ProbeType> for &str {
+    fn from(value: &ProbeType) -> Self {
+        match value {
+            ProbeType::Bandwidth => "BANDWIDTH",
+            ProbeType::Build => "BUILD",
+            ProbeType::Identifier => "IDENTIFIER",
+            ProbeType::LinkLengths => "LINK_LENGTHS",
+            ProbeType::Location => "LOCATION",
+            ProbeType::StoreSize => "STORE_SIZE",
+            ProbeType::Uptime48h => "UPTIME_48H",
+            ProbeType::Uptime7d => "UPTIME_7D",
+            ProbeType::RejectStats => "REJECT_STATS",
+            ProbeType::OverallBulkOutputCapacityUsage => "OVERALL_BULK_OUTPUT_CAPACITY_USAGE",
+        }
+    }
+}
+
+impl From<&ProbeType> for Box<str> {
+    fn from(value: &ProbeType) -> Self {
+        Into::<&str>::into(value).into()
+    }
+}
+
+impl FromStr for ProbeType {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BANDWIDTH" => Ok(ProbeType::Bandwidth),
+            "BUILD" => Ok(ProbeType::Build),
+            "IDENTIFIER" => Ok(ProbeType::Identifier),
+            "LINK_LENGTHS" => Ok(ProbeType::LinkLengths),
+            "LOCATION" => Ok(ProbeType::Location),
+            "STORE_SIZE" => Ok(ProbeType::StoreSize),
+            "UPTIME_48H" => Ok(ProbeType::Uptime48h),
+            "UPTIME_7D" => Ok(ProbeType::Uptime7d),
+            "REJECT_STATS" => Ok(ProbeType::RejectStats),
+            "OVERALL_BULK_OUTPUT_CAPACITY_USAGE" => Ok(ProbeType::OverallBulkOutputCapacityUsage),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown probe type {s}").into(),
+            )),
+        }
+    }
+}
+
+impl From<&ProbeRequestMessage> for Message {
+    fn from(value: &ProbeRequestMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("Type".into(), (&value.probe_type).into()),
+            Field::unvalidated("HopsToLive".into(), value.hops_to_live.to_string().into()),
+        ];
+
+        Self::new(Client(ProbeRequest), fields.into(), None)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType::{
+    ProbeBandwidth, ProbeBuild, ProbeIdentifier, ProbeLinkLengths, ProbeLocation,
+    ProbeOverallBulkOutputCapacityUsage, ProbeRejectStats, ProbeStoreSize, ProbeUptime,
+};
+use crate::model::unique_identifier::UniqueIdentifier;
+use std::collections::HashMap;
+
+/// The answer to a `ProbeRequest`, one of the `Probe*` messages except `ProbeError` and
+/// `ProbeRefused`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeResultMessage {
+    pub identifier: UniqueIdentifier,
+    pub result: ProbeResult,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProbeResult {
+    /// Output bandwidth limit in KiB/s
+    Bandwidth {
+        output_bandwidth: f64,
+    },
+    Build(u32),
+    /// See [estimate_network_size]
+    Identifier {
+        identifier: i64,
+        uptime_percent: u8,
+    },
+    /// Distances to the peers of the node in the keyspace
+    LinkLengths(Vec<f64>),
+    Location(f64),
+    /// Size of the datastore in GiB
+    StoreSize(f64),
+    Uptime {
+        uptime_percent: f64,
+    },
+    /// Percentage of requests and inserts rejected recently
+    RejectStats {
+        chk_request: u8,
+        ssk_request: u8,
+        chk_insert: u8,
+        ssk_insert: u8,
+    },
+    OverallBulkOutputCapacityUsage {
+        output_bandwidth_class: u8,
+        capacity_usage: f64,
+    },
+}
+
+impl TryFrom<Message> for ProbeResultMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        let fields = value.fields().field_set();
+        let result = match value.message_type() {
+            MessageType::Node(ProbeBandwidth) => ProbeResult::Bandwidth {
+                output_bandwidth: fields.parse_or_err("OutputBandwidth")?,
+            },
+            MessageType::Node(ProbeBuild) => ProbeResult::Build(fields.parse_or_err("Build")?),
+            MessageType::Node(ProbeIdentifier) => ProbeResult::Identifier {
+                identifier: fields.parse_or_err("ProbeIdentifier")?,
+                uptime_percent: fields.parse_or_err("UptimePercent")?,
+            },
+            MessageType::Node(ProbeLinkLengths) => {
+                ProbeResult::LinkLengths(fields.parse_list("LinkLengths")?.unwrap_or_default())
+            }
+            MessageType::Node(ProbeLocation) => {
+                ProbeResult::Location(fields.parse_or_err("Location")?)
+            }
+            MessageType::Node(ProbeStoreSize) => {
+                ProbeResult::StoreSize(fields.parse_or_err("StoreSize")?)
+            }
+            MessageType::Node(ProbeUptime) => ProbeResult::Uptime {
+                uptime_percent: fields.parse_or_err("UptimePercent")?,
+            },
+            MessageType::Node(ProbeRejectStats) => ProbeResult::RejectStats {
+                chk_request: fields.parse_or_err("CHKRequest")?,
+                ssk_request: fields.parse_or_err("SSKRequest")?,
+                chk_insert: fields.parse_or_err("CHKInsert")?,
+                ssk_insert: fields.parse_or_err("SSKInsert")?,
+            },
+            MessageType::Node(ProbeOverallBulkOutputCapacityUsage) => {
+                ProbeResult::OverallBulkOutputCapacityUsage {
+                    output_bandwidth_class: fields.parse_or_err("OutputBandwidthClass")?,
+                    capacity_usage: fields.parse_or_err("OverallBulkOutputCapacityUsage")?,
+                }
+            }
+            got => {
+                return Err(DecodeError::ExpectedDifferentMessageType {
+                    expected: MessageType::Node(ProbeBuild),
+                    got,
+                })
+            }
+        };
+
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.try_into()?,
+            result,
+        })
+    }
+}
+
+/// Estimates the number of nodes from the identifiers of `ProbeIdentifier` answers
+///
+/// Probes end at nodes picked at random, so among `k` samples of a network of `n` nodes about
+/// `k(k-1)/2n` pairs come from the same node. [None] until at least one pair collided.
+pub fn estimate_network_size(identifiers: &[i64]) -> Option<f64> {
+    let mut counts = HashMap::new();
+    for identifier in identifiers {
+        *counts.entry(identifier).or_insert(0u64) += 1;
+    }
+    let collisions: u64 = counts.values().map(|e| e * (e - 1) / 2).sum();
+    if collisions == 0 {
+        return None;
+    }
+
+    let samples = identifiers.len() as f64;
+    Some(samples * (samples - 1.0) / (2.0 * collisions as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::probe_result::estimate_network_size;
+
+    #[test]
+    fn test_estimate_network_size() {
+        assert_eq!(estimate_network_size(&[]), None);
+        assert_eq!(estimate_network_size(&[1, 2, 3]), None);
+        // 5 samples, 10 pairs of which 2 collide
+        assert_eq!(estimate_network_size(&[1, 2, 1, 3, 2]), Some(5.0));
+        // 3 samples of the same node are 3 colliding pairs
+        assert_eq!(estimate_network_size(&[7, 7, 7]), Some(1.0));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::protocol_error_code::ProtocolErrorCode;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// The node refused a message, e.g. as a field was missing or a file could not be read
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolErrorMessage {
+    /// Of the refused message, if it had one and the node could read it
+    pub identifier: Option<UniqueIdentifier>,
+    pub code: ProtocolErrorCode,
+    pub code_description: Box<str>,
+    /// Details like the name of the missing field
+    pub extra_description: Option<Box<str>>,
+    /// The node closes the connection after fatal errors
+    pub fatal: bool,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for ProtocolErrorMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::ProtocolError)?;
+
+        let fields = value.fields().field_set();
+        Ok(Self {
+            identifier: match fields.get("Identifier") {
+                None => None,
+                Some(identifier) => Some(identifier.try_into()?),
+            },
+            code: fields.parse_or_err::<u32>("Code")?.into(),
+            code_description: fields.get("CodeDescription").unwrap_or_default().into(),
+            extra_description: fields.get("ExtraDescription").map(Into::into),
+            fatal: fields.get_bool("Fatal")?.unwrap_or(false),
+            global: fields.get_bool("Global")?.unwrap_or(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::protocol_error::ProtocolErrorMessage;
+    use crate::model::fields::Field;
+    use crate::model::message::Message;
+    use crate::model::message_type_identifier::MessageType::Node;
+    use crate::model::message_type_identifier::NodeMessageType::ProtocolError;
+    use crate::model::protocol_error_code::ProtocolErrorCode;
+
+    fn decode(fields: &[(&'static str, &'static str)]) -> ProtocolErrorMessage {
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|(key, value)| Field::unvalidated((*key).into(), (*value).into()))
+            .collect();
+        Message::new(Node(ProtocolError), fields.into(), None)
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_codes() {
+        let protocol_error = decode(&[
+            ("Code", "25"),
+            ("CodeDescription", "Direct disk access denied"),
+            ("Fatal", "false"),
+            ("Global", "false"),
+        ]);
+        assert_eq!(
+            protocol_error.code,
+            ProtocolErrorCode::DirectDiskAccessDenied
+        );
+        assert_eq!(protocol_error.identifier, None);
+
+        let protocol_error = decode(&[("Code", "1000"), ("Fatal", "true")]);
+        assert_eq!(protocol_error.code, ProtocolErrorCode::Unknown(1000));
+        assert_eq!(u32::from(protocol_error.code), 1000);
+        assert!(protocol_error.fatal);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::RemovePeer;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Removes a peer, answered with `PeerRemoved`
+pub struct RemovePeerMessage {
+    pub identifier: UniqueIdentifier,
+    /// Name, identity or `host:port` of the peer
+    pub node_identifier: Box<str>,
+}
+
+impl From<RemovePeerMessage> for Message {
+    fn from(value: RemovePeerMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("NodeIdentifier".into(), value.node_identifier),
+        ];
+
+        Self::new(Client(RemovePeer), fields.into(), None)
+    }
+}
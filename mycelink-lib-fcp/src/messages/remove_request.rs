@@ -0,0 +1,22 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::RemoveRequest;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Cancels a running request or removes a finished persistent one
+pub struct RemoveRequestMessage {
+    pub identifier: UniqueIdentifier,
+    pub global: bool,
+}
+
+impl From<RemoveRequestMessage> for Message {
+    fn from(value: RemoveRequestMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("Global".into(), value.global.to_string().into()),
+        ];
+
+        Self::new(Client(RemoveRequest), fields.into(), None)
+    }
+}
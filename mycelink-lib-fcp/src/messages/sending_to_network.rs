@@ -0,0 +1,30 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// An insert finished encoding and now sends its blocks into the network
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SendingToNetworkMessage {
+    pub identifier: UniqueIdentifier,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for SendingToNetworkMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::SendingToNetwork)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
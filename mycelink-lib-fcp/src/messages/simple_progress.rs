@@ -0,0 +1,60 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Block counts of a running insert or fetch
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimpleProgressMessage {
+    pub identifier: UniqueIdentifier,
+    pub total: u64,
+    /// Blocks needed to finish, may be less than `total` for fetches
+    pub required: u64,
+    pub failed: u64,
+    pub fatally_failed: u64,
+    pub succeeded: u64,
+    /// Whether `total` is final or may still grow
+    pub finalized_total: bool,
+    /// Milliseconds since the unix epoch of the last progress
+    pub last_progress: Option<u64>,
+    pub global: bool,
+}
+
+impl SimpleProgressMessage {
+    /// Share of the required blocks that succeeded, between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        if self.required == 0 {
+            return 0.0;
+        }
+        (self.succeeded as f64 / self.required as f64).min(1.0)
+    }
+}
+
+impl TryFrom<Message> for SimpleProgressMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::SimpleProgress)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            total: fields.get_or_err("Total")?.value().parse()?,
+            required: fields.get_or_err("Required")?.value().parse()?,
+            failed: fields.get_or_err("Failed")?.value().parse()?,
+            fatally_failed: fields.get_or_err("FatallyFailed")?.value().parse()?,
+            succeeded: fields.get_or_err("Succeeded")?.value().parse()?,
+            finalized_total: fields.get_or_err("FinalizedTotal")?.value().parse()?,
+            last_progress: match fields.get("LastProgress") {
+                None => None,
+                Some(field) => Some(field.value().parse()?),
+            },
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -2,11 +2,12 @@ use crate::decode_error::DecodeError;
 use crate::model::message::Message;
 use crate::model::message_type_identifier::NodeMessageType;
 use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
 
 pub struct SSKKeypairMessage {
     pub identifier: UniqueIdentifier,
-    pub request_uri: Box<str>, // todo maybe replace with uri type, has to contain "freenet:"
-    pub insert_uri: Box<str>,
+    pub request_uri: URI,
+    pub insert_uri: URI,
 }
 
 impl TryFrom<Message> for SSKKeypairMessage {
@@ -23,8 +24,8 @@ impl TryFrom<Message> for SSKKeypairMessage {
                 .get_or_err("Identifier")?
                 .value()
                 .try_into()?,
-            request_uri: value.fields().get_or_err("RequestURI")?.value().into(),
-            insert_uri: value.fields().get_or_err("InsertURI")?.value().into(),
+            request_uri: value.fields().get_or_err("RequestURI")?.value().parse()?,
+            insert_uri: value.fields().get_or_err("InsertURI")?.value().parse()?,
         })
     }
 }
@@ -0,0 +1,32 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StartedCompressionMessage {
+    pub identifier: UniqueIdentifier,
+    /// Name of the compressor, e.g. `LZMA_NEW`
+    pub codec: Box<str>,
+    pub global: bool,
+}
+
+impl TryFrom<Message> for StartedCompressionMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::StartedCompression)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            codec: fields.get_or_err("Codec")?.value().into(),
+            global: match fields.get("Global") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -20,13 +20,13 @@ pub struct SubscribeUSKMessage {
 impl From<SubscribeUSKMessage> for Message {
     fn from(value: SubscribeUSKMessage) -> Self {
         let fields = vec![
-            Field::new("Identifier".into(), (&value.identifier).into()),
-            Field::new("URI".into(), (&value.uri).into()),
-            Field::new("DontPoll".into(), value.dont_poll.to_string().into()),
-            Field::new("SparsePoll".into(), value.sparse_poll.to_string().into()),
-            Field::new("PriorityClass".into(), (&value.priority_class).into()),
-            Field::new("RealTimeFlag".into(), value.real_time.to_string().into()),
-            Field::new(
+            Field::unvalidated("Identifier".into(), (&value.identifier).into()),
+            Field::unvalidated("URI".into(), (&value.uri).into()),
+            Field::unvalidated("DontPoll".into(), value.dont_poll.to_string().into()),
+            Field::unvalidated("SparsePoll".into(), value.sparse_poll.to_string().into()),
+            Field::unvalidated("PriorityClass".into(), (&value.priority_class).into()),
+            Field::unvalidated("RealTimeFlag".into(), value.real_time.to_string().into()),
+            Field::unvalidated(
                 "IgnoreUSKDatehints".into(),
                 value.ignore_usk_datehints.to_string().into(),
             ),
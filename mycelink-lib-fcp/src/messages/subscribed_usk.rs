@@ -0,0 +1,33 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+
+/// Confirms a `SubscribeUSK`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscribedUSKMessage {
+    pub identifier: UniqueIdentifier,
+    pub uri: URI,
+    pub dont_poll: bool,
+}
+
+impl TryFrom<Message> for SubscribedUSKMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::SubscribedUSK)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            uri: fields.get_or_err("URI")?.value().try_into()?,
+            dont_poll: match fields.get("DontPoll") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
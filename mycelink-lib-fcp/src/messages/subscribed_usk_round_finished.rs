@@ -0,0 +1,28 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// A polling round for a subscribed USK finished without finding newer editions
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscribedUSKRoundFinishedMessage {
+    pub identifier: UniqueIdentifier,
+}
+
+impl TryFrom<Message> for SubscribedUSKRoundFinishedMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::SubscribedUSKRoundFinished)?;
+
+        Ok(Self {
+            identifier: value
+                .fields()
+                .get_or_err("Identifier")?
+                .value()
+                .try_into()?,
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use crate::decode_error::DecodeError;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::unique_identifier::UniqueIdentifier;
+use crate::model::uri::URI;
+
+/// A newer edition of a subscribed USK was found
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscribedUSKUpdateMessage {
+    pub identifier: UniqueIdentifier,
+    pub edition: i64,
+    /// The USK at `edition`
+    pub uri: URI,
+    /// Whether the edition was actually fetched instead of only being hinted at
+    pub new_known_good: bool,
+    /// Whether the edition is newer than all previously announced ones
+    pub new_slot_too: bool,
+}
+
+impl TryFrom<Message> for SubscribedUSKUpdateMessage {
+    type Error = DecodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
+        value
+            .message_type()
+            .expect_specific_node_message(NodeMessageType::SubscribedUSKUpdate)?;
+
+        let fields = value.fields();
+        Ok(Self {
+            identifier: fields.get_or_err("Identifier")?.value().try_into()?,
+            edition: fields.get_or_err("Edition")?.value().parse()?,
+            uri: fields.get_or_err("URI")?.value().try_into()?,
+            new_known_good: match fields.get("NewKnownGood") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+            new_slot_too: match fields.get("NewSlotToo") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+        })
+    }
+}
@@ -3,10 +3,11 @@ use crate::model::message::Message;
 use crate::model::message_type_identifier::NodeMessageType;
 use std::path::{Path, PathBuf};
 
+/// Result of a `TestDDARequest`, access that was not requested is reported as not allowed
 pub struct TestDDACompleteMessage {
     pub directory: Box<Path>,
-    pub read_filename: Box<str>,
-    pub write_filename: Box<str>,
+    pub read_directory_allowed: bool,
+    pub write_directory_allowed: bool,
 }
 
 impl TryFrom<Message> for TestDDACompleteMessage {
@@ -17,10 +18,17 @@ impl TryFrom<Message> for TestDDACompleteMessage {
             .message_type()
             .expect_specific_node_message(NodeMessageType::TestDDAComplete)?;
 
+        let fields = value.fields();
         Ok(Self {
-            directory: PathBuf::from(value.fields().get_or_err("Directory")?.value()).into(),
-            read_filename: value.fields().get_or_err("ReadFilename")?.value().into(),
-            write_filename: value.fields().get_or_err("WriteFilename")?.value().into(),
+            directory: PathBuf::from(fields.get_or_err("Directory")?.value()).into(),
+            read_directory_allowed: match fields.get("ReadDirectoryAllowed") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
+            write_directory_allowed: match fields.get("WriteDirectoryAllowed") {
+                None => false,
+                Some(field) => field.value().parse()?,
+            },
         })
     }
 }
@@ -3,11 +3,14 @@ use crate::model::message::Message;
 use crate::model::message_type_identifier::NodeMessageType;
 use std::path::{Path, PathBuf};
 
+/// Asks the client to prove it can access the files of a `TestDDARequest` directory
 pub struct TestDDAReplyMessage {
     pub directory: Box<Path>,
-    pub read_filename: Box<str>,
-    pub write_filename: Box<str>,
-    pub content_to_write: Box<str>, //todo is this really a str?
+    /// File created by the node whose content is sent back, if read access was requested
+    pub read_filename: Option<Box<Path>>,
+    /// File the client has to create with `content_to_write`, if write access was requested
+    pub write_filename: Option<Box<Path>>,
+    pub content_to_write: Option<Box<str>>,
 }
 
 impl TryFrom<Message> for TestDDAReplyMessage {
@@ -18,11 +21,16 @@ impl TryFrom<Message> for TestDDAReplyMessage {
             .message_type()
             .expect_specific_node_message(NodeMessageType::TestDDAReply)?;
 
+        let fields = value.fields();
         Ok(Self {
-            directory: PathBuf::from(value.fields().get_or_err("Directory")?.value()).into(),
-            read_filename: value.fields().get_or_err("ReadFilename")?.value().into(),
-            write_filename: value.fields().get_or_err("WriteFilename")?.value().into(),
-            content_to_write: value.fields().get_or_err("ContentToWrite")?.value().into(),
+            directory: PathBuf::from(fields.get_or_err("Directory")?.value()).into(),
+            read_filename: fields
+                .get("ReadFilename")
+                .map(|e| PathBuf::from(e.value()).into()),
+            write_filename: fields
+                .get("WriteFilename")
+                .map(|e| PathBuf::from(e.value()).into()),
+            content_to_write: fields.get("ContentToWrite").map(|e| e.value().into()),
         })
     }
 }
@@ -17,12 +17,12 @@ impl From<TestDDARequestMessage> for Message {
         Message::new(
             MESSAGE_TYPE,
             vec![
-                Field::new("Directory".into(), value.directory.to_string_lossy().into()),
-                Field::new(
-                    "WandReadDirectory".into(),
+                Field::unvalidated("Directory".into(), value.directory.to_string_lossy().into()),
+                Field::unvalidated(
+                    "WantReadDirectory".into(),
                     value.want_read_directory.to_string().into(),
                 ),
-                Field::new(
+                Field::unvalidated(
                     "WantWriteDirectory".into(),
                     value.want_write_directory.to_string().into(),
                 ),
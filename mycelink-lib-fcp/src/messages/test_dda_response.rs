@@ -16,8 +16,8 @@ impl From<TestDDAResponseMessage> for Message {
         Message::new(
             MESSAGE_TYPE,
             vec![
-                Field::new("Directory".into(), value.directory.to_string_lossy().into()),
-                Field::new("ReadContent".into(), value.read_content),
+                Field::unvalidated("Directory".into(), value.directory.to_string_lossy().into()),
+                Field::unvalidated("ReadContent".into(), value.read_content),
             ]
             .into(),
             None,
@@ -0,0 +1,21 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::UnsubscribeUSK;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::unique_identifier::UniqueIdentifier;
+
+/// Stops a `SubscribeUSK`
+pub struct UnsubscribeUSKMessage {
+    pub identifier: UniqueIdentifier,
+}
+
+impl From<UnsubscribeUSKMessage> for Message {
+    fn from(value: UnsubscribeUSKMessage) -> Self {
+        let fields = vec![Field::unvalidated(
+            "Identifier".into(),
+            (&value.identifier).into(),
+        )];
+
+        Self::new(Client(UnsubscribeUSK), fields.into(), None)
+    }
+}
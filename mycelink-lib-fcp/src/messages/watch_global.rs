@@ -0,0 +1,22 @@
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType::WatchGlobal;
+use crate::model::message_type_identifier::MessageType::Client;
+use crate::model::verbosity::Verbosity;
+
+/// Subscribes to (or unsubscribes from) the messages of requests on the global queue
+pub struct WatchGlobalMessage {
+    pub enabled: bool,
+    pub verbosity_mask: Verbosity,
+}
+
+impl From<WatchGlobalMessage> for Message {
+    fn from(value: WatchGlobalMessage) -> Self {
+        let fields = vec![
+            Field::unvalidated("Enabled".into(), value.enabled.to_string().into()),
+            Field::unvalidated("VerbosityMask".into(), (&value.verbosity_mask).into()),
+        ];
+
+        Self::new(Client(WatchGlobal), fields.into(), None)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::messages::get_failed::DATA_NOT_FOUND_CODE;
+use crate::mock_node::normalize;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::ClientMessageType;
+use std::time::Duration;
+
+/// Scripted misbehaviour of a [super::MockNode]
+///
+/// A fault applies to every client message of `message_type` whose `URI` starts with `uri`
+/// (any URI if [None]). It is removed after it has been applied `times` times, or stays forever
+/// if `times` is [None], so with `Some(0)` it never applies. Faults are checked in the order they
+/// were injected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockFault {
+    pub message_type: ClientMessageType,
+    pub uri: Option<Box<str>>,
+    pub behaviour: MockBehaviour,
+    pub times: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MockBehaviour {
+    /// Handles the request normally but only after the delay has passed
+    Delay(Duration),
+    /// Swallows the request without ever answering it
+    Drop,
+    /// Answers with `PutFailed` for `ClientPut`, `GetFailed` for `ClientGet` and a
+    /// `ProtocolError` for everything else
+    Fail { code: u32 },
+}
+
+impl MockFault {
+    /// Lets the next `polls` `ClientGet` requests for `uri` fail with [DATA_NOT_FOUND_CODE]
+    pub fn hide_key(uri: &str, polls: usize) -> Self {
+        Self {
+            message_type: ClientMessageType::ClientGet,
+            uri: Some(uri.into()),
+            behaviour: MockBehaviour::Fail {
+                code: DATA_NOT_FOUND_CODE,
+            },
+            times: Some(polls),
+        }
+    }
+
+    pub(super) fn matches(&self, message_type: ClientMessageType, message: &Message) -> bool {
+        if self.message_type != message_type {
+            return false;
+        }
+
+        match &self.uri {
+            None => true,
+            Some(uri) => message
+                .fields()
+                .get("URI")
+                .map(|e| normalize(e.value()).starts_with(normalize(uri).as_ref()))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Takes the behaviour of the first matching fault and forgets faults that are used up
+pub(super) fn take_fault(
+    faults: &mut Vec<MockFault>,
+    message_type: ClientMessageType,
+    message: &Message,
+) -> Option<MockBehaviour> {
+    faults.retain(|e| e.times != Some(0));
+    let index = faults
+        .iter()
+        .position(|e| e.matches(message_type, message))?;
+    let fault = &mut faults[index];
+    let behaviour = fault.behaviour;
+
+    if let Some(times) = &mut fault.times {
+        *times -= 1;
+        if *times == 0 {
+            faults.remove(index);
+        }
+    }
+
+    Some(behaviour)
+}
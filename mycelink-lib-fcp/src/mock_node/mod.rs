@@ -0,0 +1,1912 @@
+//! In-process stand-in for a Hyphanet node.
+//!
+//! [MockNode] speaks enough FCP 2.0 to let an [crate::fcp_connector::FCPConnector] run without a
+//! real node on `localhost:9481`. Inserted data is kept in memory and shared between all
+//! connections of the same [MockNode]. Slow or failing network behaviour can be scripted
+//! with [MockFault]s.
+
+pub mod fault;
+mod node_info;
+mod peers;
+mod plugin;
+
+#[cfg(test)]
+use crate::fcp_connector::FCPConnector;
+use crate::message_reader::MessageReader;
+use crate::messages::get_failed::DATA_NOT_FOUND_CODE;
+use crate::mock_node::fault::{take_fault, MockBehaviour, MockFault};
+use crate::mock_node::peers::MockPeer;
+use crate::model::fields::{Field, Fields};
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::ClientMessageType;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::protocol_error_code::ProtocolErrorCode;
+use crate::model::uri::FREENET_BASE64;
+use crate::model::verbosity::Verbosity;
+use base64::Engine;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::io::{duplex, split, AsyncRead, AsyncWrite, DuplexStream};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::{Mutex, Notify};
+
+/// Name of the only plugin of the mock node, which echoes every `FCPPluginMessage`
+pub const MOCK_ECHO_PLUGIN: &str = "plugins.Echo.Echo";
+
+const FREENET_PREFIX: &str = "freenet:";
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+const SSK_INSERT_EXTRA: &str = "AQECAAE";
+const SSK_REQUEST_EXTRA: &str = "AQACAAE";
+const CHK_EXTRA: &str = "AAMC--8";
+
+const IN_MEMORY_BUFFER_SIZE: usize = 64 * 1024;
+
+const TOO_BIG_CODE: u32 = 21;
+
+/// The node keeps accepting connections until the tokio runtime it was started on shuts down,
+/// so connections stay usable even after the [MockNode] handle is dropped.
+pub struct MockNode {
+    address: SocketAddr,
+    state: Arc<Mutex<MockNodeState>>,
+}
+
+impl MockNode {
+    /// Binds the mock node to a random local port and starts accepting FCP connections
+    pub async fn start() -> Result<Self, tokio::io::Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockNodeState::default()));
+
+        let accept_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_connection(stream, accept_state.clone()));
+                    }
+                    Err(err) => {
+                        log::error!("Mock node failed to accept connection {err}");
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { address, state })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub async fn connect(&self) -> Result<TcpStream, tokio::io::Error> {
+        TcpStream::connect(self.address).await
+    }
+
+    /// Connects an [FCPConnector] named `name` that is already listening for answers
+    #[cfg(test)]
+    pub(crate) async fn connector(&self, name: &str) -> Arc<FCPConnector> {
+        let stream = self.connect().await.unwrap();
+        let connector = Arc::new(FCPConnector::new(stream, name).await.unwrap());
+        let listen_connector = connector.clone();
+        tokio::spawn(async move { listen_connector.listen().await });
+        connector
+    }
+
+    /// Opens a connection that bypasses the network stack entirely
+    pub fn connect_in_memory(&self) -> DuplexStream {
+        let (client, node) = duplex(IN_MEMORY_BUFFER_SIZE);
+        tokio::spawn(serve_connection(node, self.state.clone()));
+        client
+    }
+
+    /// Stores data as if it had been inserted under `uri` by some other client
+    pub async fn insert(&self, uri: &str, data: impl Into<Box<[u8]>>) {
+        let mut state = self.state.lock().await;
+        let key = normalize(&state.request_uri(uri));
+        state.store.insert(
+            key,
+            StoredData {
+                data: data.into(),
+                content_type: DEFAULT_CONTENT_TYPE.into(),
+            },
+        );
+    }
+
+    /// Returns the data stored under the request key `uri`, if any
+    pub async fn get(&self, uri: &str) -> Option<Box<[u8]>> {
+        let state = self.state.lock().await;
+        state
+            .store
+            .get(&normalize(uri))
+            .map(|stored| stored.data.clone())
+    }
+
+    /// Closes all client connections as if the node restarted, keeping the stored data
+    pub async fn disconnect_all(&self) {
+        let mut state = self.state.lock().await;
+        for connection in state.connections.drain(..) {
+            connection.close.notify_one();
+        }
+        state.subscriptions.clear();
+    }
+
+    /// Identifiers of all active USK subscriptions
+    pub async fn subscriptions(&self) -> Vec<Box<str>> {
+        let mut state = self.state.lock().await;
+        state.subscriptions.retain(|e| !e.notify.is_closed());
+        state
+            .subscriptions
+            .iter()
+            .map(|e| e.identifier.clone())
+            .collect()
+    }
+
+    /// Identifiers of all requests removed with `RemoveRequest` so far
+    pub async fn removed_requests(&self) -> Vec<Box<str>> {
+        self.state.lock().await.removed_requests.clone()
+    }
+
+    /// Lets the node name its `TestDDA` check files in `directory` instead of the tested one, as
+    /// a malicious node would to read or overwrite other files of the client
+    pub async fn misdirect_dda(&self, directory: impl Into<PathBuf>) {
+        self.state.lock().await.dda_check_directory = Some(directory.into());
+    }
+
+    /// Puts a fetch of `uri` on the global queue as if another client, e.g. FProxy, started it
+    pub async fn add_global_get(&self, identifier: &str, uri: &str, verbosity: &str) {
+        self.state.lock().await.persistent.push(PersistentEntry {
+            identifier: identifier.into(),
+            message_type: NodeMessageType::PersistentGet,
+            fields: vec![
+                ("URI", uri.into()),
+                ("Verbosity", verbosity.into()),
+                ("PersistenceType", "forever".into()),
+                ("Global", "true".into()),
+                ("MaxRetries", "-1".into()),
+                ("ReturnType", "disk".into()),
+                ("Filename", "/tmp/downloads/file".into()),
+            ],
+            priority: "2".into(),
+            client_token: None,
+            answers: Vec::new(),
+        });
+    }
+
+    /// Applies `fault` to all matching requests received from now on
+    pub async fn inject_fault(&self, fault: MockFault) {
+        self.state.lock().await.faults.push(fault);
+    }
+}
+
+#[derive(Default)]
+struct MockNodeState {
+    store: HashMap<Box<str>, StoredData>,
+    /// Maps the key part of generated insert URIs to the matching request key part
+    keypairs: HashMap<Box<str>, Box<str>>,
+    subscriptions: Vec<Subscription>,
+    faults: Vec<MockFault>,
+    removed_requests: Vec<Box<str>>,
+    /// Persistent requests of all clients, the mock node does not keep them apart
+    persistent: Vec<PersistentEntry>,
+    /// Running `TestDDA` handshakes by connection and directory as sent by the client
+    dda_tests: HashMap<(u64, Box<str>), DDATest>,
+    /// Direct disk access by connection and canonical directory
+    dda_allowed: HashMap<(u64, Box<Path>), DiskGrant>,
+    /// Directory the check files are created in instead of the tested one
+    dda_check_directory: Option<PathBuf>,
+    peers: Vec<MockPeer>,
+    /// Options changed with `ModifyConfig`, all others have their default value
+    config: HashMap<Box<str>, Box<str>>,
+    connections: Vec<MockConnection>,
+    next_connection_id: u64,
+}
+
+#[derive(Clone)]
+struct StoredData {
+    data: Box<[u8]>,
+    content_type: Box<str>,
+}
+
+/// Files of a `TestDDAReply` with the content they have to contain
+#[derive(Default)]
+struct DDATest {
+    read: Option<(PathBuf, Box<str>)>,
+    write: Option<(PathBuf, Box<str>)>,
+}
+
+#[derive(Copy, Clone, Default)]
+struct DiskGrant {
+    read: bool,
+    write: bool,
+}
+
+struct Subscription {
+    identifier: Box<str>,
+    usk: Box<str>,
+    notify: UnboundedSender<Message>,
+}
+
+/// A `ClientGet` or `ClientPut` that was not scoped to its connection
+struct PersistentEntry {
+    identifier: Box<str>,
+    message_type: NodeMessageType,
+    /// Fields of the `PersistentGet` or `PersistentPut` describing the request, except for the
+    /// ones that can be modified
+    fields: Vec<(&'static str, Box<str>)>,
+    priority: Box<str>,
+    client_token: Option<Box<str>>,
+    /// Everything the node answered to the request
+    answers: Vec<Message>,
+}
+
+impl PersistentEntry {
+    fn new(message_type: ClientMessageType, message: &Message) -> Option<Self> {
+        let fields = message.fields();
+        let field = |key: &str, default: &str| -> Box<str> {
+            fields.get(key).map(|e| e.value()).unwrap_or(default).into()
+        };
+
+        let persistence = field("Persistence", "connection");
+        if &*persistence == "connection" {
+            return None;
+        }
+
+        let mut description = vec![
+            ("URI", field("URI", "")),
+            ("Verbosity", field("Verbosity", "0")),
+            ("PersistenceType", persistence),
+            ("Global", "false".into()),
+            ("MaxRetries", field("MaxRetries", "0")),
+            ("RealTime", field("RealTimeFlag", "false")),
+        ];
+        let message_type = match message_type {
+            ClientMessageType::ClientGet => {
+                description.push(("ReturnType", field("ReturnType", "direct")));
+                NodeMessageType::PersistentGet
+            }
+            _ => {
+                description.push(("UploadFrom", field("UploadFrom", "direct")));
+                description.push(("DontCompress", field("DontCompress", "false")));
+                if let Some(data_length) = fields.get("DataLength") {
+                    description.push(("DataLength", data_length.value().into()));
+                }
+                if let Some(content_type) = fields.get("Metadata.ContentType") {
+                    description.push(("Metadata.ContentType", content_type.value().into()));
+                }
+                NodeMessageType::PersistentPut
+            }
+        };
+
+        Some(Self {
+            identifier: identifier(message).unwrap_or_default(),
+            message_type,
+            fields: description,
+            priority: field("PriorityClass", "2"),
+            client_token: fields.get("ClientToken").map(|e| e.value().into()),
+            answers: Vec::new(),
+        })
+    }
+
+    fn describe(&self) -> Message {
+        let mut fields = self.fields.clone();
+        fields.push(("Identifier", self.identifier.clone()));
+        fields.push(("PriorityClass", self.priority.clone()));
+        if let Some(client_token) = &self.client_token {
+            fields.push(("ClientToken", client_token.clone()));
+        }
+
+        node_message(self.message_type, fields, None)
+    }
+}
+
+/// A client connection, closed by notifying `close`
+struct MockConnection {
+    id: u64,
+    client_name: Option<Box<str>>,
+    outgoing: UnboundedSender<Message>,
+    close: Arc<Notify>,
+}
+
+async fn serve_connection(
+    stream: impl AsyncRead + AsyncWrite + Send + 'static,
+    state: Arc<Mutex<MockNodeState>>,
+) {
+    let (rx, tx) = split(stream);
+
+    let (outgoing, outgoing_rx) = unbounded_channel::<Message>();
+    let close = Arc::new(Notify::new());
+    tokio::spawn(write_messages(tx, outgoing_rx, close.clone()));
+
+    let id = {
+        let mut locked_state = state.lock().await;
+        locked_state.next_connection_id += 1;
+        let id = locked_state.next_connection_id;
+        locked_state.connections.push(MockConnection {
+            id,
+            client_name: None,
+            outgoing: outgoing.clone(),
+            close,
+        });
+        id
+    };
+
+    read_messages(MessageReader::new(rx), &state, &outgoing, id).await;
+
+    state.lock().await.connections.retain(|e| e.id != id);
+}
+
+async fn write_messages(
+    mut tx: impl AsyncWrite + Unpin,
+    mut outgoing_rx: UnboundedReceiver<Message>,
+    close: Arc<Notify>,
+) {
+    loop {
+        // Messages queued before closing are still written
+        let message = tokio::select! {
+            biased;
+            message = outgoing_rx.recv() => message,
+            _ = close.notified() => None,
+        };
+        let Some(message) = message else {
+            // The read half keeps the stream alive, so the client only notices this
+            let _ = tx.shutdown().await;
+            return;
+        };
+
+        let encoded = match message.encode() {
+            Ok(encoded) => encoded,
+            Err(err) => {
+                log::error!("Mock node failed to encode {message:?} {err}");
+                continue;
+            }
+        };
+        if tx.write_all(encoded.as_slice()).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn read_messages(
+    mut reader: MessageReader<impl AsyncRead + Unpin>,
+    state: &Arc<Mutex<MockNodeState>>,
+    outgoing: &UnboundedSender<Message>,
+    connection_id: u64,
+) {
+    let mut greeted = false;
+    loop {
+        let message = match Message::decode(&mut reader).await {
+            Ok(message) => message,
+            Err(err) if err.is_recoverable() => {
+                let _ = outgoing.send(protocol_error(
+                    u32::from(ProtocolErrorCode::MessageParseError),
+                    &err.to_string(),
+                    None,
+                    false,
+                ));
+                continue;
+            }
+            Err(err) => {
+                log::debug!("Mock node closes connection ({err})");
+                return;
+            }
+        };
+
+        let message_type = match message.message_type() {
+            MessageType::Client(message_type) => message_type,
+            MessageType::Node(_) => {
+                log::warn!("Mock node received node message {message:?}");
+                continue;
+            }
+            MessageType::Unknown(name) => {
+                let _ = outgoing.send(protocol_error(
+                    u32::from(ProtocolErrorCode::InvalidMessage),
+                    &format!("Unknown message name {name}"),
+                    identifier(&message),
+                    false,
+                ));
+                continue;
+            }
+        };
+
+        if !greeted && message_type != ClientMessageType::ClientHello {
+            let _ = outgoing.send(protocol_error(
+                u32::from(ProtocolErrorCode::ClientHelloMustBeFirst),
+                "Client hello must be first message",
+                None,
+                true,
+            ));
+            return;
+        }
+        if !greeted {
+            let name = message.fields().get("Name").map(|e| e.value().into());
+            state.lock().await.name_connection(connection_id, name);
+        }
+        greeted = true;
+
+        let mut locked_state = state.lock().await;
+        match take_fault(&mut locked_state.faults, message_type, &message) {
+            None => locked_state.handle(message_type, message, outgoing, connection_id),
+            Some(MockBehaviour::Drop) => {
+                log::debug!("Mock node drops {message:?}");
+            }
+            Some(MockBehaviour::Fail { code }) => {
+                let _ = outgoing.send(locked_state.failure(message_type, &message, code));
+            }
+            Some(MockBehaviour::Delay(delay)) => {
+                let state = state.clone();
+                let outgoing = outgoing.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    state
+                        .lock()
+                        .await
+                        .handle(message_type, message, &outgoing, connection_id);
+                });
+            }
+        }
+    }
+}
+
+impl MockNodeState {
+    /// Closes an older connection using the same client name, like Fred does
+    fn name_connection(&mut self, connection_id: u64, client_name: Option<Box<str>>) {
+        for connection in self.connections.iter_mut() {
+            if connection.id != connection_id
+                && connection.client_name.is_some()
+                && connection.client_name == client_name
+            {
+                let _ = connection.outgoing.send(node_message(
+                    NodeMessageType::CloseConnectionDuplicateClientName,
+                    Vec::new(),
+                    None,
+                ));
+                connection.close.notify_one();
+                connection.client_name = None;
+            }
+        }
+
+        if let Some(connection) = self.connections.iter_mut().find(|e| e.id == connection_id) {
+            connection.client_name = client_name;
+        }
+    }
+
+    fn handle(
+        &mut self,
+        message_type: ClientMessageType,
+        message: Message,
+        outgoing: &UnboundedSender<Message>,
+        connection_id: u64,
+    ) {
+        let persistent = PersistentEntry::new(message_type, &message);
+
+        let responses = match message_type {
+            ClientMessageType::ClientHello => vec![node_hello()],
+            ClientMessageType::GenerateSSK => vec![self.generate_ssk(&message)],
+            ClientMessageType::ClientPut => self.client_put(message, connection_id),
+            ClientMessageType::ClientPutComplexDir => {
+                self.client_put_complex_dir(message, connection_id)
+            }
+            ClientMessageType::ClientPutDiskDir => {
+                self.client_put_disk_dir(&message, connection_id)
+            }
+            ClientMessageType::ClientGet => self.client_get(&message, connection_id),
+            ClientMessageType::SubscribeUSK => self.subscribe_usk(&message, outgoing),
+            ClientMessageType::RemoveRequest => self.remove_request(&message),
+            ClientMessageType::UnsubscribeUSK => self.unsubscribe_usk(&message),
+            ClientMessageType::ListPersistentRequests => self.list_persistent_requests(),
+            ClientMessageType::GetRequestStatus => self.get_request_status(&message),
+            ClientMessageType::ModifyPersistentRequest => {
+                vec![self.modify_persistent_request(&message)]
+            }
+            // Requests on the global queue are not supported, so there is nothing to watch
+            ClientMessageType::WatchGlobal => Vec::new(),
+            ClientMessageType::TestDDARequest => {
+                vec![self.test_dda_request(&message, connection_id)]
+            }
+            ClientMessageType::TestDDAResponse => {
+                vec![self.test_dda_response(&message, connection_id)]
+            }
+            ClientMessageType::ListPeers => self.list_peers(&message),
+            ClientMessageType::ListPeer => vec![self.list_peer(&message)],
+            ClientMessageType::AddPeer => vec![self.add_peer(&message)],
+            ClientMessageType::ModifyPeer => vec![self.modify_peer(&message)],
+            ClientMessageType::RemovePeer => vec![self.remove_peer(&message)],
+            ClientMessageType::ListPeerNotes => self.list_peer_notes(&message),
+            ClientMessageType::ModifyPeerNote => vec![self.modify_peer_note(&message)],
+            ClientMessageType::GetNode => vec![self.get_node(&message)],
+            ClientMessageType::GetConfig => vec![self.get_config(&message)],
+            ClientMessageType::ModifyConfig => vec![self.modify_config(&message)],
+            ClientMessageType::ProbeRequest => vec![self.probe_request(&message)],
+            ClientMessageType::FCPPluginMessage => vec![self.fcp_plugin_message(message)],
+        };
+
+        if let Some(mut persistent) = persistent {
+            persistent.answers = responses.clone();
+            self.persistent
+                .retain(|e| e.identifier != persistent.identifier);
+            self.persistent.push(persistent);
+        }
+
+        for response in responses {
+            let _ = outgoing.send(response);
+        }
+    }
+
+    fn failure(&self, message_type: ClientMessageType, message: &Message, code: u32) -> Message {
+        let identifier = identifier(message).unwrap_or_default();
+        let description = format!("Injected failure (code {code})");
+
+        match message_type {
+            ClientMessageType::ClientGet => get_failed(code, &description, identifier),
+            ClientMessageType::ClientPut
+            | ClientMessageType::ClientPutComplexDir
+            | ClientMessageType::ClientPutDiskDir => {
+                let expected_uri = message
+                    .fields()
+                    .get("URI")
+                    .map(|e| format!("{FREENET_PREFIX}{}", self.request_uri(e.value())).into())
+                    .unwrap_or_default();
+                put_failed(code, &description, identifier, expected_uri)
+            }
+            _ => protocol_error(code, &description, self::identifier(message), false),
+        }
+    }
+
+    fn generate_ssk(&mut self, message: &Message) -> Message {
+        let crypto_key = random_key();
+        let insert_key: Box<str> =
+            format!("SSK@{},{crypto_key},{SSK_INSERT_EXTRA}", random_key()).into();
+        let request_key: Box<str> =
+            format!("SSK@{},{crypto_key},{SSK_REQUEST_EXTRA}", random_key()).into();
+
+        self.keypairs
+            .insert(insert_key.clone(), request_key.clone());
+
+        node_message(
+            NodeMessageType::SSKKeypair,
+            vec![
+                ("Identifier", identifier(message).unwrap_or_default()),
+                ("InsertURI", format!("{FREENET_PREFIX}{insert_key}/").into()),
+                (
+                    "RequestURI",
+                    format!("{FREENET_PREFIX}{request_key}/").into(),
+                ),
+            ],
+            None,
+        )
+    }
+
+    fn client_put(&mut self, message: Message, connection_id: u64) -> Vec<Message> {
+        let identifier = identifier(&message).unwrap_or_default();
+        let fields = message.fields();
+
+        let upload_from = fields
+            .get("UploadFrom")
+            .map(|e| e.value())
+            .unwrap_or("direct");
+        let disk_data = match upload_from {
+            "direct" => None,
+            "disk" => match self.read_disk_file(&message, connection_id) {
+                Ok(data) => Some(data),
+                Err(error) => return vec![error],
+            },
+            _ => {
+                return vec![protocol_error(
+                    u32::from(ProtocolErrorCode::NotSupported),
+                    "Mock node only supports direct and disk uploads",
+                    Some(identifier),
+                    false,
+                )]
+            }
+        };
+
+        let Some(uri) = fields.get("URI").map(|e| e.value().to_string()) else {
+            return vec![missing_field("URI", identifier)];
+        };
+        let content_type: Box<str> = fields
+            .get("Metadata.ContentType")
+            .map(|e| e.value())
+            .unwrap_or(DEFAULT_CONTENT_TYPE)
+            .into();
+        let get_chk_only = fields
+            .get("GetCHKOnly")
+            .map(|e| e.value() == "true")
+            .unwrap_or(false);
+        let data = match disk_data {
+            Some(data) => data,
+            None => message
+                .clone()
+                .payload()
+                .map(|e| e.data)
+                .unwrap_or_default(),
+        };
+
+        let request_uri = if normalize(&uri).starts_with("CHK@") {
+            chk_key(&[data.as_ref(), chk_settings(fields).as_bytes()].concat())
+        } else if normalize(&uri).starts_with("USK@") {
+            self.free_usk_edition(normalize(&self.request_uri(&uri)))
+        } else {
+            self.request_uri(&uri)
+        };
+        let key = normalize(&request_uri);
+        let generated_uri: Box<str> = format!("{FREENET_PREFIX}{request_uri}").into();
+        let mut responses = put_events(&message, &generated_uri, data.len());
+
+        if !get_chk_only {
+            self.store
+                .insert(key.clone(), StoredData { data, content_type });
+            self.notify_subscribers(&key);
+        }
+
+        responses.push(node_message(
+            NodeMessageType::PutSuccessful,
+            vec![("Identifier", identifier), ("URI", generated_uri)],
+            None,
+        ));
+
+        responses
+    }
+
+    fn client_put_complex_dir(&mut self, message: Message, connection_id: u64) -> Vec<Message> {
+        let identifier = identifier(&message).unwrap_or_default();
+        let fields = message.fields().field_set();
+        let payload = message
+            .clone()
+            .payload()
+            .map(|e| e.data)
+            .unwrap_or_default();
+
+        let mut offset = 0;
+        let mut files = Vec::new();
+        for (index, file) in fields.list("Files").enumerate() {
+            let field = |key: &str| format!("Files.{index}.{key}");
+            let Some(name) = file.get("Name") else {
+                return vec![missing_field(&field("Name"), identifier)];
+            };
+            let content_type: Box<str> = file
+                .get("Metadata.ContentType")
+                .unwrap_or(DEFAULT_CONTENT_TYPE)
+                .into();
+
+            let data: Box<[u8]> = match file.get("UploadFrom").unwrap_or("direct") {
+                "direct" => {
+                    let Ok(Some(len)) = file.parse::<usize>("DataLength") else {
+                        return vec![missing_field(&field("DataLength"), identifier)];
+                    };
+                    let Some(data) = payload.get(offset..offset + len) else {
+                        return vec![protocol_error(
+                            u32::from(ProtocolErrorCode::MessageParseError),
+                            "Payload is shorter than the direct files",
+                            Some(identifier),
+                            false,
+                        )];
+                    };
+                    offset += len;
+                    data.into()
+                }
+                "disk" => {
+                    let Some(filename) = file.get("Filename") else {
+                        return vec![missing_field(&field("Filename"), identifier)];
+                    };
+                    let filename = Path::new(filename);
+                    let access = filename.parent().map(|e| {
+                        self.check_disk_access(e, identifier.clone(), connection_id, |e| e.read)
+                    });
+                    if let Some(Err(error)) = access {
+                        return vec![error];
+                    }
+                    match std::fs::read(filename) {
+                        Ok(data) => data.into(),
+                        Err(err) => {
+                            return vec![protocol_error(
+                                u32::from(ProtocolErrorCode::CouldNotReadFile),
+                                &err.to_string(),
+                                Some(identifier),
+                                false,
+                            )]
+                        }
+                    }
+                }
+                "redirect" => {
+                    let Some(target) = file.get("TargetURI") else {
+                        return vec![missing_field(&field("TargetURI"), identifier)];
+                    };
+                    // A redirect to unknown data fails to be followed, like a dangling redirect
+                    match self.store.get(&normalize(target)) {
+                        Some(stored) => stored.data.clone(),
+                        None => continue,
+                    }
+                }
+                _ => {
+                    return vec![protocol_error(
+                        u32::from(ProtocolErrorCode::NotSupported),
+                        "Unknown UploadFrom",
+                        Some(identifier),
+                        false,
+                    )]
+                }
+            };
+            files.push((name.into(), StoredData { data, content_type }));
+        }
+
+        self.put_directory(&message, files)
+    }
+
+    fn client_put_disk_dir(&mut self, message: &Message, connection_id: u64) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let fields = message.fields().field_set();
+        let Some(directory) = fields.get("Filename") else {
+            return vec![missing_field("Filename", identifier)];
+        };
+        let directory = Path::new(directory);
+        if let Err(error) =
+            self.check_disk_access(directory, identifier.clone(), connection_id, |e| e.read)
+        {
+            return vec![error];
+        }
+
+        let include_hidden = fields.get_bool("IncludeHiddenFiles").ok().flatten();
+        let allow_unreadable = fields.get_bool("AllowUnreadableFiles").ok().flatten();
+        let mut files = Vec::new();
+        for path in directory_files(directory, include_hidden.unwrap_or(false)) {
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(_) if allow_unreadable.unwrap_or(false) => continue,
+                Err(err) => {
+                    return vec![protocol_error(
+                        u32::from(ProtocolErrorCode::CouldNotReadFile),
+                        &err.to_string(),
+                        Some(identifier),
+                        false,
+                    )]
+                }
+            };
+            let name = path.strip_prefix(directory).unwrap_or(&path);
+            let name = name
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            let content_type = DEFAULT_CONTENT_TYPE.into();
+            files.push((
+                name.into(),
+                StoredData {
+                    data: data.into(),
+                    content_type,
+                },
+            ));
+        }
+
+        self.put_directory(message, files)
+    }
+
+    /// Stores every file under `<manifest>/<name>` and the `DefaultName` file under the manifest
+    fn put_directory(
+        &mut self,
+        message: &Message,
+        files: Vec<(Box<str>, StoredData)>,
+    ) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let fields = message.fields().field_set();
+        let Some(uri) = fields.get("URI") else {
+            return vec![missing_field("URI", identifier)];
+        };
+        let get_chk_only = fields
+            .get_bool("GetCHKOnly")
+            .ok()
+            .flatten()
+            .unwrap_or(false);
+
+        let manifest_uri = if normalize(uri).starts_with("CHK@") {
+            let mut manifest = Vec::new();
+            for (name, stored) in &files {
+                manifest.extend_from_slice(name.as_bytes());
+                manifest.push(0);
+                manifest.extend_from_slice(&stored.data);
+            }
+            chk_key(&manifest)
+        } else if normalize(uri).starts_with("USK@") {
+            self.free_usk_edition(normalize(&self.request_uri(uri)))
+        } else {
+            self.request_uri(uri)
+        };
+        let key = normalize(&manifest_uri);
+        let generated_uri: Box<str> = format!("{FREENET_PREFIX}{manifest_uri}").into();
+        let data_len = files.iter().map(|(_, e)| e.data.len()).sum();
+        let mut responses = put_events(message, &generated_uri, data_len);
+
+        if !get_chk_only {
+            let default_file = fields
+                .get("DefaultName")
+                .and_then(|default| files.iter().find(|(name, _)| &**name == default));
+            if let Some((_, stored)) = default_file {
+                self.store.insert(key.clone(), stored.clone());
+            }
+            for (name, stored) in files {
+                self.store.insert(format!("{key}/{name}").into(), stored);
+            }
+            self.notify_subscribers(&key);
+        }
+
+        responses.push(node_message(
+            NodeMessageType::PutSuccessful,
+            vec![("Identifier", identifier), ("URI", generated_uri)],
+            None,
+        ));
+        responses
+    }
+
+    fn client_get(&self, message: &Message, connection_id: u64) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let fields = message.fields();
+
+        let Some(uri) = fields.get("URI").map(|e| e.value()) else {
+            return vec![missing_field("URI", identifier)];
+        };
+        let return_type = fields
+            .get("ReturnType")
+            .map(|e| e.value())
+            .unwrap_or("direct");
+        let max_size: Option<usize> = fields.get("MaxSize").and_then(|e| e.value().parse().ok());
+
+        let mut key = normalize(uri);
+        if key.starts_with("USK@") {
+            if let Some(latest) = self.requested_usk_edition(&key) {
+                key = latest;
+            }
+        }
+
+        let Some(stored) = self.store.get(&key) else {
+            return vec![get_failed(
+                DATA_NOT_FOUND_CODE,
+                "Data not found",
+                identifier,
+            )];
+        };
+
+        if max_size.map(|max| stored.data.len() > max).unwrap_or(false) {
+            return vec![get_failed(TOO_BIG_CODE, "Too big", identifier)];
+        }
+
+        let verbosity = verbosity(message);
+        let mut responses = Vec::new();
+        if verbosity.expected_mime {
+            responses.push(node_message(
+                NodeMessageType::ExpectedMIME,
+                vec![
+                    ("Identifier", identifier.clone()),
+                    ("Metadata.ContentType", stored.content_type.clone()),
+                ],
+                None,
+            ));
+        }
+        if verbosity.expected_data_length {
+            responses.push(node_message(
+                NodeMessageType::ExpectedDataLength,
+                vec![
+                    ("Identifier", identifier.clone()),
+                    ("DataLength", stored.data.len().to_string().into()),
+                ],
+                None,
+            ));
+        }
+        if verbosity.simple_progress {
+            responses.push(simple_progress(&identifier, stored.data.len()));
+        }
+
+        let data_found = node_message(
+            NodeMessageType::DataFound,
+            vec![
+                ("Identifier", identifier.clone()),
+                ("Metadata.ContentType", stored.content_type.clone()),
+                ("DataLength", stored.data.len().to_string().into()),
+            ],
+            None,
+        );
+
+        match return_type {
+            "direct" => {
+                responses.push(data_found);
+                responses.push(node_message(
+                    NodeMessageType::AllData,
+                    vec![
+                        ("Identifier", identifier),
+                        ("Metadata.ContentType", stored.content_type.clone()),
+                    ],
+                    Some(stored.data.clone()),
+                ));
+            }
+            "none" => responses.push(data_found),
+            "disk" => {
+                if let Err(error) = self.write_disk_file(message, connection_id, &stored.data) {
+                    return vec![error];
+                }
+                responses.push(data_found);
+            }
+            _ => {
+                return vec![protocol_error(
+                    u32::from(ProtocolErrorCode::NotSupported),
+                    "Mock node only supports direct, disk and no downloads",
+                    Some(identifier),
+                    false,
+                )]
+            }
+        }
+
+        responses
+    }
+
+    fn remove_request(&mut self, message: &Message) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+
+        self.subscriptions.retain(|e| e.identifier != identifier);
+        self.persistent.retain(|e| e.identifier != identifier);
+        self.removed_requests.push(identifier.clone());
+
+        vec![node_message(
+            NodeMessageType::PersistentRequestRemoved,
+            vec![("Identifier", identifier), ("Global", "false".into())],
+            None,
+        )]
+    }
+
+    /// Creates the files proving that the client runs on the same machine, the mock node allows
+    /// direct disk access to all directories
+    fn test_dda_request(&mut self, message: &Message, connection_id: u64) -> Message {
+        let fields = message.fields();
+        let Some(directory) = fields.get("Directory").map(|e| e.value()) else {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::MissingField),
+                "Missing field Directory",
+                None,
+                false,
+            );
+        };
+        let Ok(canonical) = std::fs::canonicalize(directory) else {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::FileNotFound),
+                &format!("No directory {directory}"),
+                None,
+                false,
+            );
+        };
+        let wanted = |name| {
+            fields
+                .get(name)
+                .map(|e| e.value() == "true")
+                .unwrap_or(false)
+        };
+
+        let check_directory = self.dda_check_directory.as_deref().unwrap_or(&canonical);
+
+        let mut reply = vec![("Directory", directory.into())];
+        let mut test = DDATest::default();
+        if wanted("WantReadDirectory") {
+            let (filename, content) = dda_check_file(check_directory);
+            if std::fs::write(&filename, content.as_bytes()).is_ok() {
+                reply.push(("ReadFilename", filename.to_string_lossy().into()));
+                test.read = Some((filename, content));
+            }
+        }
+        if wanted("WantWriteDirectory") {
+            let (filename, content) = dda_check_file(check_directory);
+            reply.push(("WriteFilename", filename.to_string_lossy().into()));
+            reply.push(("ContentToWrite", content.clone()));
+            test.write = Some((filename, content));
+        }
+
+        self.dda_tests
+            .insert((connection_id, directory.into()), test);
+        node_message(NodeMessageType::TestDDAReply, reply, None)
+    }
+
+    fn test_dda_response(&mut self, message: &Message, connection_id: u64) -> Message {
+        let fields = message.fields();
+        let Some(directory) = fields.get("Directory").map(|e| e.value()) else {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::MissingField),
+                "Missing field Directory",
+                None,
+                false,
+            );
+        };
+        let Some(test) = self.dda_tests.remove(&(connection_id, directory.into())) else {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::InvalidMessage),
+                &format!("No TestDDARequest for {directory}"),
+                None,
+                false,
+            );
+        };
+
+        let mut complete = vec![("Directory", directory.into())];
+        let mut allowed = DiskGrant::default();
+        if let Some((filename, content)) = test.read {
+            let read_content = fields.get("ReadContent").map(|e| e.value());
+            allowed.read = read_content == Some(content.as_ref());
+            complete.push(("ReadDirectoryAllowed", allowed.read.to_string().into()));
+            let _ = std::fs::remove_file(filename);
+        }
+        if let Some((filename, content)) = test.write {
+            let written = std::fs::read_to_string(&filename).ok();
+            allowed.write = written.as_deref() == Some(content.as_ref());
+            complete.push(("WriteDirectoryAllowed", allowed.write.to_string().into()));
+            let _ = std::fs::remove_file(filename);
+        }
+
+        if let Ok(canonical) = std::fs::canonicalize(directory) {
+            self.dda_allowed
+                .insert((connection_id, canonical.into()), allowed);
+        }
+        node_message(NodeMessageType::TestDDAComplete, complete, None)
+    }
+
+    /// Reads the `Filename` of a disk upload if the connection may read its directory
+    fn read_disk_file(&self, message: &Message, connection_id: u64) -> Result<Box<[u8]>, Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let filename = self.disk_file(message, connection_id, |e| e.read)?;
+        std::fs::read(filename).map(Into::into).map_err(|err| {
+            protocol_error(
+                u32::from(ProtocolErrorCode::CouldNotReadFile),
+                &err.to_string(),
+                Some(identifier),
+                false,
+            )
+        })
+    }
+
+    /// Writes the `Filename` of a disk download if the connection may write its directory
+    fn write_disk_file(
+        &self,
+        message: &Message,
+        connection_id: u64,
+        data: &[u8],
+    ) -> Result<(), Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let filename = self.disk_file(message, connection_id, |e| e.write)?;
+        std::fs::write(filename, data).map_err(|err| {
+            protocol_error(
+                u32::from(ProtocolErrorCode::CouldNotWriteFile),
+                &err.to_string(),
+                Some(identifier),
+                false,
+            )
+        })
+    }
+
+    fn disk_file(
+        &self,
+        message: &Message,
+        connection_id: u64,
+        access: fn(&DiskGrant) -> bool,
+    ) -> Result<PathBuf, Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let Some(filename) = message.fields().get("Filename").map(|e| e.value()) else {
+            return Err(missing_field("Filename", identifier));
+        };
+        let filename = PathBuf::from(filename);
+        let Some(directory) = filename.parent() else {
+            return Err(missing_field("Filename", identifier));
+        };
+        self.check_disk_access(directory, identifier, connection_id, access)?;
+
+        Ok(filename)
+    }
+
+    fn check_disk_access(
+        &self,
+        directory: &Path,
+        identifier: Box<str>,
+        connection_id: u64,
+        access: fn(&DiskGrant) -> bool,
+    ) -> Result<(), Message> {
+        let allowed = std::fs::canonicalize(directory)
+            .ok()
+            .and_then(|e| self.dda_allowed.get(&(connection_id, e.into())))
+            .map(access)
+            .unwrap_or(false);
+        if !allowed {
+            return Err(protocol_error(
+                u32::from(ProtocolErrorCode::DirectDiskAccessDenied),
+                "Direct disk access denied",
+                Some(identifier),
+                false,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn unsubscribe_usk(&mut self, message: &Message) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        self.subscriptions.retain(|e| e.identifier != identifier);
+        Vec::new()
+    }
+
+    fn list_persistent_requests(&self) -> Vec<Message> {
+        let mut responses: Vec<Message> = self
+            .persistent
+            .iter()
+            .flat_map(|e| std::iter::once(e.describe()).chain(e.answers.iter().cloned()))
+            .collect();
+        responses.push(node_message(
+            NodeMessageType::EndListPersistentRequests,
+            Vec::new(),
+            None,
+        ));
+
+        responses
+    }
+
+    fn get_request_status(&self, message: &Message) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let only_data = message
+            .fields()
+            .get("OnlyData")
+            .map(|e| e.value() == "true")
+            .unwrap_or(false);
+
+        let Some(persistent) = self.persistent.iter().find(|e| e.identifier == identifier) else {
+            return vec![no_such_identifier(identifier)];
+        };
+
+        let answers = persistent.answers.iter().cloned();
+        if only_data {
+            answers
+                .filter(|e| e.message_type() == MessageType::Node(NodeMessageType::AllData))
+                .collect()
+        } else {
+            std::iter::once(persistent.describe())
+                .chain(answers)
+                .collect()
+        }
+    }
+
+    fn modify_persistent_request(&mut self, message: &Message) -> Message {
+        let identifier = identifier(message).unwrap_or_default();
+        let Some(persistent) = self
+            .persistent
+            .iter_mut()
+            .find(|e| e.identifier == identifier)
+        else {
+            return no_such_identifier(identifier);
+        };
+
+        let mut fields = vec![("Identifier", identifier), ("Global", "false".into())];
+        if let Some(priority) = message.fields().get("PriorityClass") {
+            persistent.priority = priority.value().into();
+            fields.push(("PriorityClass", persistent.priority.clone()));
+        }
+        if let Some(client_token) = message.fields().get("ClientToken") {
+            persistent.client_token = Some(client_token.value().into());
+            fields.push(("ClientToken", client_token.value().into()));
+        }
+
+        node_message(NodeMessageType::PersistentRequestModified, fields, None)
+    }
+
+    fn subscribe_usk(
+        &mut self,
+        message: &Message,
+        outgoing: &UnboundedSender<Message>,
+    ) -> Vec<Message> {
+        let identifier = identifier(message).unwrap_or_default();
+        let Some(uri) = message.fields().get("URI").map(|e| e.value()) else {
+            return vec![missing_field("URI", identifier)];
+        };
+        let (usk, edition) = split_usk_edition(&normalize(uri));
+        let dont_poll = message
+            .fields()
+            .get("DontPoll")
+            .map(|e| e.value())
+            .unwrap_or("false");
+
+        let mut responses = vec![node_message(
+            NodeMessageType::SubscribedUSK,
+            vec![
+                ("Identifier", identifier.clone()),
+                ("URI", uri.into()),
+                ("DontPoll", dont_poll.into()),
+            ],
+            None,
+        )];
+
+        if let Some(latest) = self.latest_usk_edition(&usk) {
+            if latest >= edition {
+                responses.push(subscribed_usk_update(&identifier, &usk, latest));
+            }
+        }
+        // The store is the whole network of the mock node, so a single round finds everything
+        if dont_poll != "true" {
+            responses.push(node_message(
+                NodeMessageType::SubscribedUSKRoundFinished,
+                vec![("Identifier", identifier.clone())],
+                None,
+            ));
+        }
+
+        self.subscriptions.push(Subscription {
+            identifier,
+            usk,
+            notify: outgoing.clone(),
+        });
+
+        responses
+    }
+
+    fn notify_subscribers(&mut self, key: &str) {
+        self.subscriptions.retain(|e| !e.notify.is_closed());
+
+        if !key.starts_with("USK@") {
+            return;
+        }
+        let (usk, edition) = split_usk_edition(key);
+
+        for subscription in self.subscriptions.iter().filter(|e| e.usk == usk) {
+            let _ = subscription.notify.send(subscribed_usk_update(
+                &subscription.identifier,
+                &usk,
+                edition,
+            ));
+        }
+    }
+
+    /// Translates insert keys generated by this node into their request keys
+    fn request_uri(&self, uri: &str) -> Box<str> {
+        let uri = uri.strip_prefix(FREENET_PREFIX).unwrap_or(uri);
+        let (key, path) = match uri.split_once('/') {
+            Some((key, path)) => (key, Some(path)),
+            None => (uri, None),
+        };
+
+        let key = match key.strip_prefix("USK@") {
+            Some(stripped) => self
+                .keypairs
+                .get(format!("SSK@{stripped}").as_str())
+                .map(|e| e.replacen("SSK@", "USK@", 1).into_boxed_str())
+                .unwrap_or_else(|| key.into()),
+            None => self
+                .keypairs
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| key.into()),
+        };
+
+        match path {
+            None => key,
+            Some(path) => format!("{key}/{path}").into(),
+        }
+    }
+
+    fn free_usk_edition(&self, key: Box<str>) -> Box<str> {
+        let (usk, edition) = split_usk_edition(&key);
+        let mut edition = edition.max(0);
+        while self
+            .store
+            .contains_key(usk_with_edition(&usk, edition).as_ref())
+        {
+            edition += 1;
+        }
+        usk_with_edition(&usk, edition)
+    }
+
+    fn latest_usk_edition(&self, usk: &str) -> Option<i64> {
+        self.store
+            .keys()
+            .filter(|e| e.starts_with("USK@"))
+            .map(|e| split_usk_edition(e))
+            .filter(|(base, _)| base.as_ref() == usk)
+            .map(|(_, edition)| edition)
+            .max()
+    }
+
+    /// Resolves negative editions to the latest known edition
+    fn requested_usk_edition(&self, key: &str) -> Option<Box<str>> {
+        let (usk, edition) = split_usk_edition(key);
+        if edition >= 0 {
+            return None;
+        }
+        self.latest_usk_edition(&usk)
+            .map(|latest| usk_with_edition(&usk, latest))
+    }
+}
+
+/// Strips the `freenet:` prefix and empty path components
+fn normalize(uri: &str) -> Box<str> {
+    let uri = uri.strip_prefix(FREENET_PREFIX).unwrap_or(uri);
+    uri.split('/')
+        .filter(|e| !e.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+        .into()
+}
+
+fn split_usk_edition(key: &str) -> (Box<str>, i64) {
+    match key.rsplit_once('/') {
+        Some((usk, edition)) => match edition.parse() {
+            Ok(edition) => (usk.into(), edition),
+            Err(_) => (key.into(), 0),
+        },
+        None => (key.into(), 0),
+    }
+}
+
+fn usk_with_edition(usk: &str, edition: i64) -> Box<str> {
+    format!("{usk}/{edition}").into()
+}
+
+/// A not yet existing file in `directory` and random content for it
+fn dda_check_file(directory: &Path) -> (PathBuf, Box<str>) {
+    let filename = directory.join(format!("DDACheck-{:016x}.tmp", rand::random::<u64>()));
+    let content = format!("{:032x}", rand::random::<u128>());
+    (filename, content.into())
+}
+
+pub(crate) fn random_key() -> String {
+    FREENET_BASE64.encode(rand::random::<[u8; 32]>())
+}
+
+/// Derives a stable pseudo CHK, the same data always ends up under the same key
+fn chk_key(data: &[u8]) -> Box<str> {
+    let mut digest = Vec::with_capacity(64);
+    for salt in 0u8..8 {
+        let mut hasher = DefaultHasher::new();
+        salt.hash(&mut hasher);
+        data.hash(&mut hasher);
+        digest.extend_from_slice(&hasher.finish().to_be_bytes());
+    }
+    let (routing_key, crypto_key) = digest.split_at(32);
+
+    format!(
+        "CHK@{},{},{CHK_EXTRA}",
+        FREENET_BASE64.encode(routing_key),
+        FREENET_BASE64.encode(crypto_key)
+    )
+    .into()
+}
+
+/// Fields of a `ClientPut` the CHK depends on besides the data
+fn chk_settings(fields: &Fields) -> String {
+    ["CompatibilityMode", "OverrideSplitfileCryptoKey"]
+        .into_iter()
+        .filter_map(|key| fields.get(key))
+        .map(|e| format!("{}={}", e.key(), e.value()))
+        .collect()
+}
+
+/// All files below `directory` in a stable order
+fn directory_files(directory: &Path, include_hidden: bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| include_hidden || !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .flat_map(|path| match path.is_dir() {
+            true => directory_files(&path, include_hidden),
+            false => vec![path],
+        })
+        .collect()
+}
+
+fn identifier(message: &Message) -> Option<Box<str>> {
+    message.fields().get("Identifier").map(|e| e.value().into())
+}
+
+fn verbosity(message: &Message) -> Verbosity {
+    message
+        .fields()
+        .get("Verbosity")
+        .map(|e| Verbosity::parse_lenient(e.value()))
+        .unwrap_or_default()
+}
+
+/// Progress of a request that is split into one block per started KiB
+/// The messages of an insert of `data_len` bytes before it succeeds, depending on its verbosity
+fn put_events(message: &Message, generated_uri: &str, data_len: usize) -> Vec<Message> {
+    let identifier = identifier(message).unwrap_or_default();
+    let field = |key: &str| message.fields().get(key).map(|e| e.value() == "true");
+    let verbosity = verbosity(message);
+
+    let mut responses = Vec::new();
+    if verbosity.compression && !field("DontCompress").unwrap_or(false) {
+        responses.extend(compression_events(&identifier, data_len));
+    }
+    responses.push(node_message(
+        NodeMessageType::URIGenerated,
+        vec![
+            ("Identifier", identifier.clone()),
+            ("URI", generated_uri.into()),
+        ],
+        None,
+    ));
+    if verbosity.simple_progress {
+        responses.push(simple_progress(&identifier, data_len));
+    }
+    if verbosity.sending_to_network && !field("GetCHKOnly").unwrap_or(false) {
+        responses.push(node_message(
+            NodeMessageType::SendingToNetwork,
+            vec![("Identifier", identifier), ("Global", "false".into())],
+            None,
+        ));
+    }
+    responses
+}
+
+fn simple_progress(identifier: &str, data_len: usize) -> Message {
+    let blocks = data_len.div_ceil(1024).max(1).to_string().into_boxed_str();
+    node_message(
+        NodeMessageType::SimpleProgress,
+        vec![
+            ("Identifier", identifier.into()),
+            ("Total", blocks.clone()),
+            ("Required", blocks.clone()),
+            ("Failed", "0".into()),
+            ("FatallyFailed", "0".into()),
+            ("Succeeded", blocks),
+            ("FinalizedTotal", "true".into()),
+            ("Global", "false".into()),
+        ],
+        None,
+    )
+}
+
+/// The mock node never actually compresses, so the data keeps its size
+fn compression_events(identifier: &str, data_len: usize) -> [Message; 2] {
+    [
+        node_message(
+            NodeMessageType::StartedCompression,
+            vec![("Identifier", identifier.into()), ("Codec", "GZIP".into())],
+            None,
+        ),
+        node_message(
+            NodeMessageType::FinishedCompression,
+            vec![
+                ("Identifier", identifier.into()),
+                ("Codec", "-1".into()),
+                ("OriginalSize", data_len.to_string().into()),
+                ("CompressedSize", data_len.to_string().into()),
+            ],
+            None,
+        ),
+    ]
+}
+
+fn node_message(
+    message_type: NodeMessageType,
+    fields: Vec<(&'static str, Box<str>)>,
+    payload: Option<Box<[u8]>>,
+) -> Message {
+    let fields: Vec<Field> = fields
+        .into_iter()
+        .map(|(key, value)| Field::unvalidated(key.into(), value))
+        .collect();
+
+    Message::new(
+        MessageType::Node(message_type),
+        Fields::from(fields),
+        payload.map(|data| MessagePayload {
+            data,
+            data_len_identifier: "DataLength".into(),
+        }),
+    )
+}
+
+fn node_hello() -> Message {
+    node_message(
+        NodeMessageType::NodeHello,
+        vec![
+            ("FCPVersion", "2.0".into()),
+            ("Node", "Fred".into()),
+            ("Version", "Fred,0.7,1.0,1497".into()),
+            ("Build", "1497".into()),
+            ("Revision", "mock".into()),
+            ("Testnet", "false".into()),
+            (
+                "CompressionCodecs",
+                "3 - GZIP(0), BZIP2(1), LZMA_NEW(2)".into(),
+            ),
+            ("ConnectionIdentifier", hex_id().into()),
+            ("NodeLanguage", "ENGLISH".into()),
+        ],
+        None,
+    )
+}
+
+fn hex_id() -> String {
+    rand::random::<[u8; 16]>()
+        .iter()
+        .map(|e| format!("{e:02x}"))
+        .collect()
+}
+
+fn get_failed(code: u32, description: &str, identifier: Box<str>) -> Message {
+    node_message(
+        NodeMessageType::GetFailed,
+        vec![
+            ("Identifier", identifier),
+            ("Code", code.to_string().into()),
+            ("CodeDescription", description.into()),
+            ("ShortCodeDescription", description.into()),
+            ("Fatal", "true".into()),
+            ("Global", "false".into()),
+        ],
+        None,
+    )
+}
+
+fn put_failed(
+    code: u32,
+    description: &str,
+    identifier: Box<str>,
+    expected_uri: Box<str>,
+) -> Message {
+    node_message(
+        NodeMessageType::PutFailed,
+        vec![
+            ("Identifier", identifier),
+            ("Code", code.to_string().into()),
+            ("CodeDescription", description.into()),
+            ("ShortCodeDescription", description.into()),
+            ("ExtraDescription", description.into()),
+            ("ExpectedURI", expected_uri),
+            ("Fatal", "true".into()),
+            ("Global", "false".into()),
+        ],
+        None,
+    )
+}
+
+fn subscribed_usk_update(identifier: &str, usk: &str, edition: i64) -> Message {
+    node_message(
+        NodeMessageType::SubscribedUSKUpdate,
+        vec![
+            ("Identifier", identifier.into()),
+            ("Edition", edition.to_string().into()),
+            ("URI", usk_with_edition(usk, edition)),
+            ("NewKnownGood", "true".into()),
+            ("NewSlotToo", "true".into()),
+        ],
+        None,
+    )
+}
+
+fn missing_field(field: &str, identifier: Box<str>) -> Message {
+    protocol_error(
+        u32::from(ProtocolErrorCode::MissingField),
+        &format!("Missing field {field}"),
+        Some(identifier),
+        false,
+    )
+}
+
+fn no_such_identifier(identifier: Box<str>) -> Message {
+    protocol_error(
+        u32::from(ProtocolErrorCode::NoSuchIdentifier),
+        "No such identifier",
+        Some(identifier),
+        false,
+    )
+}
+
+fn protocol_error(
+    code: u32,
+    description: &str,
+    identifier: Option<Box<str>>,
+    fatal: bool,
+) -> Message {
+    let mut fields = vec![
+        ("Code", code.to_string().into()),
+        ("CodeDescription", description.into()),
+        ("Fatal", fatal.to_string().into()),
+        ("Global", "false".into()),
+    ];
+    if let Some(identifier) = identifier {
+        fields.push(("Identifier", identifier));
+    }
+
+    node_message(NodeMessageType::ProtocolError, fields, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message_reader::MessageReader;
+    use crate::messages::all_data::AllDataMessage;
+    use crate::messages::client_get::ClientGetMessage;
+    use crate::messages::client_hello::{ClientHelloMessage, EXPECTED_VERSION};
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::messages::generate_ssk::GenerateSSKMessage;
+    use crate::messages::get_failed::{GetFailedMessage, DATA_NOT_FOUND_CODE};
+    use crate::messages::node_hello::NodeHelloMessage;
+    use crate::messages::put_failed::PutFailedMessage;
+    use crate::messages::put_successful::PutSuccessfulMessage;
+    use crate::messages::ssk_keypair::SSKKeypairMessage;
+    use crate::messages::subscribe_usk::SubscribeUSKMessage;
+    use crate::messages::uri_generated::UriGeneratedMessage;
+    use crate::mock_node::fault::{MockBehaviour, MockFault};
+    use crate::mock_node::{random_key, MockNode};
+    use crate::model::message::{FCPEncodable, Message};
+    use crate::model::message_type_identifier::ClientMessageType;
+    use crate::model::message_type_identifier::MessageType::Node;
+    use crate::model::message_type_identifier::NodeMessageType::{
+        SubscribedUSK, SubscribedUSKRoundFinished, SubscribedUSKUpdate,
+    };
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::return_type::ReturnType;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+    async fn connect(node: &MockNode) -> (OwnedWriteHalf, MessageReader<OwnedReadHalf>) {
+        let (rx, mut tx) = node.connect().await.unwrap().into_split();
+        // Fred closes older connections using the same name
+        let client_hello = ClientHelloMessage {
+            name: format!("Mock node test {}", rand::random::<u32>()).into(),
+            version: EXPECTED_VERSION,
+        };
+        tx.write_all(client_hello.to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+
+        let mut reader = MessageReader::new(rx);
+        let _node_hello: NodeHelloMessage = Message::decode(&mut reader)
+            .await
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        (tx, reader)
+    }
+
+    fn put_message(uri: &str, data: &[u8]) -> ClientPutMessage {
+        ClientPutMessage {
+            uri: uri.try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Mock put").unwrap(),
+            verbosity: Default::default(),
+            max_retries: 0,
+            priority: PriorityClass::Medium,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Direct { data: data.into() },
+            is_binary_blob: false,
+            real_time: true,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        }
+    }
+
+    fn get_message(uri: &str) -> ClientGetMessage {
+        ClientGetMessage {
+            identifier: UniqueIdentifier::new("Mock get").unwrap(),
+            uri: uri.try_into().unwrap(),
+            verbosity: Default::default(),
+            return_type: ReturnType::Direct,
+            max_size: None,
+            max_temp_size: None,
+            max_retries: 0,
+            priority: PriorityClass::Medium,
+            persistence: Persistence::Connection,
+            ignore_data_store: false,
+            data_store_only: false,
+            real_time: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_put_get_ssk() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+
+        let generate = GenerateSSKMessage {
+            identifier: UniqueIdentifier::new("Mock generate").unwrap(),
+        };
+        tx.write_all(generate.to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let keypair: SSKKeypairMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+
+        let put = put_message(&format!("{}test", keypair.insert_uri), b"Hello World");
+        tx.write_all((&put).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let _uri_generated: UriGeneratedMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        let put_successful: PutSuccessfulMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(
+            put_successful.uri,
+            format!("{}test", keypair.request_uri)
+                .as_str()
+                .try_into()
+                .unwrap()
+        );
+
+        let get = get_message(&format!("{}test", keypair.request_uri));
+        tx.write_all((&get).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let _data_found = Message::decode(&mut rx).await.unwrap();
+        let all_data: AllDataMessage = Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(all_data.data.as_ref(), b"Hello World");
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_ksk() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+
+        let get = get_message("KSK@missing");
+        tx.write_all((&get).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let get_failed: GetFailedMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+
+        assert_eq!(get_failed.code, DATA_NOT_FOUND_CODE);
+        assert_eq!(get_failed.identifier, get.identifier);
+    }
+
+    #[tokio::test]
+    async fn test_chk_is_content_addressed() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+
+        let mut uris = Vec::new();
+        for _ in 0..2 {
+            let put = put_message("CHK@", b"Same content");
+            tx.write_all((&put).to_message().encode().unwrap().as_slice())
+                .await
+                .unwrap();
+            let _uri_generated = Message::decode(&mut rx).await.unwrap();
+            let put_successful: PutSuccessfulMessage =
+                Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+            uris.push(put_successful.uri);
+        }
+
+        assert_eq!(uris[0], uris[1]);
+        assert_eq!(
+            node.get(Box::<str>::from(&uris[0]).as_ref())
+                .await
+                .unwrap()
+                .as_ref(),
+            b"Same content"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usk_subscription_update() {
+        let node = MockNode::start().await.unwrap();
+        let (mut subscriber_tx, mut subscriber_rx) = connect(&node).await;
+        let (mut inserter_tx, mut inserter_rx) = connect(&node).await;
+
+        let usk = format!("USK@{},{},AQACAAE/feed/0", random_key(), random_key());
+        let subscribe = SubscribeUSKMessage {
+            uri: usk.as_str().try_into().unwrap(),
+            dont_poll: false,
+            identifier: UniqueIdentifier::new("Mock subscribe").unwrap(),
+            priority_class: PriorityClass::Medium,
+            real_time: true,
+            sparse_poll: false,
+            ignore_usk_datehints: false,
+        };
+        subscriber_tx
+            .write_all(subscribe.to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let subscribed = Message::decode(&mut subscriber_rx).await.unwrap();
+        assert_eq!(subscribed.message_type(), Node(SubscribedUSK));
+        let round_finished = Message::decode(&mut subscriber_rx).await.unwrap();
+        assert_eq!(
+            round_finished.message_type(),
+            Node(SubscribedUSKRoundFinished)
+        );
+
+        for _ in 0..2 {
+            let put = put_message(&usk, b"Edition");
+            inserter_tx
+                .write_all((&put).to_message().encode().unwrap().as_slice())
+                .await
+                .unwrap();
+            let _uri_generated = Message::decode(&mut inserter_rx).await.unwrap();
+            let _put_successful = Message::decode(&mut inserter_rx).await.unwrap();
+        }
+
+        for edition in ["0", "1"] {
+            let update = Message::decode(&mut subscriber_rx).await.unwrap();
+            assert_eq!(update.message_type(), Node(SubscribedUSKUpdate));
+            assert_eq!(update.fields().get("Edition").unwrap().value(), edition);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hide_key() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+        node.insert("KSK@hidden", b"Hidden".as_slice()).await;
+        node.inject_fault(MockFault::hide_key("KSK@hidden", 1))
+            .await;
+
+        let get = get_message("KSK@hidden");
+        tx.write_all((&get).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let get_failed: GetFailedMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(get_failed.code, DATA_NOT_FOUND_CODE);
+
+        tx.write_all((&get).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let _data_found = Message::decode(&mut rx).await.unwrap();
+        let all_data: AllDataMessage = Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(all_data.data.as_ref(), b"Hidden");
+    }
+
+    #[tokio::test]
+    async fn test_fault_for_zero_times_is_ignored() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+        node.insert("KSK@hidden", b"Hidden".as_slice()).await;
+        node.inject_fault(MockFault::hide_key("KSK@hidden", 0))
+            .await;
+
+        let get = get_message("KSK@hidden");
+        tx.write_all((&get).to_message().encode().unwrap().as_slice())
+            .await
+            .unwrap();
+        let _data_found = Message::decode(&mut rx).await.unwrap();
+        let all_data: AllDataMessage = Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(all_data.data.as_ref(), b"Hidden");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_and_failed_put() {
+        let node = MockNode::start().await.unwrap();
+        let (mut tx, mut rx) = connect(&node).await;
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientPut,
+            uri: Some("KSK@flaky".into()),
+            behaviour: MockBehaviour::Drop,
+            times: Some(1),
+        })
+        .await;
+        node.inject_fault(MockFault {
+            message_type: ClientMessageType::ClientPut,
+            uri: Some("KSK@flaky".into()),
+            behaviour: MockBehaviour::Fail { code: 5 },
+            times: Some(1),
+        })
+        .await;
+
+        let put = put_message("KSK@flaky", b"Flaky");
+        for _ in 0..2 {
+            tx.write_all((&put).to_message().encode().unwrap().as_slice())
+                .await
+                .unwrap();
+        }
+
+        // The dropped request is never answered, so the first answer belongs to the second put
+        let put_failed: PutFailedMessage =
+            Message::decode(&mut rx).await.unwrap().try_into().unwrap();
+        assert_eq!(put_failed.code, 5);
+        assert_eq!(node.get("KSK@flaky").await, None);
+    }
+}
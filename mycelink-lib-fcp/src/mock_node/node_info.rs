@@ -0,0 +1,289 @@
+//! What the [super::MockNode] tells about itself, its configuration and the simulated network
+//! around it
+
+use super::{identifier, missing_field, node_message, MockNodeState};
+use crate::model::field_set::FieldSet;
+use crate::model::fields::Field;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+
+/// Number of nodes probes end at, all of them answer identically except for their identifier
+pub const MOCK_NETWORK_SIZE: u64 = 100;
+const MAX_HOPS_TO_LIVE: u8 = 70;
+
+const NODE_REF: &[(&str, &str)] = &[
+    ("identity", "mock-node-identity"),
+    ("myName", "Mock node"),
+    ("version", "Fred,0.7,1.0,1497"),
+    ("lastGoodVersion", "Fred,0.7,1.0,1475"),
+    ("location", "0.5"),
+    ("physical.udp", "127.0.0.1:12345"),
+    ("ark.number", "1"),
+    ("ark.pubURI", "SSK@mock-ark,mock-ark-key,AQACAAE/ark"),
+    ("sig", "mock-signature"),
+];
+const PRIVATE: &[(&str, &str)] = &[
+    (
+        "ark.privURI",
+        "SSK@mock-ark-private,mock-ark-key,AQECAAE/ark",
+    ),
+    ("dsaPrivKey.x", "mock-private-key"),
+    ("ecdsa.P256.pri", "mock-ecdsa-private-key"),
+    ("clientNonce", "mock-nonce"),
+];
+
+/// Options of the mock node by name with their default, data type and short description
+const CONFIG: &[(&str, &str, &str, &str)] = &[
+    (
+        "node.name",
+        "Mock node",
+        "string",
+        "Name of the node shown to darknet peers",
+    ),
+    (
+        "node.opennet.enabled",
+        "true",
+        "boolean",
+        "Connect to strangers",
+    ),
+    (
+        "node.inputBandwidthLimit",
+        "65536",
+        "number",
+        "Input bandwidth limit in bytes per second",
+    ),
+    (
+        "node.outputBandwidthLimit",
+        "32768",
+        "number",
+        "Output bandwidth limit in bytes per second",
+    ),
+    (
+        "node.storeSize",
+        "1073741824",
+        "number",
+        "Size of the datastore in bytes",
+    ),
+    (
+        "fcp.port",
+        "9481",
+        "number",
+        "Port the node accepts FCP connections on",
+    ),
+];
+
+impl MockNodeState {
+    pub(super) fn get_node(&self, message: &Message) -> Message {
+        let fields = message.fields().field_set();
+        let mut node = FieldSet::new();
+        for (key, value) in NODE_REF {
+            node.set(key, *value);
+        }
+        if flag(&fields, "GiveOpennetRef") {
+            node.set("identity", "mock-node-opennet-identity");
+            node.remove("myName");
+        }
+        node.set("opennet", flag(&fields, "GiveOpennetRef").to_string());
+
+        if flag(&fields, "WithPrivate") {
+            for (key, value) in PRIVATE {
+                node.set(key, *value);
+            }
+        }
+        if flag(&fields, "WithVolatile") {
+            *node.subset_mut("volatile") = self.volatile();
+        }
+        if let Some(identifier) = identifier(message) {
+            node.set("Identifier", identifier);
+        }
+
+        Message::new(
+            MessageType::Node(NodeMessageType::NodeData),
+            (&node).into(),
+            None,
+        )
+    }
+
+    /// Statistics about the store, the mock node does not count its traffic
+    fn volatile(&self) -> FieldSet {
+        let store_size: usize = self.store.values().map(|e| e.data.len()).sum();
+
+        let mut volatile = FieldSet::new();
+        volatile.set("storeKeys", self.store.len().to_string());
+        volatile.set("storeSize", store_size.to_string());
+        for key in [
+            "storeHits",
+            "storeMisses",
+            "cachedKeys",
+            "cachedSize",
+            "cachedStoreHits",
+            "cachedStoreMisses",
+            "totalInputBytes",
+            "totalOutputBytes",
+            "totalInputRate",
+            "totalOutputRate",
+            "uptimeSeconds",
+        ] {
+            volatile.set(key, "0");
+        }
+        volatile.set("networkSizeEstimateSession", MOCK_NETWORK_SIZE.to_string());
+        volatile
+    }
+
+    pub(super) fn get_config(&self, message: &Message) -> Message {
+        let fields = message.fields().field_set();
+        let parts: Vec<_> = [
+            ("WithCurrent", "current"),
+            ("WithDefaults", "default"),
+            ("WithDataTypes", "dataType"),
+            ("WithExpertFlag", "expertFlag"),
+            ("WithForceWriteFlag", "forceWriteFlag"),
+            ("WithShortDescription", "shortDescription"),
+            ("WithLongDescription", "longDescription"),
+            ("WithSortOrder", "sortOrder"),
+        ]
+        .into_iter()
+        .filter(|(flag_key, _)| flag(&fields, flag_key))
+        .map(|(_, part)| part)
+        .collect();
+
+        self.config_data(message, &parts)
+    }
+
+    /// Options with values of the wrong type keep their old value, unknown ones are ignored
+    pub(super) fn modify_config(&mut self, message: &Message) -> Message {
+        for field in message.fields().iter() {
+            let Some((name, _, data_type, _)) = CONFIG.iter().find(|e| e.0 == field.key()) else {
+                continue;
+            };
+            let valid = match *data_type {
+                "number" => field.value().parse::<u64>().is_ok(),
+                "boolean" => field.value().parse::<bool>().is_ok(),
+                _ => true,
+            };
+            if valid {
+                self.config.insert((*name).into(), field.value().into());
+            }
+        }
+
+        self.config_data(message, &["current"])
+    }
+
+    fn config_data(&self, message: &Message, parts: &[&str]) -> Message {
+        let mut fields = Vec::new();
+        if let Some(identifier) = identifier(message) {
+            fields.push(Field::unvalidated("Identifier".into(), identifier));
+        }
+        for (sort_order, (name, default, data_type, description)) in CONFIG.iter().enumerate() {
+            for part in parts {
+                let value: Box<str> = match *part {
+                    "current" => self
+                        .config
+                        .get(*name)
+                        .cloned()
+                        .unwrap_or_else(|| (*default).into()),
+                    "default" => (*default).into(),
+                    "dataType" => (*data_type).into(),
+                    "expertFlag" => name.starts_with("fcp.").to_string().into(),
+                    "forceWriteFlag" => "false".into(),
+                    "shortDescription" | "longDescription" => (*description).into(),
+                    _ => sort_order.to_string().into(),
+                };
+                fields.push(Field::unvalidated(format!("{part}.{name}").into(), value));
+            }
+        }
+
+        Message::new(
+            MessageType::Node(NodeMessageType::ConfigData),
+            fields.into(),
+            None,
+        )
+    }
+
+    pub(super) fn probe_request(&self, message: &Message) -> Message {
+        let identifier = identifier(message).unwrap_or_default();
+        let fields = message.fields().field_set();
+        let Some(probe_type) = fields.get("Type") else {
+            return missing_field("Type", identifier);
+        };
+        if fields
+            .parse::<u8>("HopsToLive")
+            .ok()
+            .flatten()
+            .is_some_and(|e| e > MAX_HOPS_TO_LIVE)
+        {
+            return node_message(
+                NodeMessageType::ProbeRefused,
+                vec![("Identifier", identifier)],
+                None,
+            );
+        }
+
+        let (message_type, mut fields): (_, Vec<(&'static str, Box<str>)>) = match probe_type {
+            "BANDWIDTH" => (
+                NodeMessageType::ProbeBandwidth,
+                vec![("OutputBandwidth", "32.0".into())],
+            ),
+            "BUILD" => (NodeMessageType::ProbeBuild, vec![("Build", "1497".into())]),
+            "IDENTIFIER" => (
+                NodeMessageType::ProbeIdentifier,
+                vec![
+                    (
+                        "ProbeIdentifier",
+                        (rand::random::<u64>() % MOCK_NETWORK_SIZE)
+                            .to_string()
+                            .into(),
+                    ),
+                    ("UptimePercent", "100".into()),
+                ],
+            ),
+            "LINK_LENGTHS" => (
+                NodeMessageType::ProbeLinkLengths,
+                vec![("LinkLengths", "0.01;0.1;0.3".into())],
+            ),
+            "LOCATION" => (
+                NodeMessageType::ProbeLocation,
+                vec![("Location", rand::random::<f64>().to_string().into())],
+            ),
+            "STORE_SIZE" => (
+                NodeMessageType::ProbeStoreSize,
+                vec![("StoreSize", "1.0".into())],
+            ),
+            "UPTIME_48H" | "UPTIME_7D" => (
+                NodeMessageType::ProbeUptime,
+                vec![("UptimePercent", "99.5".into())],
+            ),
+            "REJECT_STATS" => (
+                NodeMessageType::ProbeRejectStats,
+                vec![
+                    ("CHKRequest", "0".into()),
+                    ("SSKRequest", "0".into()),
+                    ("CHKInsert", "0".into()),
+                    ("SSKInsert", "0".into()),
+                ],
+            ),
+            "OVERALL_BULK_OUTPUT_CAPACITY_USAGE" => (
+                NodeMessageType::ProbeOverallBulkOutputCapacityUsage,
+                vec![
+                    ("OutputBandwidthClass", "2".into()),
+                    ("OverallBulkOutputCapacityUsage", "0.25".into()),
+                ],
+            ),
+            _ => (
+                NodeMessageType::ProbeError,
+                vec![
+                    ("Type", "UNRECOGNIZED_TYPE".into()),
+                    ("Local", "true".into()),
+                ],
+            ),
+        };
+        fields.push(("Identifier", identifier));
+
+        node_message(message_type, fields, None)
+    }
+}
+
+fn flag(fields: &FieldSet, key: &str) -> bool {
+    fields.get_bool(key).ok().flatten().unwrap_or(false)
+}
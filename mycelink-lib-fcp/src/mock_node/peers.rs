@@ -0,0 +1,251 @@
+//! Darknet peers of the [super::MockNode], which never connect to anything
+
+use super::{identifier, missing_field, node_message, protocol_error, MockNodeState};
+use crate::model::field_set::FieldSet;
+use crate::model::message::Message;
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::peer_status::PeerStatus;
+use crate::model::protocol_error_code::ProtocolErrorCode;
+
+/// Settings of [crate::messages::modify_peer::ModifyPeerMessage] as they are reported in the
+/// `metadata.*` fields
+const PEER_FLAGS: &[(&str, &str)] = &[
+    ("AllowLocalAddresses", "allowLocalAddresses"),
+    ("IsDisabled", "isDisabled"),
+    ("IsListenOnly", "isListenOnly"),
+    ("IsBurstOnly", "isBurstOnly"),
+    ("IgnoreSourcePort", "ignoreSourcePort"),
+];
+
+pub(super) struct MockPeer {
+    node_ref: FieldSet,
+    metadata: FieldSet,
+    /// Base64 encoded, as sent in `PeerNote`
+    note: Box<str>,
+}
+
+impl MockPeer {
+    /// Matches the name, identity or any address of the peer
+    fn is(&self, node_identifier: &str) -> bool {
+        self.node_ref.get("identity") == Some(node_identifier)
+            || self.node_ref.get("myName") == Some(node_identifier)
+            || self
+                .node_ref
+                .get_list("physical.udp")
+                .unwrap_or_default()
+                .contains(&node_identifier)
+    }
+
+    fn status(&self) -> PeerStatus {
+        match self.metadata.get("isDisabled") {
+            Some("true") => PeerStatus::Disabled,
+            _ => PeerStatus::NeverConnected,
+        }
+    }
+
+    fn describe(&self, message: &Message, with_metadata: bool, with_volatile: bool) -> Message {
+        let mut fields = self.node_ref.clone();
+        if let Some(identifier) = identifier(message) {
+            fields.set("Identifier", identifier);
+        }
+        if with_metadata {
+            *fields.subset_mut("metadata") = self.metadata.clone();
+        }
+        if with_volatile {
+            fields.set("volatile.status", &self.status());
+        }
+
+        Message::new(
+            MessageType::Node(NodeMessageType::Peer),
+            (&fields).into(),
+            None,
+        )
+    }
+
+    fn note(&self, message: &Message) -> Message {
+        let mut fields = vec![
+            (
+                "NodeIdentifier",
+                self.node_ref.get("identity").unwrap_or_default().into(),
+            ),
+            ("PeerNoteType", "1".into()),
+            ("NoteText", self.note.clone()),
+        ];
+        if let Some(identifier) = identifier(message) {
+            fields.push(("Identifier", identifier));
+        }
+        node_message(NodeMessageType::PeerNote, fields, None)
+    }
+}
+
+impl MockNodeState {
+    pub(super) fn list_peers(&self, message: &Message) -> Vec<Message> {
+        let fields = message.fields().field_set();
+        let with_metadata = flag(&fields, "WithMetadata");
+        let with_volatile = flag(&fields, "WithVolatile");
+
+        let mut responses: Vec<_> = self
+            .peers
+            .iter()
+            .map(|e| e.describe(message, with_metadata, with_volatile))
+            .collect();
+        responses.push(node_message(
+            NodeMessageType::EndListPeers,
+            identifier(message)
+                .map(|e| ("Identifier", e))
+                .into_iter()
+                .collect(),
+            None,
+        ));
+        responses
+    }
+
+    pub(super) fn list_peer(&self, message: &Message) -> Message {
+        let fields = message.fields().field_set();
+        let with_metadata = flag(&fields, "WithMetadata");
+        let with_volatile = flag(&fields, "WithVolatile");
+
+        match self.peer(message) {
+            Ok(index) => self.peers[index].describe(message, with_metadata, with_volatile),
+            Err(error) => error,
+        }
+    }
+
+    pub(super) fn add_peer(&mut self, message: &Message) -> Message {
+        let identifier = identifier(message).unwrap_or_default();
+        let mut node_ref = message.fields().field_set();
+        if node_ref.get("File").is_some() || node_ref.get("URL").is_some() {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::NotSupported),
+                "Mock node only adds peers from inline references",
+                Some(identifier),
+                false,
+            );
+        }
+        for key in ["Identifier", "Trust", "Visibility"] {
+            node_ref.remove(key);
+        }
+
+        let Some(identity) = node_ref.get("identity") else {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::RefParseError),
+                "Reference has no identity",
+                Some(identifier),
+                false,
+            );
+        };
+        if self.peers.iter().any(|e| e.is(identity)) {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::DuplicatePeerRef),
+                "Node already has a peer with this reference",
+                Some(identifier),
+                false,
+            );
+        }
+
+        let mut metadata = FieldSet::new();
+        for (_, key) in PEER_FLAGS {
+            metadata.set(key, "false");
+        }
+        self.peers.push(MockPeer {
+            node_ref,
+            metadata,
+            note: "".into(),
+        });
+        self.peers[self.peers.len() - 1].describe(message, true, true)
+    }
+
+    pub(super) fn modify_peer(&mut self, message: &Message) -> Message {
+        let index = match self.peer(message) {
+            Ok(index) => index,
+            Err(error) => return error,
+        };
+        let fields = message.fields().field_set();
+        let peer = &mut self.peers[index];
+        for (field, key) in PEER_FLAGS {
+            if let Some(value) = fields.get(field) {
+                peer.metadata.set(key, value);
+            }
+        }
+
+        peer.describe(message, true, true)
+    }
+
+    pub(super) fn remove_peer(&mut self, message: &Message) -> Message {
+        let index = match self.peer(message) {
+            Ok(index) => index,
+            Err(error) => return error,
+        };
+        let peer = self.peers.remove(index);
+
+        let mut fields = vec![(
+            "NodeIdentifier",
+            peer.node_ref.get("identity").unwrap_or_default().into(),
+        )];
+        if let Some(identifier) = identifier(message) {
+            fields.push(("Identifier", identifier));
+        }
+        node_message(NodeMessageType::PeerRemoved, fields, None)
+    }
+
+    pub(super) fn list_peer_notes(&self, message: &Message) -> Vec<Message> {
+        let index = match self.peer(message) {
+            Ok(index) => index,
+            Err(error) => return vec![error],
+        };
+        let peer = &self.peers[index];
+
+        let mut fields = vec![(
+            "NodeIdentifier",
+            peer.node_ref.get("identity").unwrap_or_default().into(),
+        )];
+        if let Some(identifier) = identifier(message) {
+            fields.push(("Identifier", identifier));
+        }
+        vec![
+            peer.note(message),
+            node_message(NodeMessageType::EndListPeerNotes, fields, None),
+        ]
+    }
+
+    pub(super) fn modify_peer_note(&mut self, message: &Message) -> Message {
+        let index = match self.peer(message) {
+            Ok(index) => index,
+            Err(error) => return error,
+        };
+        let Some(note) = message.fields().get("NoteText") else {
+            return missing_field("NoteText", identifier(message).unwrap_or_default());
+        };
+
+        self.peers[index].note = note.value().into();
+        self.peers[index].note(message)
+    }
+
+    /// Index of the peer with the `NodeIdentifier` of `message`, `UnknownNodeIdentifier` if there
+    /// is none
+    fn peer(&self, message: &Message) -> Result<usize, Message> {
+        let Some(node_identifier) = message.fields().get("NodeIdentifier").map(|e| e.value())
+        else {
+            return Err(missing_field(
+                "NodeIdentifier",
+                identifier(message).unwrap_or_default(),
+            ));
+        };
+
+        self.peers
+            .iter()
+            .position(|e| e.is(node_identifier))
+            .ok_or_else(|| {
+                let mut fields = vec![("NodeIdentifier", node_identifier.into())];
+                if let Some(identifier) = identifier(message) {
+                    fields.push(("Identifier", identifier));
+                }
+                node_message(NodeMessageType::UnknownNodeIdentifier, fields, None)
+            })
+    }
+}
+
+fn flag(fields: &FieldSet, key: &str) -> bool {
+    fields.get_bool(key).ok().flatten().unwrap_or(false)
+}
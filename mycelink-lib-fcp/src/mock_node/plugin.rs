@@ -0,0 +1,58 @@
+//! Plugins loaded by the [super::MockNode]
+
+use super::{identifier, node_message, protocol_error, MockNodeState, MOCK_ECHO_PLUGIN};
+use crate::model::field_set::FieldSet;
+use crate::model::message::{Message, MessagePayload};
+use crate::model::message_type_identifier::MessageType;
+use crate::model::message_type_identifier::NodeMessageType;
+use crate::model::protocol_error_code::ProtocolErrorCode;
+
+impl MockNodeState {
+    /// The echo plugin replies with the params and data it received, unless the param `Refuse`
+    /// is set
+    pub(super) fn fcp_plugin_message(&self, message: Message) -> Message {
+        let identifier = identifier(&message).unwrap_or_default();
+        let fields = message.fields().field_set();
+        let plugin_name = fields.get("PluginName").unwrap_or_default();
+        if plugin_name != MOCK_ECHO_PLUGIN {
+            return protocol_error(
+                u32::from(ProtocolErrorCode::NoSuchPlugin),
+                &format!("Plugin {plugin_name} is not loaded"),
+                Some(identifier),
+                false,
+            );
+        }
+
+        let params = fields.subset("Param").cloned().unwrap_or_default();
+        if params.get("Refuse").is_some() {
+            return node_message(
+                NodeMessageType::FCPPluginReply,
+                vec![
+                    ("Identifier", identifier),
+                    ("PluginName", MOCK_ECHO_PLUGIN.into()),
+                    ("Success", "false".into()),
+                    ("ErrorCode", "Refused".into()),
+                    ("ErrorMessage", "Asked to refuse".into()),
+                ],
+                None,
+            );
+        }
+
+        let mut reply = FieldSet::new();
+        reply.set("Identifier", identifier);
+        reply.set("PluginName", MOCK_ECHO_PLUGIN);
+        if !params.is_empty() {
+            *reply.subset_mut("Replies") = params;
+        }
+        let payload = message.payload().map(|e| MessagePayload {
+            data: e.data,
+            data_len_identifier: "DataLength".into(),
+        });
+
+        Message::new(
+            MessageType::Node(NodeMessageType::FCPPluginReply),
+            (&reply).into(),
+            payload,
+        )
+    }
+}
@@ -0,0 +1,55 @@
+use crate::decode_error::DecodeError;
+use std::str::FromStr;
+
+/// Version of the splitting and encryption an insert uses, the same data and settings inserted
+/// with the same mode always end up under the same CHK
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CompatibilityMode {
+    Compat1250Exact,
+    Compat1250,
+    Compat1251,
+    Compat1255,
+    Compat1416,
+    Compat1468,
+    /// Whatever the node considers current, which changes with node updates
+    Current,
+}
+
+impl From<&CompatibilityMode> for &str {
+    fn from(value: &CompatibilityMode) -> Self {
+        match value {
+            CompatibilityMode::Compat1250Exact => "COMPAT_1250_EXACT",
+            CompatibilityMode::Compat1250 => "COMPAT_1250",
+            CompatibilityMode::Compat1251 => "COMPAT_1251",
+            CompatibilityMode::Compat1255 => "COMPAT_1255",
+            CompatibilityMode::Compat1416 => "COMPAT_1416",
+            CompatibilityMode::Compat1468 => "COMPAT_1468",
+            CompatibilityMode::Current => "COMPAT_CURRENT",
+        }
+    }
+}
+
+impl From<&CompatibilityMode> for Box<str> {
+    fn from(value: &CompatibilityMode) -> Self {
+        Into::<&str>::into(value).into()
+    }
+}
+
+impl FromStr for CompatibilityMode {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "COMPAT_1250_EXACT" => Ok(CompatibilityMode::Compat1250Exact),
+            "COMPAT_1250" => Ok(CompatibilityMode::Compat1250),
+            "COMPAT_1251" => Ok(CompatibilityMode::Compat1251),
+            "COMPAT_1255" => Ok(CompatibilityMode::Compat1255),
+            "COMPAT_1416" => Ok(CompatibilityMode::Compat1416),
+            "COMPAT_1468" => Ok(CompatibilityMode::Compat1468),
+            "COMPAT_CURRENT" => Ok(CompatibilityMode::Current),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown compatibility mode {s}").into(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use crate::decode_error::DecodeError;
+use crate::model::fields::{Field, Fields};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Separates the levels of a path, e.g. `Files.0.Name`
+pub const PATH_SEPARATOR: char = '.';
+/// Separates the items of a list value, e.g. `Addresses=127.0.0.1;::1`
+pub const LIST_SEPARATOR: char = ';';
+
+/// Fields whose dotted keys are read as a tree, like Fred's `SimpleFieldSet`
+///
+/// `Files.0.Name=a` is the value `Name` in the subset `0` of the subset `Files`. Numbered subsets
+/// starting at `0` form a list, see [FieldSet::list].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FieldSet {
+    values: Vec<(Box<str>, Box<str>)>,
+    subsets: Vec<(Box<str>, FieldSet)>,
+}
+
+impl FieldSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.subsets.is_empty()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&str> {
+        let (parent, key) = match path.rsplit_once(PATH_SEPARATOR) {
+            Some((parent, key)) => (self.subset(parent)?, key),
+            None => (self, path),
+        };
+
+        parent
+            .values
+            .iter()
+            .find(|(e, _)| e.as_ref() == key)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    pub fn get_or_err(&self, path: &str) -> Result<&str, DecodeError> {
+        self.get(path)
+            .ok_or_else(|| DecodeError::MissingField(path.into()))
+    }
+
+    /// Sets the value at `path`, creating all subsets on the way
+    pub fn set(&mut self, path: &str, value: impl Into<Box<str>>) {
+        let (parent, key) = match path.rsplit_once(PATH_SEPARATOR) {
+            Some((parent, key)) => (self.subset_mut(parent), key),
+            None => (self, path),
+        };
+
+        let value = value.into();
+        match parent.values.iter_mut().find(|(e, _)| e.as_ref() == key) {
+            Some((_, old)) => *old = value,
+            None => parent.values.push((key.into(), value)),
+        }
+    }
+
+    pub fn subset(&self, path: &str) -> Option<&FieldSet> {
+        path.split(PATH_SEPARATOR).try_fold(self, |current, name| {
+            current
+                .subsets
+                .iter()
+                .find(|(e, _)| e.as_ref() == name)
+                .map(|(_, subset)| subset)
+        })
+    }
+
+    /// The subset at `path`, which is created if it does not exist yet
+    pub fn subset_mut(&mut self, path: &str) -> &mut FieldSet {
+        path.split(PATH_SEPARATOR).fold(self, |current, name| {
+            let index = match current.subsets.iter().position(|(e, _)| e.as_ref() == name) {
+                Some(index) => index,
+                None => {
+                    current.subsets.push((name.into(), FieldSet::new()));
+                    current.subsets.len() - 1
+                }
+            };
+            &mut current.subsets[index].1
+        })
+    }
+
+    /// Removes the value at `path` and returns it
+    pub fn remove(&mut self, path: &str) -> Option<Box<str>> {
+        let (parent, key) = match path.rsplit_once(PATH_SEPARATOR) {
+            Some((parent, key)) => (self.subset_mut_existing(parent)?, key),
+            None => (self, path),
+        };
+        let index = parent.values.iter().position(|(e, _)| e.as_ref() == key)?;
+        Some(parent.values.remove(index).1)
+    }
+
+    /// Removes the subset at `path` and returns it
+    pub fn remove_subset(&mut self, path: &str) -> Option<FieldSet> {
+        let (parent, name) = match path.rsplit_once(PATH_SEPARATOR) {
+            Some((parent, name)) => (self.subset_mut_existing(parent)?, name),
+            None => (self, path),
+        };
+        let index = parent
+            .subsets
+            .iter()
+            .position(|(e, _)| e.as_ref() == name)?;
+        Some(parent.subsets.remove(index).1)
+    }
+
+    fn subset_mut_existing(&mut self, path: &str) -> Option<&mut FieldSet> {
+        path.split(PATH_SEPARATOR).try_fold(self, |current, name| {
+            current
+                .subsets
+                .iter_mut()
+                .find(|(e, _)| e.as_ref() == name)
+                .map(|(_, subset)| subset)
+        })
+    }
+
+    /// The values directly in this set, without the ones of subsets
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(key, value)| (&**key, &**value))
+    }
+
+    /// The subsets directly in this set by their name
+    pub fn subsets(&self) -> impl Iterator<Item = (&str, &FieldSet)> {
+        self.subsets.iter().map(|(name, subset)| (&**name, subset))
+    }
+
+    /// The subsets `0`, `1`, ... of the subset at `path`, up to the first missing index
+    pub fn list<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a FieldSet> {
+        let parent = self.subset(path);
+        (0..).map_while(move |index: usize| parent?.subset(&index.to_string()))
+    }
+
+    /// Appends `item` to the list at `path` and returns its index
+    pub fn push(&mut self, path: &str, item: FieldSet) -> usize {
+        let parent = self.subset_mut(path);
+        let index = (0..)
+            .find(|index: &usize| parent.subset(&index.to_string()).is_none())
+            .unwrap_or_default();
+        *parent.subset_mut(&index.to_string()) = item;
+        index
+    }
+
+    /// Parses the value at `path`, [None] if it is missing
+    pub fn parse<T: FromStr>(&self, path: &str) -> Result<Option<T>, DecodeError> {
+        self.get(path)
+            .map(|value| parse_value(path, value))
+            .transpose()
+    }
+
+    pub fn parse_or_err<T: FromStr>(&self, path: &str) -> Result<T, DecodeError> {
+        parse_value(path, self.get_or_err(path)?)
+    }
+
+    /// Reads booleans like Fred, which also accepts `yes` and `no` in any case
+    pub fn get_bool(&self, path: &str) -> Result<Option<bool>, DecodeError> {
+        let Some(value) = self.get(path) else {
+            return Ok(None);
+        };
+
+        match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" => Ok(Some(true)),
+            "false" | "no" => Ok(Some(false)),
+            _ => Err(DecodeError::ParseError(
+                format!("Field {path} is no bool: '{value}'").into(),
+            )),
+        }
+    }
+
+    /// Splits the value at `path` at [LIST_SEPARATOR], an empty value is an empty list
+    pub fn get_list(&self, path: &str) -> Option<Vec<&str>> {
+        self.get(path).map(|value| match value.is_empty() {
+            true => Vec::new(),
+            false => value.split(LIST_SEPARATOR).collect(),
+        })
+    }
+
+    pub fn parse_list<T: FromStr>(&self, path: &str) -> Result<Option<Vec<T>>, DecodeError> {
+        self.get_list(path)
+            .map(|items| items.into_iter().map(|e| parse_value(path, e)).collect())
+            .transpose()
+    }
+
+    pub fn set_list<T: Display>(&mut self, path: &str, items: impl IntoIterator<Item = T>) {
+        let value = items
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(&LIST_SEPARATOR.to_string());
+        self.set(path, value);
+    }
+
+    fn flatten(&self, prefix: &str, into: &mut Vec<Field>) {
+        for (key, value) in &self.values {
+            into.push(Field::unvalidated(
+                format!("{prefix}{key}").into(),
+                value.clone(),
+            ));
+        }
+        for (name, subset) in &self.subsets {
+            subset.flatten(&format!("{prefix}{name}{PATH_SEPARATOR}"), into);
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(path: &str, value: &str) -> Result<T, DecodeError> {
+    value.parse().map_err(|_| {
+        DecodeError::ParseError(
+            format!(
+                "Field {path} is no {}: '{value}'",
+                std::any::type_name::<T>()
+            )
+            .into(),
+        )
+    })
+}
+
+impl From<&Fields> for FieldSet {
+    fn from(value: &Fields) -> Self {
+        let mut field_set = FieldSet::new();
+        for field in value.iter() {
+            field_set.set(field.key(), field.value());
+        }
+        field_set
+    }
+}
+
+impl From<&FieldSet> for Fields {
+    fn from(value: &FieldSet) -> Self {
+        let mut fields = Vec::new();
+        value.flatten("", &mut fields);
+        fields.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decode_error::DecodeError;
+    use crate::model::field_set::FieldSet;
+    use crate::model::fields::{Field, Fields};
+
+    fn manifest() -> FieldSet {
+        let fields: Fields = vec![
+            Field::unvalidated("Identifier".into(), "dir".into()),
+            Field::unvalidated("Files.0.Name".into(), "index.html".into()),
+            Field::unvalidated("Files.0.DataLength".into(), "42".into()),
+            Field::unvalidated("Files.1.Name".into(), "style.css".into()),
+            Field::unvalidated("Files.1.DataLength".into(), "7".into()),
+            Field::unvalidated("Files.3.Name".into(), "unreachable".into()),
+        ]
+        .into();
+        (&fields).into()
+    }
+
+    #[test]
+    fn test_nested_get() {
+        let manifest = manifest();
+        assert_eq!(manifest.get("Identifier"), Some("dir"));
+        assert_eq!(manifest.get("Files.1.Name"), Some("style.css"));
+        assert_eq!(manifest.get("Files.2.Name"), None);
+        assert_eq!(
+            manifest.subset("Files.0").unwrap().get("Name"),
+            Some("index.html")
+        );
+        assert!(matches!(
+            manifest.get_or_err("Files.0.Metadata"),
+            Err(DecodeError::MissingField(_))
+        ));
+    }
+
+    #[test]
+    fn test_list() {
+        let mut manifest = manifest();
+        let names: Vec<_> = manifest
+            .list("Files")
+            .map(|e| e.get("Name").unwrap())
+            .collect();
+        assert_eq!(names, ["index.html", "style.css"]);
+
+        let mut file = FieldSet::new();
+        file.set("Name", "script.js");
+        assert_eq!(manifest.push("Files", file), 2);
+        assert_eq!(manifest.list("Files").count(), 4);
+        assert_eq!(manifest.list("Peers").count(), 0);
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let mut fields = manifest();
+        fields.set("Opennet", "Yes");
+        fields.set("Files.0.Checked", "maybe");
+        fields.set_list("Addresses", ["127.0.0.1", "::1"]);
+        fields.set_list("Ports", [9481, 8888]);
+
+        assert_eq!(
+            fields.parse_or_err::<u64>("Files.0.DataLength").unwrap(),
+            42
+        );
+        assert_eq!(fields.parse::<u64>("Files.0.Missing").unwrap(), None);
+        assert!(matches!(
+            fields.parse::<u64>("Files.0.Name"),
+            Err(DecodeError::ParseError(_))
+        ));
+        assert_eq!(fields.get_bool("Opennet").unwrap(), Some(true));
+        assert!(fields.get_bool("Files.0.Checked").is_err());
+        assert_eq!(fields.get_list("Addresses").unwrap(), ["127.0.0.1", "::1"]);
+        assert_eq!(
+            fields.parse_list::<u16>("Ports").unwrap().unwrap(),
+            [9481, 8888]
+        );
+    }
+
+    #[test]
+    fn test_flatten_roundtrip() {
+        let mut field_set = FieldSet::new();
+        field_set.set("Peer.Version", "Fred,0.7,1.0,1497");
+        field_set.set("Peer.ark.number", "3");
+        field_set.set("Peer.Version", "Fred,0.7,1.0,1498");
+
+        let fields: Fields = (&field_set).into();
+        let keys: Vec<_> = fields.iter().map(|e| (e.key(), e.value())).collect();
+        assert_eq!(
+            keys,
+            [
+                ("Peer.Version", "Fred,0.7,1.0,1498"),
+                ("Peer.ark.number", "3")
+            ]
+        );
+        assert_eq!(FieldSet::from(&fields), field_set);
+
+        assert_eq!(field_set.remove("Peer.ark.number").as_deref(), Some("3"));
+        assert_eq!(field_set.remove("Peer.ark.number"), None);
+        assert!(field_set.remove_subset("Peer").is_some());
+        assert!(field_set.is_empty());
+    }
+}
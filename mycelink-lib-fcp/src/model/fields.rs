@@ -1,23 +1,24 @@
 use crate::decode_error::DecodeError;
-use crate::decode_error::DecodeError::{ParseError, UnexpectedEOF};
-use crate::peekable_reader::Peeker;
+use crate::encode_error::EncodeError;
+use crate::model::field_set::FieldSet;
 use std::borrow::Cow;
-use std::ops::Deref;
 use std::slice::Iter;
-use tokio::io::AsyncRead;
 
 pub const END_MESSAGE_LIT: &str = "EndMessage";
 pub const DATA_LIT: &str = "Data";
 
-const PAYLOAD_LENGTH_HINT_KEYS: &[&str] = &["DataLength"];
+pub(crate) const PAYLOAD_LENGTH_HINT_KEYS: &[&str] = &["DataLength"];
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Characters that would end the line of a field early, `=` is forbidden in keys as well
+const FORBIDDEN_CHARACTERS: &[char] = &['\n', '\r', '\0'];
+
+#[derive(Clone, Eq, PartialEq)]
 pub struct Fields {
     fields: Vec<Field>,
 }
 
 impl Fields {
-    pub fn iter(&self) -> Iter<Field> {
+    pub fn iter(&self) -> Iter<'_, Field> {
         self.fields.iter()
     }
 
@@ -29,6 +30,11 @@ impl Fields {
         self.get(key).ok_or(DecodeError::MissingField(key.into()))
     }
 
+    /// Reads the dotted keys as a tree, e.g. for `Files.0.Name`
+    pub fn field_set(&self) -> FieldSet {
+        self.into()
+    }
+
     pub fn get_payload_size_hint(&self) -> Result<&Field, DecodeError> {
         let mut iter = self
             .iter()
@@ -39,47 +45,42 @@ impl Fields {
             None => Err(DecodeError::MissingField("PAYLOAD LENGTH HINT".into())),
             Some(hint) => {
                 if iter.next().is_some() {
-                    todo!("How to handle if multiple hints match?")
+                    Err(DecodeError::ProtocolBreak(
+                        "Message contains multiple payload length hints".into(),
+                    ))
                 } else {
                     Ok(hint)
                 }
             }
         }
     }
-
-    pub async fn decode(
-        peeker: &mut Peeker<'_, impl AsyncRead + Unpin>,
-    ) -> Result<Self, DecodeError> {
-        let mut fields: Vec<Field> = Vec::new();
-
-        let mut line = peeker.next_contentful_line().await?.ok_or(UnexpectedEOF)?;
-
-        while Field::is_field(line.deref()) {
-            fields.push(line.as_ref().try_into()?);
-            line = peeker.next_contentful_line().await?.ok_or(UnexpectedEOF)?;
-        }
-
-        if line.as_ref() != END_MESSAGE_LIT && line.as_ref() != DATA_LIT {
-            return Err(ParseError(
-                format!("'{line}' neither indicates the end of a Fields nor is a field itself.")
-                    .into(),
-            ));
-        }
-
-        Ok(fields.into())
-    }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Field {
     key: Cow<'static, str>,
     value: Box<str>,
 }
 
 impl Field {
-    pub fn new(key: Cow<'static, str>, value: Box<str>) -> Self {
+    /// Fails if the field could not be written to the FCP stream as a single `key=value` line
+    pub fn new(key: Cow<'static, str>, value: Box<str>) -> Result<Self, EncodeError> {
+        let field = Self { key, value };
+        field.validate()?;
+        Ok(field)
+    }
+
+    /// Skips the validation of [Field::new] for fields of messages built by this crate, which are
+    /// validated when the message is encoded
+    pub(crate) fn unvalidated(key: Cow<'static, str>, value: Box<str>) -> Self {
         Self { key, value }
     }
+
+    pub fn validate(&self) -> Result<(), EncodeError> {
+        validate_key(&self.key)?;
+        validate_value(&self.key, &self.value)
+    }
+
     pub fn key(&self) -> &str {
         &self.key
     }
@@ -114,3 +115,62 @@ impl TryFrom<&str> for Field {
         })
     }
 }
+
+pub(crate) fn validate_key(key: &str) -> Result<(), EncodeError> {
+    if key.is_empty() || key.contains('=') || key.contains(FORBIDDEN_CHARACTERS) {
+        return Err(EncodeError::InvalidKey(key.into()));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_value(key: &str, value: &str) -> Result<(), EncodeError> {
+    if value.contains(FORBIDDEN_CHARACTERS) {
+        return Err(EncodeError::InvalidValue {
+            key: key.into(),
+            value: value.into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encode_error::EncodeError;
+    use crate::model::fields::Field;
+
+    #[test]
+    fn test_reject_injection() {
+        assert!(Field::new("TargetFilename".into(), "a=b.txt".into()).is_ok());
+        assert!(matches!(
+            Field::new(
+                "TargetFilename".into(),
+                "a\nEndMessage\nRemoveRequest".into()
+            ),
+            Err(EncodeError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            Field::new("Target\rFilename".into(), "a".into()),
+            Err(EncodeError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            Field::new("Target=Filename".into(), "a".into()),
+            Err(EncodeError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            Field::new("".into(), "a".into()),
+            Err(EncodeError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_value_is_redacted() {
+        let insert_key = "SSK@GB3wuHmtxN2wLc7g4y1ZVydkK6sOT-DuOsUo-eHK35w,\
+            c63EzO7uBEN0piUbHPkMcJYW7i7cOvG42CM3YDduXDs,AQECAAE/chat";
+        let err = Field::new("URI".into(), format!("{insert_key}\n").into()).unwrap_err();
+
+        for formatted in [err.to_string(), format!("{err:?}")] {
+            assert!(!formatted.contains("GB3wuHmtxN2wLc7g4y1ZVydkK6sOT"));
+            assert!(formatted.contains("SSK@<redacted>"));
+        }
+    }
+}
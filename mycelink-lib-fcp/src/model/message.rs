@@ -1,12 +1,15 @@
 use crate::decode_error::DecodeError;
 
-use crate::model::fields::{Fields, DATA_LIT, END_MESSAGE_LIT};
+use crate::encode_error::EncodeError;
+use crate::message_reader::MessageReader;
+use crate::model::fields::{validate_key, Fields, DATA_LIT, END_MESSAGE_LIT};
 use crate::model::message_type_identifier::MessageType;
-use crate::peekable_reader::{PeekableReader, Peeker};
-use std::ops::Deref;
-use tokio::io::AsyncRead;
+use crate::model::redaction::TracedMessage;
+use bytes::{Buf, BufMut};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Formatting it masks secrets, see [crate::model::redaction]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Message {
     message_type: MessageType,
     fields: Fields,
@@ -14,40 +17,92 @@ pub struct Message {
 }
 
 impl Message {
-    pub fn encode(&self) -> Vec<u8> {
-        let mut builder = String::new();
-
-        builder.push_str(self.message_type.name());
-        builder.push('\n');
+    /// Fails without encoding anything if a field could inject further fields or messages
+    pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut buf = Vec::new();
+        self.encode_into(&mut buf)?;
+        Ok(buf)
+    }
 
-        for field in self.fields.iter() {
-            builder.push_str(field.key());
-            builder.push('=');
-            builder.push_str(field.value());
-            builder.push('\n');
+    pub fn encode_into(&self, dst: &mut impl BufMut) -> Result<(), EncodeError> {
+        self.encode_header_into(dst)?;
+        if let Some(payload) = &self.payload {
+            dst.put_slice(&payload.data);
         }
+        Ok(())
+    }
+
+    /// Encodes everything but the payload, which follows the header unchanged on the stream
+    pub fn encode_header(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut buf = Vec::new();
+        self.encode_header_into(&mut buf)?;
+        Ok(buf)
+    }
 
+    pub fn encode_header_into(&self, dst: &mut impl BufMut) -> Result<(), EncodeError> {
         match &self.payload {
             None => {
-                builder.push_str(END_MESSAGE_LIT);
-                builder.push('\n');
-                builder.into_bytes()
+                self.validate()?;
+                self.encode_fields_into(dst);
+                dst.put_slice(END_MESSAGE_LIT.as_bytes());
+                dst.put_u8(b'\n');
+                Ok(())
             }
-            Some(payload) => {
-                builder.push_str(payload.data_len_identifier.deref());
-                builder.push('=');
-                builder.push_str(payload.data.len().to_string().as_str());
-                builder.push('\n');
+            Some(payload) => self.encode_payload_header_into(
+                &payload.data_len_identifier,
+                payload.data.len() as u64,
+                dst,
+            ),
+        }
+    }
 
-                builder.push_str(DATA_LIT);
-                builder.push('\n');
+    /// Writes the header and then the payload straight from the message, with a single vectored
+    /// write if `dst` supports them
+    pub async fn write_to(&self, dst: &mut (impl AsyncWrite + Unpin)) -> Result<(), EncodeError> {
+        let header = self.encode_header()?;
+        let payload = self.payload.as_ref().map(|e| &*e.data).unwrap_or_default();
+        dst.write_all_buf(&mut header.as_slice().chain(payload))
+            .await?;
+        Ok(())
+    }
 
-                let mut buf = builder.into_bytes();
+    /// Encodes the message up to where a payload of `len` bytes would start
+    pub fn encode_payload_header_into(
+        &self,
+        data_len_identifier: &str,
+        len: u64,
+        dst: &mut impl BufMut,
+    ) -> Result<(), EncodeError> {
+        self.validate()?;
+        validate_key(data_len_identifier)?;
+        self.encode_fields_into(dst);
 
-                buf.extend_from_slice(payload.data.deref());
+        dst.put_slice(data_len_identifier.as_bytes());
+        dst.put_u8(b'=');
+        dst.put_slice(len.to_string().as_bytes());
+        dst.put_u8(b'\n');
 
-                buf
-            }
+        dst.put_slice(DATA_LIT.as_bytes());
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+
+    /// Checks that every field can be written as a single `key=value` line
+    pub fn validate(&self) -> Result<(), EncodeError> {
+        self.fields.iter().try_for_each(|e| e.validate())
+    }
+
+    fn encode_fields_into(&self, dst: &mut impl BufMut) {
+        dst.put_slice(self.message_type.name().as_bytes());
+        dst.put_u8(b'\n');
+
+        // Decoded messages keep their length hint as field, it is written from the payload instead
+        let length_hint = self.payload.as_ref().map(|e| &*e.data_len_identifier);
+        for field in self.fields.iter().filter(|e| Some(e.key()) != length_hint) {
+            dst.put_slice(field.key().as_bytes());
+            dst.put_u8(b'=');
+            dst.put_slice(field.value().as_bytes());
+            dst.put_u8(b'\n');
         }
     }
 }
@@ -62,7 +117,7 @@ impl Message {
     }
 
     pub fn message_type(&self) -> MessageType {
-        self.message_type
+        self.message_type.clone()
     }
 
     pub fn fields(&self) -> &Fields {
@@ -73,57 +128,34 @@ impl Message {
         self.payload
     }
 
-    pub async fn decode(
-        encoded: &mut PeekableReader<impl AsyncRead + Unpin>,
-    ) -> Result<Self, DecodeError> {
-        let mut peeker = Peeker::new(encoded);
-        let message_type = MessageType::decode(&mut peeker).await?;
-        let fields = Fields::decode(&mut peeker).await?;
-
-        if peeker
-            .current_line()
-            .await?
-            .map(|e| e.deref() == END_MESSAGE_LIT)
-            .unwrap_or(false)
-        {
-            let stats = peeker.into();
-            encoded.advance_to_peeker_stats(stats);
-
-            return Ok(Self {
-                message_type,
-                fields,
-                payload: None,
-            });
-        } else if peeker
-            .current_line()
-            .await?
-            .map(|e| e.deref() == DATA_LIT)
-            .unwrap_or(false)
-        {
-            let stats = peeker.into();
-            encoded.advance_to_peeker_stats(stats);
-
-            let size_hint = fields.get_payload_size_hint()?;
-            let size_hint_key = size_hint.key().into();
-
-            let mut payload = vec![0; size_hint.value().parse()?];
-            encoded.read_exact(payload.as_mut_slice()).await?;
-
-            return Ok(Self {
-                message_type,
-                fields,
-                payload: Some(MessagePayload {
-                    data: payload.into(),
-                    data_len_identifier: size_hint_key,
-                }),
-            });
+    pub fn payload_ref(&self) -> Option<&MessagePayload> {
+        self.payload.as_ref()
+    }
+
+    /// Formats the message with private keys and payload bytes only if `unredacted` is set
+    pub fn traced(&self, unredacted: bool) -> TracedMessage<'_> {
+        TracedMessage::new(self, unredacted)
+    }
+
+    pub fn with_payload(self, payload: MessagePayload) -> Self {
+        Self {
+            payload: Some(payload),
+            ..self
         }
+    }
 
-        todo!("Cannot recover from failed message parse yet")
+    /// Decodes the next message
+    ///
+    /// If the message is malformed, it is skipped up to and including the next `EndMessage` or
+    /// `Data` boundary, so decoding can continue with the following message.
+    pub async fn decode(
+        encoded: &mut MessageReader<impl AsyncRead + Unpin>,
+    ) -> Result<Self, DecodeError> {
+        encoded.read_message().await
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct MessagePayload {
     pub data: Box<[u8]>,
     pub data_len_identifier: Box<str>,
@@ -138,3 +170,130 @@ impl<T: Into<Message>> FCPEncodable for T {
         self.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::decode_error::DecodeError;
+    use crate::encode_error::EncodeError;
+    use crate::message_reader::MessageReader;
+    use crate::model::fields::Field;
+    use crate::model::message::{Message, MessagePayload};
+    use crate::model::message_type_identifier::ClientMessageType::ClientPut;
+    use crate::model::message_type_identifier::MessageType::{Client, Node, Unknown};
+    use crate::model::message_type_identifier::NodeMessageType::NodeHello;
+    use tokio_test::io::Builder;
+
+    const NODE_HELLO: &[u8] = b"NodeHello\nFCPVersion=2.0\nEndMessage\n";
+
+    #[tokio::test]
+    async fn test_unknown_message_type_keeps_name() {
+        let encoded = b"FutureMessage\nIdentifier=abc\nEndMessage\n";
+        let mock = Builder::new().read(encoded).build();
+        let mut reader = MessageReader::new(mock);
+
+        let message = Message::decode(&mut reader).await.unwrap();
+        assert_eq!(message.message_type(), Unknown("FutureMessage".into()));
+        assert_eq!(message.fields().get("Identifier").unwrap().value(), "abc");
+        assert_eq!(message.encode().unwrap(), encoded);
+    }
+
+    #[test]
+    fn test_encode_rejects_injected_fields() {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), "abc".into()),
+            Field::unvalidated(
+                "TargetFilename".into(),
+                "a.txt\nEndMessage\nRemoveRequest\nIdentifier=other".into(),
+            ),
+        ];
+        let message = Message::new(Client(ClientPut), fields.into(), None);
+
+        assert!(matches!(
+            message.encode(),
+            Err(EncodeError::InvalidValue { key, .. }) if &*key == "TargetFilename"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_to_matches_encode() {
+        let fields = vec![Field::unvalidated("Identifier".into(), "abc".into())];
+        let message =
+            Message::new(Client(ClientPut), fields.into(), None).with_payload(MessagePayload {
+                data: b"Hello World".as_slice().into(),
+                data_len_identifier: "DataLength".into(),
+            });
+
+        let mut written = Vec::new();
+        message.write_to(&mut written).await.unwrap();
+        assert_eq!(written, message.encode().unwrap());
+        assert!(written.ends_with(b"\nData\nHello World"));
+        assert_eq!(
+            message.encode_header().unwrap(),
+            &written[..written.len() - b"Hello World".len()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resync_after_malformed_fields() {
+        let mock = Builder::new()
+            .read(b"NodeHello\nFCPVersion=2.0\nnot a field\nEndMessage\n")
+            .read(NODE_HELLO)
+            .build();
+        let mut reader = MessageReader::new(mock);
+
+        let err = Message::decode(&mut reader).await.unwrap_err();
+        assert!(matches!(err, DecodeError::ParseError(_)));
+
+        let message = Message::decode(&mut reader).await.unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+    }
+
+    #[tokio::test]
+    async fn test_resync_skips_payload() {
+        let mock = Builder::new()
+            .read(b"AllData\nDataLength=5\nnot a field\nData\nab\ncd")
+            .read(NODE_HELLO)
+            .build();
+        let mut reader = MessageReader::new(mock);
+
+        assert!(Message::decode(&mut reader).await.is_err());
+
+        let message = Message::decode(&mut reader).await.unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+    }
+
+    #[tokio::test]
+    async fn test_non_utf8_field_is_error() {
+        let mock = Builder::new()
+            .read(b"NodeHello\nNode=\xff\xfe\nEndMessage\n")
+            .read(NODE_HELLO)
+            .build();
+        let mut reader = MessageReader::new(mock);
+
+        let err = Message::decode(&mut reader).await.unwrap_err();
+        assert!(matches!(err, DecodeError::Utf8Error(_)));
+
+        let message = Message::decode(&mut reader).await.unwrap();
+        assert_eq!(message.message_type(), Node(NodeHello));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_payload_hints_is_error() {
+        let mock = Builder::new()
+            .read(b"AllData\nDataLength=1\nDataLength=2\nData\na")
+            .build();
+        let mut reader = MessageReader::new(mock);
+
+        let err = Message::decode(&mut reader).await.unwrap_err();
+        assert!(matches!(err, DecodeError::ProtocolBreak(_)));
+    }
+
+    #[tokio::test]
+    async fn test_eof_is_not_recoverable() {
+        let mock = Builder::new().read(b"NodeHello\nFCPVersion=2.0\n").build();
+        let mut reader = MessageReader::new(mock);
+
+        let err = Message::decode(&mut reader).await.unwrap_err();
+        assert!(!err.is_recoverable());
+    }
+}
@@ -1,18 +1,24 @@
 use crate::decode_error::DecodeError;
-use crate::decode_error::DecodeError::UnexpectedEOF;
 use crate::model::message_type_identifier::ClientMessageType::{
-    ClientGet, ClientHello, ClientPut, GenerateSSK, ListPeer, SubscribeUSK, TestDDARequest,
-    TestDDAResponse,
+    AddPeer, ClientGet, ClientHello, ClientPut, ClientPutComplexDir, ClientPutDiskDir,
+    FCPPluginMessage, GenerateSSK, GetConfig, GetNode, GetRequestStatus, ListPeer, ListPeerNotes,
+    ListPeers, ListPersistentRequests, ModifyConfig, ModifyPeer, ModifyPeerNote,
+    ModifyPersistentRequest, ProbeRequest, RemovePeer, RemoveRequest, SubscribeUSK, TestDDARequest,
+    TestDDAResponse, UnsubscribeUSK, WatchGlobal,
 };
-use crate::model::message_type_identifier::MessageType::{Client, Node};
+use crate::model::message_type_identifier::MessageType::{Client, Node, Unknown};
 use crate::model::message_type_identifier::NodeMessageType::{
-    AllData, DataFound, GetFailed, NodeHello, ProtocolError, PutFailed, PutSuccessful, SSKKeypair,
-    SubscribedUSK, SubscribedUSKRoundFinished, SubscribedUSKSendingToNetwork, SubscribedUSKUpdate,
-    TestDDAComplete, TestDDAReply, URIGenerated,
+    AllData, CloseConnectionDuplicateClientName, ConfigData, DataFound, EndListPeerNotes,
+    EndListPeers, EndListPersistentRequests, ExpectedDataLength, ExpectedHashes, ExpectedMIME,
+    FCPPluginReply, FinishedCompression, GetFailed, NodeData, NodeHello, Peer, PeerNote,
+    PeerRemoved, PersistentGet, PersistentPut, PersistentRequestModified, PersistentRequestRemoved,
+    ProbeBandwidth, ProbeBuild, ProbeError, ProbeIdentifier, ProbeLinkLengths, ProbeLocation,
+    ProbeOverallBulkOutputCapacityUsage, ProbeRefused, ProbeRejectStats, ProbeStoreSize,
+    ProbeUptime, ProtocolError, PutFailed, PutSuccessful, SSKKeypair, SendingToNetwork,
+    SimpleProgress, StartedCompression, SubscribedUSK, SubscribedUSKRoundFinished,
+    SubscribedUSKSendingToNetwork, SubscribedUSKUpdate, TestDDAComplete, TestDDAReply,
+    URIGenerated, UnknownNodeIdentifier,
 };
-use crate::peekable_reader::Peeker;
-use std::ops::Deref;
-use tokio::io::AsyncRead;
 
 pub const CLIENT_MESSAGE_TYPES: &[ClientMessageType] = &[
     ClientHello,
@@ -23,6 +29,25 @@ pub const CLIENT_MESSAGE_TYPES: &[ClientMessageType] = &[
     TestDDARequest,
     TestDDAResponse,
     SubscribeUSK,
+    RemoveRequest,
+    ListPersistentRequests,
+    WatchGlobal,
+    GetRequestStatus,
+    ModifyPersistentRequest,
+    UnsubscribeUSK,
+    ClientPutComplexDir,
+    ClientPutDiskDir,
+    ListPeers,
+    AddPeer,
+    ModifyPeer,
+    RemovePeer,
+    ListPeerNotes,
+    ModifyPeerNote,
+    GetNode,
+    GetConfig,
+    ModifyConfig,
+    ProbeRequest,
+    FCPPluginMessage,
 ];
 pub const NODE_MESSAGE_TYPES: &[NodeMessageType] = &[
     NodeHello,
@@ -40,9 +65,42 @@ pub const NODE_MESSAGE_TYPES: &[NodeMessageType] = &[
     SubscribedUSKUpdate,
     SubscribedUSKSendingToNetwork,
     SubscribedUSKRoundFinished,
+    PersistentRequestRemoved,
+    CloseConnectionDuplicateClientName,
+    SimpleProgress,
+    StartedCompression,
+    FinishedCompression,
+    ExpectedHashes,
+    ExpectedMIME,
+    ExpectedDataLength,
+    SendingToNetwork,
+    PersistentGet,
+    PersistentPut,
+    PersistentRequestModified,
+    EndListPersistentRequests,
+    Peer,
+    EndListPeers,
+    PeerRemoved,
+    PeerNote,
+    EndListPeerNotes,
+    UnknownNodeIdentifier,
+    NodeData,
+    ConfigData,
+    ProbeBandwidth,
+    ProbeBuild,
+    ProbeIdentifier,
+    ProbeLinkLengths,
+    ProbeLocation,
+    ProbeStoreSize,
+    ProbeUptime,
+    ProbeRejectStats,
+    ProbeOverallBulkOutputCapacityUsage,
+    ProbeError,
+    ProbeRefused,
+    FCPPluginReply,
 ];
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ClientMessageType {
     ClientHello,
     ClientGet,
@@ -52,6 +110,25 @@ pub enum ClientMessageType {
     TestDDARequest,
     TestDDAResponse,
     SubscribeUSK,
+    RemoveRequest,
+    ListPersistentRequests,
+    WatchGlobal,
+    GetRequestStatus,
+    ModifyPersistentRequest,
+    UnsubscribeUSK,
+    ClientPutComplexDir,
+    ClientPutDiskDir,
+    ListPeers,
+    AddPeer,
+    ModifyPeer,
+    RemovePeer,
+    ListPeerNotes,
+    ModifyPeerNote,
+    GetNode,
+    GetConfig,
+    ModifyConfig,
+    ProbeRequest,
+    FCPPluginMessage,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -71,12 +148,47 @@ pub enum NodeMessageType {
     SubscribedUSKUpdate,
     SubscribedUSKSendingToNetwork,
     SubscribedUSKRoundFinished,
+    PersistentRequestRemoved,
+    CloseConnectionDuplicateClientName,
+    SimpleProgress,
+    StartedCompression,
+    FinishedCompression,
+    ExpectedHashes,
+    ExpectedMIME,
+    ExpectedDataLength,
+    SendingToNetwork,
+    PersistentGet,
+    PersistentPut,
+    PersistentRequestModified,
+    EndListPersistentRequests,
+    Peer,
+    EndListPeers,
+    PeerRemoved,
+    PeerNote,
+    EndListPeerNotes,
+    UnknownNodeIdentifier,
+    NodeData,
+    ConfigData,
+    ProbeBandwidth,
+    ProbeBuild,
+    ProbeIdentifier,
+    ProbeLinkLengths,
+    ProbeLocation,
+    ProbeStoreSize,
+    ProbeUptime,
+    ProbeRejectStats,
+    ProbeOverallBulkOutputCapacityUsage,
+    ProbeError,
+    ProbeRefused,
+    FCPPluginReply,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum MessageType {
     Client(ClientMessageType),
     Node(NodeMessageType),
+    /// A message this library does not know yet, keeping the name it was sent with
+    Unknown(Box<str>),
 }
 
 impl NodeMessageType {
@@ -98,6 +210,39 @@ impl NodeMessageType {
             SubscribedUSKUpdate => "SubscribedUSKUpdate",
             SubscribedUSKSendingToNetwork => "SubscribedUSKSendingToNetwork",
             SubscribedUSKRoundFinished => "SubscribedUSKRoundFinished",
+            PersistentRequestRemoved => "PersistentRequestRemoved",
+            CloseConnectionDuplicateClientName => "CloseConnectionDuplicateClientName",
+            SimpleProgress => "SimpleProgress",
+            StartedCompression => "StartedCompression",
+            FinishedCompression => "FinishedCompression",
+            ExpectedHashes => "ExpectedHashes",
+            ExpectedMIME => "ExpectedMIME",
+            ExpectedDataLength => "ExpectedDataLength",
+            SendingToNetwork => "SendingToNetwork",
+            PersistentGet => "PersistentGet",
+            PersistentPut => "PersistentPut",
+            PersistentRequestModified => "PersistentRequestModified",
+            EndListPersistentRequests => "EndListPersistentRequests",
+            Peer => "Peer",
+            EndListPeers => "EndListPeers",
+            PeerRemoved => "PeerRemoved",
+            PeerNote => "PeerNote",
+            EndListPeerNotes => "EndListPeerNotes",
+            UnknownNodeIdentifier => "UnknownNodeIdentifier",
+            NodeData => "NodeData",
+            ConfigData => "ConfigData",
+            ProbeBandwidth => "ProbeBandwidth",
+            ProbeBuild => "ProbeBuild",
+            ProbeIdentifier => "ProbeIdentifier",
+            ProbeLinkLengths => "ProbeLinkLengths",
+            ProbeLocation => "ProbeLocation",
+            ProbeStoreSize => "ProbeStoreSize",
+            ProbeUptime => "ProbeUptime",
+            ProbeRejectStats => "ProbeRejectStats",
+            ProbeOverallBulkOutputCapacityUsage => "ProbeOverallBulkOutputCapacityUsage",
+            ProbeError => "ProbeError",
+            ProbeRefused => "ProbeRefused",
+            FCPPluginReply => "FCPPluginReply",
         }
     }
 }
@@ -114,22 +259,42 @@ impl ClientMessageType {
             TestDDARequest => "TestDDARequest",
             TestDDAResponse => "TestDDAResponse",
             SubscribeUSK => "SubscribeUSK",
+            RemoveRequest => "RemoveRequest",
+            ListPersistentRequests => "ListPersistentRequests",
+            WatchGlobal => "WatchGlobal",
+            GetRequestStatus => "GetRequestStatus",
+            ModifyPersistentRequest => "ModifyPersistentRequest",
+            UnsubscribeUSK => "UnsubscribeUSK",
+            ClientPutComplexDir => "ClientPutComplexDir",
+            ClientPutDiskDir => "ClientPutDiskDir",
+            ListPeers => "ListPeers",
+            AddPeer => "AddPeer",
+            ModifyPeer => "ModifyPeer",
+            RemovePeer => "RemovePeer",
+            ListPeerNotes => "ListPeerNotes",
+            ModifyPeerNote => "ModifyPeerNote",
+            GetNode => "GetNode",
+            GetConfig => "GetConfig",
+            ModifyConfig => "ModifyConfig",
+            ProbeRequest => "ProbeRequest",
+            FCPPluginMessage => "FCPPluginMessage",
         }
     }
 }
 
 impl MessageType {
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self {
             Client(inner) => inner.name(),
             Node(inner) => inner.name(),
+            Unknown(name) => name,
         }
     }
 
     pub fn is_specific_node_message(&self, matches: NodeMessageType) -> bool {
         match self {
-            Client(_) => false,
             Node(inner) => inner == &matches,
+            Client(_) | Unknown(_) => false,
         }
     }
 
@@ -140,7 +305,7 @@ impl MessageType {
         if !self.is_specific_node_message(matches) {
             Err(DecodeError::ExpectedDifferentMessageType {
                 expected: Node(matches),
-                got: *self,
+                got: self.clone(),
             })
         } else {
             Ok(())
@@ -181,18 +346,3 @@ impl TryFrom<&str> for MessageType {
             .or(ClientMessageType::try_from(value).map(MessageType::Client))
     }
 }
-
-impl MessageType {
-    pub async fn decode(
-        peeker: &mut Peeker<'_, impl AsyncRead + Unpin>,
-    ) -> Result<Self, DecodeError>
-    where
-        Self: Sized,
-    {
-        let peeked_identifier = peeker.next_contentful_line().await?.ok_or(UnexpectedEOF)?;
-
-        let res = MessageType::try_from(peeked_identifier.deref())?;
-
-        Ok(res)
-    }
-}
@@ -1,11 +1,19 @@
+pub mod compatibility_mode;
 pub mod connection_identifier;
 pub mod content_type;
 pub mod fcp_version;
+pub mod field_set;
 pub mod fields;
 pub mod message;
 pub mod message_type_identifier;
+pub mod node_ref;
+pub mod peer_status;
+pub mod peer_trust;
+pub mod peer_visibility;
 pub mod persistence;
 pub mod priority_class;
+pub mod protocol_error_code;
+pub mod redaction;
 pub mod return_type;
 pub mod unique_identifier;
 pub mod upload_type;
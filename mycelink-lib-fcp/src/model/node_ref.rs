@@ -0,0 +1,140 @@
+use crate::decode_error::DecodeError;
+use crate::model::field_set::FieldSet;
+use crate::model::fields::Fields;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Ends a noderef written as text
+pub const END_LIT: &str = "End";
+
+/// The reference of a node that others need to peer with it, as exchanged between darknet peers
+///
+/// All fields are kept as they are, as the signature of the reference covers them. Written as
+/// text it is a `key=value` line per field followed by `End`, like the references shown by Fred.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeRef {
+    fields: FieldSet,
+}
+
+impl NodeRef {
+    /// Fails if the fields lack the `identity` every reference has
+    pub fn new(fields: FieldSet) -> Result<Self, DecodeError> {
+        fields.get_or_err("identity")?;
+        Ok(Self { fields })
+    }
+
+    pub fn fields(&self) -> &FieldSet {
+        &self.fields
+    }
+
+    pub fn identity(&self) -> &str {
+        self.fields.get("identity").unwrap_or_default()
+    }
+
+    /// Name chosen by the operator of the node, darknet references only
+    pub fn name(&self) -> Option<&str> {
+        self.fields.get("myName")
+    }
+
+    pub fn opennet(&self) -> bool {
+        self.fields
+            .get_bool("opennet")
+            .ok()
+            .flatten()
+            .unwrap_or(false)
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.fields.get("version")
+    }
+
+    /// Addresses of the node as `host:port`
+    pub fn physical_udp(&self) -> Vec<&str> {
+        self.fields.get_list("physical.udp").unwrap_or_default()
+    }
+
+    pub fn location(&self) -> Result<Option<f64>, DecodeError> {
+        self.fields.parse("location")
+    }
+
+    /// Edition of the address resolution key the node publishes its addresses under
+    pub fn ark_number(&self) -> Result<Option<i64>, DecodeError> {
+        self.fields.parse("ark.number")
+    }
+}
+
+impl FromStr for NodeRef {
+    type Err = DecodeError;
+
+    /// Reads the text form, leading whitespace and empty lines are skipped
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = FieldSet::new();
+        for line in s.lines().map(str::trim).filter(|e| !e.is_empty()) {
+            if line == END_LIT {
+                return NodeRef::new(fields);
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(DecodeError::ParseError(
+                    format!("Noderef line is no field: '{line}'").into(),
+                ));
+            };
+            fields.set(key, value);
+        }
+
+        Err(DecodeError::ParseError(
+            format!("Noderef does not end with '{END_LIT}'").into(),
+        ))
+    }
+}
+
+impl Display for NodeRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields: Fields = (&self.fields).into();
+        for field in fields.iter() {
+            writeln!(f, "{}={}", field.key(), field.value())?;
+        }
+        writeln!(f, "{END_LIT}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::node_ref::NodeRef;
+
+    const NODE_REF: &str = "\
+        identity=t6mO1ePGQ4d5qUCx0kcGyvGdo1M9KcuWsA9c2vJnxAs\n\
+        myName=Alice\n\
+        opennet=false\n\
+        version=Fred,0.7,1.0,1497\n\
+        location=0.4152\n\
+        sig=3045022100a1\n\
+        physical.udp=192.0.2.7:12345;[2001:db8::7]:12345\n\
+        ark.number=12\n\
+        ark.pubURI=SSK@abc,def,AQACAAE/ark\n\
+        End\n";
+
+    #[test]
+    fn test_text_roundtrip() {
+        let node_ref: NodeRef = NODE_REF.parse().unwrap();
+        assert_eq!(node_ref.name(), Some("Alice"));
+        assert!(!node_ref.opennet());
+        assert_eq!(
+            node_ref.physical_udp(),
+            ["192.0.2.7:12345", "[2001:db8::7]:12345"]
+        );
+        assert_eq!(node_ref.location().unwrap(), Some(0.4152));
+        assert_eq!(node_ref.ark_number().unwrap(), Some(12));
+
+        assert_eq!(node_ref.to_string(), NODE_REF);
+        assert_eq!(node_ref.to_string().parse::<NodeRef>().unwrap(), node_ref);
+    }
+
+    #[test]
+    fn test_reject_incomplete() {
+        assert!("identity=abc\n".parse::<NodeRef>().is_err());
+        assert!("myName=Alice\nEnd\n".parse::<NodeRef>().is_err());
+        assert!("identity=abc\nnot a field\nEnd\n"
+            .parse::<NodeRef>()
+            .is_err());
+    }
+}
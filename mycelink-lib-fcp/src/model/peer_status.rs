@@ -0,0 +1,79 @@
+/// Connection state of a peer as reported in `volatile.status`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PeerStatus {
+    Connected,
+    /// Connected, but requests are not routed to it for a while after it rejected some
+    BackedOff,
+    /// Connected, but the versions of the nodes are too far apart to route requests
+    TooNew,
+    TooOld,
+    Disconnected,
+    NeverConnected,
+    Disabled,
+    Bursting,
+    Listening,
+    ListenOnly,
+    ClockProblem,
+    ConnectionError,
+    Disconnecting,
+    RoutingDisabled,
+    NoLoadStats,
+    /// A status added to Fred after this library
+    Unknown(Box<str>),
+}
+
+impl PeerStatus {
+    /// Whether requests can be exchanged with the peer right now
+    pub fn is_connected(&self) -> bool {
+        matches!(
+            self,
+            PeerStatus::Connected | PeerStatus::BackedOff | PeerStatus::NoLoadStats
+        )
+    }
+}
+
+impl From<&PeerStatus> for Box<str> {
+    fn from(value: &PeerStatus) -> Self {
+        match value {
+            PeerStatus::Connected => "CONNECTED".into(),
+            PeerStatus::BackedOff => "BACKED OFF".into(),
+            PeerStatus::TooNew => "TOO NEW".into(),
+            PeerStatus::TooOld => "TOO OLD".into(),
+            PeerStatus::Disconnected => "DISCONNECTED".into(),
+            PeerStatus::NeverConnected => "NEVER CONNECTED".into(),
+            PeerStatus::Disabled => "DISABLED".into(),
+            PeerStatus::Bursting => "BURSTING".into(),
+            PeerStatus::Listening => "LISTENING".into(),
+            PeerStatus::ListenOnly => "LISTEN ONLY".into(),
+            PeerStatus::ClockProblem => "CLOCK PROBLEM".into(),
+            PeerStatus::ConnectionError => "CONNECTION ERROR".into(),
+            PeerStatus::Disconnecting => "DISCONNECTING".into(),
+            PeerStatus::RoutingDisabled => "ROUTING DISABLED".into(),
+            PeerStatus::NoLoadStats => "NO LOAD STATS".into(),
+            PeerStatus::Unknown(inner) => inner.clone(),
+        }
+    }
+}
+
+impl From<&str> for PeerStatus {
+    fn from(value: &str) -> Self {
+        match value {
+            "CONNECTED" => PeerStatus::Connected,
+            "BACKED OFF" => PeerStatus::BackedOff,
+            "TOO NEW" => PeerStatus::TooNew,
+            "TOO OLD" => PeerStatus::TooOld,
+            "DISCONNECTED" => PeerStatus::Disconnected,
+            "NEVER CONNECTED" => PeerStatus::NeverConnected,
+            "DISABLED" => PeerStatus::Disabled,
+            "BURSTING" => PeerStatus::Bursting,
+            "LISTENING" => PeerStatus::Listening,
+            "LISTEN ONLY" => PeerStatus::ListenOnly,
+            "CLOCK PROBLEM" => PeerStatus::ClockProblem,
+            "CONNECTION ERROR" => PeerStatus::ConnectionError,
+            "DISCONNECTING" => PeerStatus::Disconnecting,
+            "ROUTING DISABLED" => PeerStatus::RoutingDisabled,
+            "NO LOAD STATS" => PeerStatus::NoLoadStats,
+            _ => PeerStatus::Unknown(value.into()),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::decode_error::DecodeError;
+use std::str::FromStr;
+
+/// How much a darknet peer is trusted, which decides e.g. what it may see of the node
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PeerTrust {
+    Low,
+    Normal,
+    High,
+}
+
+impl From<&PeerTrust> for &str {
+    fn from(value: &PeerTrust) -> Self {
+        match value {
+            PeerTrust::Low => "LOW",
+            PeerTrust::Normal => "NORMAL",
+            PeerTrust::High => "HIGH",
+        }
+    }
+}
+
+impl From<&PeerTrust> for Box<str> {
+    fn from(value: &PeerTrust) -> Self {
+        Into::<&str>::into(value).into()
+    }
+}
+
+impl FromStr for PeerTrust {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LOW" => Ok(PeerTrust::Low),
+            "NORMAL" => Ok(PeerTrust::Normal),
+            "HIGH" => Ok(PeerTrust::High),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown peer trust {s}").into(),
+            )),
+        }
+    }
+}
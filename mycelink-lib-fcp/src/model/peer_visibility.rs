@@ -0,0 +1,41 @@
+use crate::decode_error::DecodeError;
+use std::str::FromStr;
+
+/// What the other peers of the node learn about a darknet peer
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PeerVisibility {
+    No,
+    NameOnly,
+    Yes,
+}
+
+impl From<&PeerVisibility> for &str {
+    fn from(value: &PeerVisibility) -> Self {
+        match value {
+            PeerVisibility::No => "NO",
+            PeerVisibility::NameOnly => "NAME_ONLY",
+            PeerVisibility::Yes => "YES",
+        }
+    }
+}
+
+impl From<&PeerVisibility> for Box<str> {
+    fn from(value: &PeerVisibility) -> Self {
+        Into::<&str>::into(value).into()
+    }
+}
+
+impl FromStr for PeerVisibility {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NO" => Ok(PeerVisibility::No),
+            "NAME_ONLY" => Ok(PeerVisibility::NameOnly),
+            "YES" => Ok(PeerVisibility::Yes),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown peer visibility {s}").into(),
+            )),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use crate::decode_error::DecodeError;
+use std::str::FromStr;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Persistence {
     Connection,
@@ -20,3 +23,18 @@ impl From<&Persistence> for Box<str> {
         Into::<&str>::into(value).into()
     }
 }
+
+impl FromStr for Persistence {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "connection" => Ok(Persistence::Connection),
+            "reboot" => Ok(Persistence::Reboot),
+            "forever" => Ok(Persistence::Forever),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown persistence {s}").into(),
+            )),
+        }
+    }
+}
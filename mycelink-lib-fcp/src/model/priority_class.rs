@@ -1,4 +1,6 @@
+use crate::decode_error::DecodeError;
 use std::cmp::Ordering;
+use std::str::FromStr;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum PriorityClass {
@@ -25,6 +27,33 @@ impl From<&PriorityClass> for u8 {
     }
 }
 
+impl TryFrom<u8> for PriorityClass {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PriorityClass::Maximum),
+            1 => Ok(PriorityClass::VeryHigh),
+            2 => Ok(PriorityClass::High),
+            3 => Ok(PriorityClass::Medium),
+            4 => Ok(PriorityClass::Low),
+            5 => Ok(PriorityClass::VeryLow),
+            6 => Ok(PriorityClass::Pause),
+            _ => Err(DecodeError::ParseError(
+                format!("Unknown priority class {value}").into(),
+            )),
+        }
+    }
+}
+
+impl FromStr for PriorityClass {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<u8>()?.try_into()
+    }
+}
+
 impl From<&PriorityClass> for Box<str> {
     fn from(value: &PriorityClass) -> Self {
         u8::from(value).to_string().into_boxed_str()
@@ -0,0 +1,110 @@
+/// Why the node refused a message, sent as `Code` of a `ProtocolError`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProtocolErrorCode {
+    ClientHelloMustBeFirst,
+    /// A second `ClientHello` on the same connection
+    NoLateClientHellos,
+    MessageParseError,
+    URIParseError,
+    MissingField,
+    ErrorParsingNumber,
+    InvalidMessage,
+    InvalidField,
+    FileNotFound,
+    DiskTargetExists,
+    SameDirectoryExpected,
+    CouldNotCreateFile,
+    CouldNotWriteFile,
+    CouldNotRenameFile,
+    /// No request with the `Identifier` of the message is running
+    NoSuchIdentifier,
+    NotSupported,
+    InternalError,
+    ShuttingDown,
+    /// No peer has the `NodeIdentifier` of the message
+    NoSuchNodeIdentifier,
+    URLParseError,
+    RefParseError,
+    FileParseError,
+    NotAFileError,
+    AccessDenied,
+    /// The directory was not allowed with `TestDDARequest` before
+    DirectDiskAccessDenied,
+    CouldNotReadFile,
+    RefSignatureInvalid,
+    CannotPeerWithSelf,
+    /// The node already has a peer with this reference
+    DuplicatePeerRef,
+    OpennetDisabled,
+    DarknetOnly,
+    /// No plugin with the `PluginName` of the message is loaded
+    NoSuchPlugin,
+    /// The node does not keep requests over restarts
+    PersistenceDisabled,
+    TooManyFilesInInsert,
+    BadMIMEType,
+    /// A code added to Fred after this library
+    Unknown(u32),
+}
+
+/// Codes as defined by Fred
+const CODES: &[(ProtocolErrorCode, u32)] = &[
+    (ProtocolErrorCode::ClientHelloMustBeFirst, 1),
+    (ProtocolErrorCode::NoLateClientHellos, 2),
+    (ProtocolErrorCode::MessageParseError, 3),
+    (ProtocolErrorCode::URIParseError, 4),
+    (ProtocolErrorCode::MissingField, 5),
+    (ProtocolErrorCode::ErrorParsingNumber, 6),
+    (ProtocolErrorCode::InvalidMessage, 7),
+    (ProtocolErrorCode::InvalidField, 8),
+    (ProtocolErrorCode::FileNotFound, 9),
+    (ProtocolErrorCode::DiskTargetExists, 10),
+    (ProtocolErrorCode::SameDirectoryExpected, 11),
+    (ProtocolErrorCode::CouldNotCreateFile, 12),
+    (ProtocolErrorCode::CouldNotWriteFile, 13),
+    (ProtocolErrorCode::CouldNotRenameFile, 14),
+    (ProtocolErrorCode::NoSuchIdentifier, 15),
+    (ProtocolErrorCode::NotSupported, 16),
+    (ProtocolErrorCode::InternalError, 17),
+    (ProtocolErrorCode::ShuttingDown, 18),
+    (ProtocolErrorCode::NoSuchNodeIdentifier, 19),
+    (ProtocolErrorCode::URLParseError, 20),
+    (ProtocolErrorCode::RefParseError, 21),
+    (ProtocolErrorCode::FileParseError, 22),
+    (ProtocolErrorCode::NotAFileError, 23),
+    (ProtocolErrorCode::AccessDenied, 24),
+    (ProtocolErrorCode::DirectDiskAccessDenied, 25),
+    (ProtocolErrorCode::CouldNotReadFile, 26),
+    (ProtocolErrorCode::RefSignatureInvalid, 27),
+    (ProtocolErrorCode::CannotPeerWithSelf, 28),
+    (ProtocolErrorCode::DuplicatePeerRef, 29),
+    (ProtocolErrorCode::OpennetDisabled, 30),
+    (ProtocolErrorCode::DarknetOnly, 31),
+    (ProtocolErrorCode::NoSuchPlugin, 32),
+    (ProtocolErrorCode::PersistenceDisabled, 33),
+    (ProtocolErrorCode::TooManyFilesInInsert, 34),
+    (ProtocolErrorCode::BadMIMEType, 35),
+];
+
+impl From<u32> for ProtocolErrorCode {
+    fn from(value: u32) -> Self {
+        CODES
+            .iter()
+            .find(|(_, code)| *code == value)
+            .map(|(e, _)| *e)
+            .unwrap_or(ProtocolErrorCode::Unknown(value))
+    }
+}
+
+impl From<ProtocolErrorCode> for u32 {
+    fn from(value: ProtocolErrorCode) -> Self {
+        match value {
+            ProtocolErrorCode::Unknown(code) => code,
+            known => CODES
+                .iter()
+                .find(|(e, _)| *e == known)
+                .map(|(_, code)| *code)
+                .expect("All known codes are listed"),
+        }
+    }
+}
@@ -0,0 +1,383 @@
+//! Masks secrets when messages are formatted for logs
+//!
+//! Insert keys, KSK names and payload bytes are replaced with [REDACTED], unless a message is
+//! formatted with [Message::traced] to debug the protocol itself. [URI]s and [UploadType]s are
+//! always formatted redacted.
+
+use crate::model::fields::{Field, Fields};
+use crate::model::message::{Message, MessagePayload};
+use crate::model::upload_type::UploadType;
+use crate::model::uri::{is_insert_key, KeyMaterial, URI};
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+
+pub const REDACTED: &str = "<redacted>";
+
+/// Suffixes of keys whose values are private whatever they contain, e.g. `ark.privURI`
+const PRIVATE_KEY_SUFFIXES: &[&str] = &["InsertURI", "privURI", "PrivKey.x", ".pri"];
+
+/// Key types a URI in a value can start with, the name of a KSK is its secret
+const KEY_TYPES: &[&str] = &["KSK@", "SSK@", "USK@"];
+
+/// The value of the field `key` with every private key it contains masked
+pub fn redact_value<'a>(key: &str, value: &'a str) -> Cow<'a, str> {
+    if PRIVATE_KEY_SUFFIXES.iter().any(|e| key.ends_with(e)) {
+        return REDACTED.into();
+    }
+
+    let mut cursor = 0;
+    while let Some((start, key_type)) = KEY_TYPES
+        .iter()
+        .filter_map(|key_type| {
+            let start = value[cursor..].find(key_type)?;
+            Some((cursor + start, *key_type))
+        })
+        .min()
+    {
+        cursor = start + key_type.len();
+        let key = value[cursor..].split('/').next().unwrap_or_default();
+        if key_type == "KSK@" || is_insert_key(key) {
+            // The rest of the value is masked as well, it could contain more of the key
+            return format!("{}{key_type}{REDACTED}", &value[..start]).into();
+        }
+    }
+
+    value.into()
+}
+
+/// Formats a [Message], with secrets unless they are redacted
+pub struct TracedMessage<'a> {
+    message: &'a Message,
+    unredacted: bool,
+}
+
+struct TracedFields<'a> {
+    fields: &'a Fields,
+    unredacted: bool,
+}
+
+struct TracedField<'a> {
+    field: &'a Field,
+    unredacted: bool,
+}
+
+struct TracedPayload<'a> {
+    payload: &'a MessagePayload,
+    unredacted: bool,
+}
+
+impl<'a> TracedMessage<'a> {
+    pub(crate) fn new(message: &'a Message, unredacted: bool) -> Self {
+        Self {
+            message,
+            unredacted,
+        }
+    }
+}
+
+impl TracedField<'_> {
+    fn value(&self) -> Cow<'_, str> {
+        match self.unredacted {
+            true => self.field.value().into(),
+            false => redact_value(self.field.key(), self.field.value()),
+        }
+    }
+}
+
+impl Debug for TracedMessage<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Message")
+            .field("message_type", &self.message.message_type())
+            .field(
+                "fields",
+                &TracedFields {
+                    fields: self.message.fields(),
+                    unredacted: self.unredacted,
+                },
+            )
+            .field(
+                "payload",
+                &self.message.payload_ref().map(|payload| TracedPayload {
+                    payload,
+                    unredacted: self.unredacted,
+                }),
+            )
+            .finish()
+    }
+}
+
+/// One line with the name and fields of the message, payloads are only counted
+impl Display for TracedMessage<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{", self.message.message_type().name())?;
+        for (index, field) in self.message.fields().iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            let field = TracedField {
+                field,
+                unredacted: self.unredacted,
+            };
+            write!(f, "{separator}{}={}", field.field.key(), field.value())?;
+        }
+        write!(f, " }}")?;
+
+        if let Some(payload) = self.message.payload_ref() {
+            write!(f, " with {} bytes of data", payload.data.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for TracedFields<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<_> = self
+            .fields
+            .iter()
+            .map(|field| TracedField {
+                field,
+                unredacted: self.unredacted,
+            })
+            .collect();
+        f.debug_struct("Fields").field("fields", &fields).finish()
+    }
+}
+
+impl Debug for TracedField<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("key", &self.field.key())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+impl Debug for TracedPayload<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("MessagePayload");
+        match self.unredacted {
+            true => debug.field("data", &self.payload.data),
+            false => debug.field(
+                "data",
+                &format_args!("{REDACTED} {} bytes", self.payload.data.len()),
+            ),
+        };
+        debug
+            .field("data_len_identifier", &self.payload.data_len_identifier)
+            .finish()
+    }
+}
+
+impl Debug for Fields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        TracedFields {
+            fields: self,
+            unredacted: false,
+        }
+        .fmt(f)
+    }
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        TracedField {
+            field: self,
+            unredacted: false,
+        }
+        .fmt(f)
+    }
+}
+
+impl Debug for MessagePayload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        TracedPayload {
+            payload: self,
+            unredacted: false,
+        }
+        .fmt(f)
+    }
+}
+
+impl Debug for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.traced(false), f)
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.traced(false), f)
+    }
+}
+
+impl Debug for KeyMaterial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("KeyMaterial");
+        match self.is_insert_key() {
+            true => debug
+                .field("routing_key", &format_args!("{REDACTED}"))
+                .field("crypto_key", &format_args!("{REDACTED}")),
+            false => debug
+                .field("routing_key", &self.routing_key)
+                .field("crypto_key", &self.crypto_key),
+        };
+        debug.field("extra", &self.extra).finish()
+    }
+}
+
+impl Debug for URI {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            URI::CHK { key, meta_strings } => f
+                .debug_struct("CHK")
+                .field("key", key)
+                .field("meta_strings", meta_strings)
+                .finish(),
+            URI::SSK {
+                key,
+                docname,
+                meta_strings,
+            } => f
+                .debug_struct("SSK")
+                .field("key", key)
+                .field("docname", docname)
+                .field("meta_strings", meta_strings)
+                .finish(),
+            URI::USK {
+                key,
+                docname,
+                edition,
+                meta_strings,
+            } => f
+                .debug_struct("USK")
+                .field("key", key)
+                .field("docname", docname)
+                .field("edition", edition)
+                .field("meta_strings", meta_strings)
+                .finish(),
+            URI::KSK { meta_strings, .. } => f
+                .debug_struct("KSK")
+                .field("keyword", &format_args!("{REDACTED}"))
+                .field("meta_strings", meta_strings)
+                .finish(),
+        }
+    }
+}
+
+impl Debug for UploadType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadType::Direct { data } => f
+                .debug_struct("Direct")
+                .field("data", &format_args!("{REDACTED} {} bytes", data.len()))
+                .finish(),
+            UploadType::Stream => f.write_str("Stream"),
+            UploadType::Disk { path } => f.debug_struct("Disk").field("path", path).finish(),
+            UploadType::Redirect { target } => {
+                f.debug_struct("Redirect").field("target", target).finish()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::client_put::ClientPutMessage;
+    use crate::model::fields::Field;
+    use crate::model::message::{Message, MessagePayload};
+    use crate::model::message_type_identifier::ClientMessageType::ClientPut;
+    use crate::model::message_type_identifier::MessageType::Client;
+    use crate::model::persistence::Persistence;
+    use crate::model::priority_class::PriorityClass;
+    use crate::model::redaction::redact_value;
+    use crate::model::unique_identifier::UniqueIdentifier;
+    use crate::model::upload_type::UploadType;
+    use crate::model::uri::URI;
+    use crate::model::verbosity::Verbosity;
+
+    const INSERT_KEY: &str = "SSK@GB3wuHmtxN2wLc7g4y1ZVydkK6sOT-DuOsUo-eHK35w,\
+        c63EzO7uBEN0piUbHPkMcJYW7i7cOvG42CM3YDduXDs,AQECAAE/chat";
+    const REQUEST_KEY: &str = "SSK@GB3wuHmtxN2wLc7g4y1ZVydkK6sOT-DuOsUo-eHK35w,\
+        c63EzO7uBEN0piUbHPkMcJYW7i7cOvG42CM3YDduXDs,AQACAAE/chat";
+
+    #[test]
+    fn test_redact_value() {
+        assert_eq!(redact_value("URI", INSERT_KEY), "SSK@<redacted>");
+        assert_eq!(
+            redact_value("URI", &format!("freenet:U{}", &INSERT_KEY[1..])),
+            "freenet:USK@<redacted>"
+        );
+        assert_eq!(redact_value("URI", REQUEST_KEY), REQUEST_KEY);
+        assert_eq!(
+            redact_value("Targets", &format!("{REQUEST_KEY} {INSERT_KEY}")),
+            format!("{REQUEST_KEY} SSK@<redacted>")
+        );
+        assert_eq!(
+            redact_value("Targets", &format!("{REQUEST_KEY},KSK@channel-42")),
+            format!("{REQUEST_KEY},KSK@<redacted>")
+        );
+        assert_eq!(redact_value("URI", "KSK@channel-42"), "KSK@<redacted>");
+        assert_eq!(redact_value("ark.privURI", "anything"), "<redacted>");
+        assert_eq!(redact_value("Identifier", "abc"), "abc");
+    }
+
+    #[test]
+    fn test_client_put_debug() {
+        let put = ClientPutMessage {
+            uri: INSERT_KEY.try_into().unwrap(),
+            content_type: None,
+            identifier: UniqueIdentifier::new("Redaction test").unwrap(),
+            verbosity: Verbosity::default(),
+            max_retries: 0,
+            priority: PriorityClass::High,
+            get_only_chk: false,
+            dont_compress: true,
+            persistence: Persistence::Connection,
+            target_filename: None,
+            upload_from: UploadType::Direct {
+                data: b"secret".as_slice().into(),
+            },
+            is_binary_blob: false,
+            real_time: true,
+            compatibility_mode: None,
+            override_splitfile_crypto_key: None,
+        };
+
+        let formatted = format!("{put:?}");
+        assert!(!formatted.contains("GB3wuHmtxN2wLc7g4y1ZVydkK6sOT"));
+        assert!(!formatted.contains("c63EzO7uBEN0piUbHPkMcJYW7i7cOvG42CM3YDduXDs"));
+        assert!(!formatted.contains("115, 101, 99"));
+        assert!(formatted.contains("AQECAAE"));
+        assert!(formatted.contains("chat"));
+
+        let request_key = URI::try_from(REQUEST_KEY).unwrap();
+        assert!(format!("{request_key:?}").contains("GB3wuHmtxN2wLc7g4y1ZVydkK6sOT"));
+        let ksk = URI::try_from("KSK@channel-42").unwrap();
+        assert!(!format!("{ksk:?}").contains("channel-42"));
+    }
+
+    #[test]
+    fn test_message_formatting() {
+        let fields = vec![
+            Field::unvalidated("Identifier".into(), "abc".into()),
+            Field::unvalidated("URI".into(), INSERT_KEY.into()),
+        ];
+        let message =
+            Message::new(Client(ClientPut), fields.into(), None).with_payload(MessagePayload {
+                data: b"secret".as_slice().into(),
+                data_len_identifier: "DataLength".into(),
+            });
+
+        for formatted in [format!("{message:?}"), message.to_string()] {
+            assert!(!formatted.contains("GB3wuHmtxN2wLc7g4y1ZVydkK6sOT"));
+            assert!(!formatted.contains("115, 101, 99"));
+            assert!(formatted.contains("abc"));
+        }
+        assert_eq!(
+            message.to_string(),
+            "ClientPut { Identifier=abc, URI=SSK@<redacted> } with 6 bytes of data"
+        );
+
+        let traced = format!("{:?}", message.traced(true));
+        assert!(traced.contains(INSERT_KEY));
+        assert!(traced.contains("115, 101, 99"));
+    }
+}
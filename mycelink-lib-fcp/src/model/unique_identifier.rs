@@ -1,11 +1,14 @@
 use crate::decode_error::DecodeError;
 use crate::decode_error::DecodeError::ParseError;
+use crate::encode_error::EncodeError;
+use crate::model::fields::validate_value;
 use base64::Engine;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 const PREFIX: &str = "[Mycelink] ";
 const NAME_NONCE_SEPARATOR: &str = " - ";
+const IDENTIFIER_KEY: &str = "Identifier";
 
 const NONCE_LENGTH: usize = 32;
 const ENCODED_NONCE_LENGTH: usize = NONCE_LENGTH.div_ceil(3) * 4;
@@ -17,13 +20,23 @@ pub struct UniqueIdentifier {
 }
 
 impl UniqueIdentifier {
-    pub fn new(name: impl Into<Box<str>>) -> Self {
-        Self {
-            name: name.into(),
+    /// Fails if `name` cannot be sent as field value or contains the separator before the nonce
+    pub fn new(name: impl Into<Box<str>>) -> Result<Self, EncodeError> {
+        let name = name.into();
+        validate_value(IDENTIFIER_KEY, &name)?;
+        if name.contains(NAME_NONCE_SEPARATOR) {
+            return Err(EncodeError::InvalidValue {
+                key: IDENTIFIER_KEY.into(),
+                value: name,
+            });
+        }
+
+        Ok(Self {
+            name,
             nonce: base64::engine::general_purpose::STANDARD
                 .encode(rand::random::<[u8; NONCE_LENGTH]>())
                 .into(),
-        }
+        })
     }
 }
 
@@ -81,3 +94,26 @@ impl Display for UniqueIdentifier {
         write!(f, "{}", Box::<str>::from(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::encode_error::EncodeError;
+    use crate::model::unique_identifier::UniqueIdentifier;
+
+    #[test]
+    fn test_roundtrip() {
+        let identifier = UniqueIdentifier::new("Contact: Alice").unwrap();
+        let encoded: Box<str> = (&identifier).into();
+        assert_eq!(UniqueIdentifier::try_from(&*encoded).unwrap(), identifier);
+    }
+
+    #[test]
+    fn test_reject_injection() {
+        for name in ["Alice\nEndMessage", "Alice\r", "Alice - Bob"] {
+            assert!(matches!(
+                UniqueIdentifier::new(name),
+                Err(EncodeError::InvalidValue { .. })
+            ));
+        }
+    }
+}
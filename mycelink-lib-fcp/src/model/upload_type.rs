@@ -2,15 +2,24 @@ use crate::model::uri::URI;
 use std::path::Path;
 
 pub enum UploadType {
-    Direct { data: Box<[u8]> },
-    Disk { path: Box<Path> },
-    Redirect { target: URI },
+    Direct {
+        data: Box<[u8]>,
+    },
+    /// Sent directly like [UploadType::Direct], with the data read from the payload passed to
+    /// [crate::fcp_connector::FCPConnector::request_with_payload]
+    Stream,
+    Disk {
+        path: Box<Path>,
+    },
+    Redirect {
+        target: URI,
+    },
 }
 
 impl From<&UploadType> for &str {
     fn from(value: &UploadType) -> Self {
         match value {
-            UploadType::Direct { .. } => "direct",
+            UploadType::Direct { .. } | UploadType::Stream => "direct",
             UploadType::Disk { .. } => "disk",
             UploadType::Redirect { .. } => "redirect",
         }
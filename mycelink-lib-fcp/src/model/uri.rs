@@ -1,9 +1,306 @@
 use crate::decode_error::DecodeError;
+use base64::alphabet::Alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Eq, PartialEq, Debug)]
-pub struct URI {
-    uri: Box<str>,
+const FREENET_PREFIX: &str = "freenet:";
+
+/// Length of decoded routing and crypto keys
+const KEY_LENGTH: usize = 32;
+
+/// The base64 variant used in Hyphanet keys (`~` and `-` instead of `+` and `/`, no padding)
+pub const FREENET_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &match Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789~-") {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("Invalid base64 alphabet"),
+    },
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// [Debug] masks private keys, see [crate::model::redaction]
+#[derive(Clone, Eq, PartialEq)]
+pub enum URI {
+    /// Content hash key, [None] as `key` lets the node derive it from the data on insert
+    CHK {
+        key: Option<KeyMaterial>,
+        meta_strings: Vec<Box<str>>,
+    },
+    /// Signed subspace key, [None] as `key` lets the node generate a new keypair on insert
+    SSK {
+        key: Option<KeyMaterial>,
+        docname: Option<Box<str>>,
+        meta_strings: Vec<Box<str>>,
+    },
+    /// Updatable subspace key, an [URI::SSK] with the edition kept apart from the docname
+    USK {
+        key: KeyMaterial,
+        docname: Box<str>,
+        /// Negative editions ask the node to search for the latest one
+        edition: i64,
+        meta_strings: Vec<Box<str>>,
+    },
+    /// Keyword signed key
+    KSK {
+        keyword: Box<str>,
+        meta_strings: Vec<Box<str>>,
+    },
+}
+
+/// Routing key, crypto key and extra field of CHK, SSK and USK keys, all in [FREENET_BASE64]
+#[derive(Clone, Eq, PartialEq)]
+pub struct KeyMaterial {
+    pub routing_key: Box<str>,
+    pub crypto_key: Box<str>,
+    pub extra: Box<str>,
+}
+
+impl URI {
+    pub fn meta_strings(&self) -> &[Box<str>] {
+        match self {
+            URI::CHK { meta_strings, .. }
+            | URI::SSK { meta_strings, .. }
+            | URI::USK { meta_strings, .. }
+            | URI::KSK { meta_strings, .. } => meta_strings,
+        }
+    }
+
+    pub fn edition(&self) -> Option<i64> {
+        match self {
+            URI::USK { edition, .. } => Some(*edition),
+            _ => None,
+        }
+    }
+
+    /// The same USK at another edition, [None] if this is no USK
+    pub fn with_edition(&self, edition: i64) -> Option<URI> {
+        match self.clone() {
+            URI::USK {
+                key,
+                docname,
+                meta_strings,
+                ..
+            } => Some(URI::USK {
+                key,
+                docname,
+                edition,
+                meta_strings,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn next_edition(&self) -> Option<URI> {
+        self.with_edition(self.edition()?.checked_add(1)?)
+    }
+
+    /// A USK under the keypair of this SSK, [None] if this is no SSK with a key
+    pub fn usk_at(&self, docname: &str, edition: i64) -> Option<URI> {
+        match self {
+            URI::SSK { key: Some(key), .. } => Some(URI::USK {
+                key: key.clone(),
+                docname: docname.into(),
+                edition,
+                meta_strings: Vec::new(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The USK an SSK with a `docname-edition` docname is an edition of
+    pub fn to_usk(&self) -> Option<URI> {
+        let URI::SSK {
+            key: Some(key),
+            docname: Some(docname),
+            meta_strings,
+        } = self
+        else {
+            return None;
+        };
+
+        let (docname, edition) = docname.rsplit_once('-')?;
+        Some(URI::USK {
+            key: key.clone(),
+            docname: docname.into(),
+            edition: edition.parse().ok().filter(|e| *e >= 0)?,
+            meta_strings: meta_strings.clone(),
+        })
+    }
+
+    /// The SSK (with a `docname-edition` docname) a USK edition is stored under
+    pub fn to_ssk(&self) -> Option<URI> {
+        let URI::USK {
+            key,
+            docname,
+            edition,
+            meta_strings,
+        } = self
+        else {
+            return None;
+        };
+
+        Some(URI::SSK {
+            key: Some(key.clone()),
+            docname: Some(format!("{docname}-{edition}").into()),
+            meta_strings: meta_strings.clone(),
+        })
+    }
+
+    fn parse(value: &str) -> Result<Self, DecodeError> {
+        let uri = match value.get(..FREENET_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(FREENET_PREFIX) => {
+                &value[FREENET_PREFIX.len()..]
+            }
+            _ => value,
+        };
+
+        let (key_type, rest) = uri
+            .split_once('@')
+            .ok_or_else(|| invalid_uri(value, "it contains no '@'"))?;
+        let (key, mut segments) = match rest.split_once('/') {
+            None => (rest, Vec::new()),
+            Some((key, path)) => (key, path.split('/').map(Into::into).collect()),
+        };
+
+        match key_type.to_ascii_uppercase().as_str() {
+            "CHK" => Ok(URI::CHK {
+                key: KeyMaterial::parse_optional(key, value)?,
+                meta_strings: segments,
+            }),
+            "SSK" => {
+                let key = KeyMaterial::parse_optional(key, value)?;
+                let docname = (!segments.is_empty()).then(|| segments.remove(0));
+                Ok(URI::SSK {
+                    key,
+                    docname,
+                    meta_strings: segments,
+                })
+            }
+            "USK" => {
+                let key = KeyMaterial::parse_optional(key, value)?
+                    .ok_or_else(|| invalid_uri(value, "a USK needs a key"))?;
+                if segments.len() < 2 {
+                    return Err(invalid_uri(value, "a USK needs a docname and an edition"));
+                }
+                let docname = segments.remove(0);
+                let edition = segments
+                    .remove(0)
+                    .parse()
+                    .map_err(|_| invalid_uri(value, "the USK edition is no number"))?;
+                Ok(URI::USK {
+                    key,
+                    docname,
+                    edition,
+                    meta_strings: segments,
+                })
+            }
+            "KSK" => {
+                if key.is_empty() {
+                    return Err(invalid_uri(value, "a KSK needs a keyword"));
+                }
+                Ok(URI::KSK {
+                    keyword: key.into(),
+                    meta_strings: segments,
+                })
+            }
+            _ => Err(invalid_uri(value, "the key type is unknown")),
+        }
+    }
+}
+
+impl KeyMaterial {
+    /// Insert keys sign new content under the key and must stay private
+    pub fn is_insert_key(&self) -> bool {
+        is_insert_key(&self.to_string())
+    }
+
+    fn parse_optional(key: &str, uri: &str) -> Result<Option<Self>, DecodeError> {
+        if key.is_empty() {
+            return Ok(None);
+        }
+
+        let [routing_key, crypto_key, extra] = key.split(',').collect::<Vec<_>>()[..] else {
+            return Err(invalid_uri(
+                uri,
+                "the key does not consist of routing key, crypto key and extra field",
+            ));
+        };
+
+        for (part, expected_len) in [
+            (routing_key, Some(KEY_LENGTH)),
+            (crypto_key, Some(KEY_LENGTH)),
+            (extra, None),
+        ] {
+            let decoded = FREENET_BASE64
+                .decode(part)
+                .map_err(|_| invalid_uri(uri, "the key is not valid base64"))?;
+            if decoded.is_empty() || expected_len.is_some_and(|len| len != decoded.len()) {
+                return Err(invalid_uri(uri, "the key has the wrong length"));
+            }
+        }
+
+        Ok(Some(Self {
+            routing_key: routing_key.into(),
+            crypto_key: crypto_key.into(),
+            extra: extra.into(),
+        }))
+    }
+}
+
+/// Whether `key` (routing key, crypto key and extra field) is the private key of an SSK
+pub(crate) fn is_insert_key(key: &str) -> bool {
+    key.split(',')
+        .nth(2)
+        .and_then(|extra| FREENET_BASE64.decode(extra).ok())
+        .is_some_and(|extra| extra.get(1) == Some(&1))
+}
+
+fn invalid_uri(uri: &str, reason: &str) -> DecodeError {
+    DecodeError::InvalidURI(format!("'{uri}' is no valid URI as {reason}").into())
+}
+
+impl Display for KeyMaterial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.routing_key, self.crypto_key, self.extra)
+    }
+}
+
+impl Display for URI {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            URI::CHK { key, .. } => {
+                write!(f, "CHK@")?;
+                if let Some(key) = key {
+                    write!(f, "{key}")?;
+                }
+            }
+            URI::SSK { key, docname, .. } => {
+                write!(f, "SSK@")?;
+                if let Some(key) = key {
+                    write!(f, "{key}")?;
+                }
+                if let Some(docname) = docname {
+                    write!(f, "/{docname}")?;
+                }
+            }
+            URI::USK {
+                key,
+                docname,
+                edition,
+                ..
+            } => write!(f, "USK@{key}/{docname}/{edition}")?,
+            URI::KSK { keyword, .. } => write!(f, "KSK@{keyword}")?,
+        }
+
+        for meta_string in self.meta_strings() {
+            write!(f, "/{meta_string}")?;
+        }
+
+        Ok(())
+    }
 }
 
 impl FromStr for URI {
@@ -18,12 +315,103 @@ impl TryFrom<&str> for URI {
     type Error = DecodeError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Ok(Self { uri: value.into() })
+        Self::parse(value)
     }
 }
 
 impl From<&URI> for Box<str> {
     fn from(value: &URI) -> Self {
-        value.uri.clone()
+        value.to_string().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decode_error::DecodeError;
+    use crate::model::uri::URI;
+
+    const KEY: &str = "GB3wuHmtxN2wLc7g4y1ZVydkK6sOT-DuOsUo-eHK35w,\
+        c63EzO7uBEN0piUbHPkMcJYW7i7cOvG42CM3YDduXDs,AQACAAE";
+
+    fn parse(uri: &str) -> URI {
+        uri.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for uri in [
+            "CHK@".to_string(),
+            format!("CHK@{KEY}"),
+            format!("CHK@{KEY}/file.txt"),
+            "SSK@".to_string(),
+            format!("SSK@{KEY}/"),
+            format!("SSK@{KEY}/site-3/index.html"),
+            format!("USK@{KEY}/site/-1"),
+            format!("USK@{KEY}/site/5/index.html"),
+            "KSK@some keyword".to_string(),
+        ] {
+            assert_eq!(parse(&uri).to_string(), uri);
+        }
+    }
+
+    #[test]
+    fn test_parse_usk() {
+        let uri = parse(&format!("freenet:USK@{KEY}/site/5/a/b"));
+
+        let URI::USK {
+            key,
+            docname,
+            edition,
+            meta_strings,
+        } = uri
+        else {
+            panic!("Expected a USK")
+        };
+        assert_eq!(&*key.extra, "AQACAAE");
+        assert_eq!(&*docname, "site");
+        assert_eq!(edition, 5);
+        assert_eq!(meta_strings, vec!["a".into(), "b".into()]);
+    }
+
+    #[test]
+    fn test_reject_malformed() {
+        for uri in [
+            "no key".to_string(),
+            "ABC@key".to_string(),
+            "KSK@".to_string(),
+            "CHK@abc,def,AAMC--8".to_string(),
+            format!("CHK@{KEY},more"),
+            format!("SSK@{}", KEY.replace('w', "+")),
+            format!("USK@{KEY}/site"),
+            format!("USK@{KEY}/site/latest"),
+            "USK@/site/0".to_string(),
+        ] {
+            let res = URI::try_from(uri.as_str());
+            assert!(matches!(res, Err(DecodeError::InvalidURI(_))), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_ssk_usk_conversion() {
+        let ssk = parse(&format!("SSK@{KEY}/site-5/index.html"));
+        let usk = parse(&format!("USK@{KEY}/site/5/index.html"));
+
+        assert_eq!(ssk.to_usk(), Some(usk.clone()));
+        assert_eq!(usk.to_ssk(), Some(ssk));
+        assert_eq!(parse(&format!("SSK@{KEY}/site")).to_usk(), None);
+    }
+
+    #[test]
+    fn test_editions() {
+        let usk = parse(&format!("USK@{KEY}/site/5"));
+
+        assert_eq!(usk.edition(), Some(5));
+        assert_eq!(usk.next_edition().unwrap().edition(), Some(6));
+        assert_eq!(usk.with_edition(-1).unwrap().edition(), Some(-1));
+        assert_eq!(parse("KSK@keyword").next_edition(), None);
+        assert_eq!(
+            parse(&format!("SSK@{KEY}/")).usk_at("feed", 0),
+            Some(parse(&format!("USK@{KEY}/feed/0")))
+        );
     }
 }
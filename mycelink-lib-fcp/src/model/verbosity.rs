@@ -1,4 +1,4 @@
-type Bitmask = u8;
+type Bitmask = u16;
 
 const SIMPLE_PROGRESS: Bitmask = 1;
 const SENDING_TO_NETWORK: Bitmask = 1 << 1;
@@ -7,6 +7,8 @@ const EXPECTED_HASH: Bitmask = 1 << 3;
 // Skip one bit
 const EXPECTED_MIME: Bitmask = 1 << 5;
 const EXPECTED_DATA_LENGTH: Bitmask = 1 << 6;
+// Skip two bits
+const COMPRESSION: Bitmask = 1 << 9;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
 pub struct Verbosity {
@@ -16,6 +18,8 @@ pub struct Verbosity {
     pub expected_hashes: bool,
     pub expected_mime: bool,
     pub expected_data_length: bool,
+    /// `StartedCompression` and `FinishedCompression` events of inserts
+    pub compression: bool,
 }
 
 impl Verbosity {
@@ -40,9 +44,46 @@ impl Verbosity {
         if self.expected_data_length {
             res |= EXPECTED_DATA_LENGTH;
         }
+        if self.compression {
+            res |= COMPRESSION;
+        }
 
         res
     }
+
+    pub fn from_bitmask(bitmask: Bitmask) -> Self {
+        Self {
+            simple_progress: bitmask & SIMPLE_PROGRESS != 0,
+            sending_to_network: bitmask & SENDING_TO_NETWORK != 0,
+            compatibility_mode: bitmask & COMPATIBILITY_MODE != 0,
+            expected_hashes: bitmask & EXPECTED_HASH != 0,
+            expected_mime: bitmask & EXPECTED_MIME != 0,
+            expected_data_length: bitmask & EXPECTED_DATA_LENGTH != 0,
+            compression: bitmask & COMPRESSION != 0,
+        }
+    }
+
+    /// Parses a bitmask as sent by the node, which uses `-1` for every event and may set bits this
+    /// library doesn't know, anything that is not a number asks for no events
+    pub fn parse_lenient(value: &str) -> Self {
+        match value.trim().parse::<i64>() {
+            Ok(bitmask) => Self::from_bitmask(bitmask as Bitmask),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Asks for every progress event the node knows
+    pub fn all() -> Self {
+        Self {
+            simple_progress: true,
+            sending_to_network: true,
+            compatibility_mode: true,
+            expected_hashes: true,
+            expected_mime: true,
+            expected_data_length: true,
+            compression: true,
+        }
+    }
 }
 
 impl From<&Verbosity> for Box<str> {
@@ -1,3 +1,4 @@
+use mycelink_lib_fcp::message_reader::MessageReader;
 use mycelink_lib_fcp::messages::all_data::AllDataMessage;
 use mycelink_lib_fcp::messages::client_get::ClientGetMessage;
 use mycelink_lib_fcp::messages::client_hello::{ClientHelloMessage, EXPECTED_VERSION};
@@ -8,6 +9,7 @@ use mycelink_lib_fcp::messages::node_hello::NodeHelloMessage;
 use mycelink_lib_fcp::messages::put_successful::PutSuccessfulMessage;
 use mycelink_lib_fcp::messages::ssk_keypair::SSKKeypairMessage;
 use mycelink_lib_fcp::messages::uri_generated::UriGeneratedMessage;
+use mycelink_lib_fcp::mock_node::MockNode;
 use mycelink_lib_fcp::model::fcp_version::FCPVersion;
 use mycelink_lib_fcp::model::message::{FCPEncodable, Message};
 use mycelink_lib_fcp::model::persistence::Persistence;
@@ -16,15 +18,13 @@ use mycelink_lib_fcp::model::return_type::ReturnType;
 use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
 use mycelink_lib_fcp::model::upload_type::UploadType;
 use mycelink_lib_fcp::model::verbosity::Verbosity;
-use mycelink_lib_fcp::peekable_reader::PeekableReader;
 use rand::RngCore;
-use std::ops::Deref;
 use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
 
 #[tokio::test]
 async fn integration_generate_put_get_ssk() {
-    let mut stream = TcpStream::connect("localhost:9481").await.unwrap();
+    let node = MockNode::start().await.unwrap();
+    let mut stream = node.connect().await.unwrap();
 
     // Handshake
     // #############################################################################################
@@ -34,14 +34,14 @@ async fn integration_generate_put_get_ssk() {
         name: "Integration_test_generate_put_get_ssk".into(),
     };
 
-    let encoded = client_hello.to_message().encode();
+    let encoded = client_hello.to_message().encode().unwrap();
 
     stream.write_all(encoded.as_slice()).await.unwrap();
 
     let (rx, mut tx) = stream.split();
 
-    let mut peekable_reader = PeekableReader::new(rx);
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let mut message_reader = MessageReader::new(rx);
+    let message = Message::decode(&mut message_reader).await.unwrap();
 
     let node_hello: NodeHelloMessage = message.try_into().unwrap();
 
@@ -50,30 +50,30 @@ async fn integration_generate_put_get_ssk() {
         NodeHelloMessage {
             fcp_version: FCPVersion::V2_0,
             node: "Fred".into(),
-            connection_identifier: node_hello.connection_identifier.clone(),
+            ..node_hello.clone()
         }
     );
 
     // Generate
     // #############################################################################################
-    let generate_ssk_identifier = UniqueIdentifier::new("Generate Test");
+    let generate_ssk_identifier = UniqueIdentifier::new("Generate Test").unwrap();
     let generate_ssk = GenerateSSKMessage {
         identifier: generate_ssk_identifier,
     };
-    let encoded = generate_ssk.to_message().encode();
+    let encoded = generate_ssk.to_message().encode().unwrap();
 
     tx.write_all(encoded.as_slice()).await.unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let ssk_keypair: SSKKeypairMessage = message.try_into().unwrap();
 
     // Put
     // #############################################################################################
-    let client_put_identifier = UniqueIdentifier::new("Put Test");
+    let client_put_identifier = UniqueIdentifier::new("Put Test").unwrap();
     let mut payload_bytes = [0; 128];
     rand::thread_rng().fill_bytes(&mut payload_bytes);
     let client_put = ClientPutMessage {
-        uri: ssk_keypair.insert_uri.deref().try_into().unwrap(),
+        uri: ssk_keypair.insert_uri.clone(),
         content_type: None,
         identifier: client_put_identifier.clone(),
         verbosity: Verbosity {
@@ -83,6 +83,7 @@ async fn integration_generate_put_get_ssk() {
             expected_hashes: false,
             expected_mime: false,
             expected_data_length: false,
+            compression: false,
         },
         max_retries: 0,
         priority: PriorityClass::Medium,
@@ -95,31 +96,30 @@ async fn integration_generate_put_get_ssk() {
         },
         is_binary_blob: false,
         real_time: true,
+        compatibility_mode: None,
+        override_splitfile_crypto_key: None,
     };
 
-    let encoded = client_put.to_message().encode();
+    let encoded = client_put.to_message().encode().unwrap();
 
     tx.write_all(encoded.as_slice()).await.unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let generated_uri_message: UriGeneratedMessage = message.try_into().unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let put_sucessful: PutSuccessfulMessage = message.try_into().unwrap();
 
     assert_eq!(generated_uri_message.uri, put_sucessful.uri);
-    assert_eq!(
-        generated_uri_message.uri,
-        ssk_keypair.request_uri.deref().try_into().unwrap()
-    );
+    assert_eq!(generated_uri_message.uri, ssk_keypair.request_uri.clone());
     assert_eq!(put_sucessful.identifier, client_put_identifier);
 
     // Get
     // #############################################################################################
-    let client_get_identifier = UniqueIdentifier::new("Get Message");
+    let client_get_identifier = UniqueIdentifier::new("Get Message").unwrap();
     let client_get_message = ClientGetMessage {
         identifier: client_get_identifier.clone(),
-        uri: ssk_keypair.request_uri.deref().try_into().unwrap(),
+        uri: ssk_keypair.request_uri.clone(),
         verbosity: Verbosity {
             simple_progress: false,
             sending_to_network: false,
@@ -127,6 +127,7 @@ async fn integration_generate_put_get_ssk() {
             expected_hashes: false,
             expected_mime: false,
             expected_data_length: false,
+            compression: false,
         },
         return_type: ReturnType::Direct,
         max_size: None,
@@ -139,13 +140,13 @@ async fn integration_generate_put_get_ssk() {
         real_time: false,
     };
 
-    let encoded = client_get_message.to_message().encode();
+    let encoded = client_get_message.to_message().encode().unwrap();
     tx.write_all(encoded.as_slice()).await.unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let _data_found: DataFoundMessage = message.try_into().unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let all_data_message: AllDataMessage = message.try_into().unwrap();
 
     assert_eq!(
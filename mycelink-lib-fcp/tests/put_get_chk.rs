@@ -1,3 +1,4 @@
+use mycelink_lib_fcp::message_reader::MessageReader;
 use mycelink_lib_fcp::messages::all_data::AllDataMessage;
 use mycelink_lib_fcp::messages::client_get::ClientGetMessage;
 use mycelink_lib_fcp::messages::client_hello::{ClientHelloMessage, EXPECTED_VERSION};
@@ -6,6 +7,7 @@ use mycelink_lib_fcp::messages::data_found::DataFoundMessage;
 use mycelink_lib_fcp::messages::node_hello::NodeHelloMessage;
 use mycelink_lib_fcp::messages::put_successful::PutSuccessfulMessage;
 use mycelink_lib_fcp::messages::uri_generated::UriGeneratedMessage;
+use mycelink_lib_fcp::mock_node::MockNode;
 use mycelink_lib_fcp::model::fcp_version::FCPVersion;
 use mycelink_lib_fcp::model::message::{FCPEncodable, Message};
 use mycelink_lib_fcp::model::persistence::Persistence;
@@ -14,14 +16,13 @@ use mycelink_lib_fcp::model::return_type::ReturnType;
 use mycelink_lib_fcp::model::unique_identifier::UniqueIdentifier;
 use mycelink_lib_fcp::model::upload_type::UploadType;
 use mycelink_lib_fcp::model::verbosity::Verbosity;
-use mycelink_lib_fcp::peekable_reader::PeekableReader;
 use rand::RngCore;
 use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
 
 #[tokio::test]
 async fn integration_put_get_chk() {
-    let mut stream = TcpStream::connect("localhost:9481").await.unwrap();
+    let node = MockNode::start().await.unwrap();
+    let mut stream = node.connect().await.unwrap();
 
     // Handshake
     // #############################################################################################
@@ -31,14 +32,14 @@ async fn integration_put_get_chk() {
         name: "Integration_test_put_get_chk".into(),
     };
 
-    let encoded = client_hello.to_message().encode();
+    let encoded = client_hello.to_message().encode().unwrap();
 
     stream.write_all(encoded.as_slice()).await.unwrap();
 
     let (rx, mut tx) = stream.split();
 
-    let mut peekable_reader = PeekableReader::new(rx);
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let mut message_reader = MessageReader::new(rx);
+    let message = Message::decode(&mut message_reader).await.unwrap();
 
     let node_hello: NodeHelloMessage = message.try_into().unwrap();
 
@@ -47,13 +48,13 @@ async fn integration_put_get_chk() {
         NodeHelloMessage {
             fcp_version: FCPVersion::V2_0,
             node: "Fred".into(),
-            connection_identifier: node_hello.connection_identifier.clone(),
+            ..node_hello.clone()
         }
     );
 
     // Put
     // #############################################################################################
-    let client_put_identifier = UniqueIdentifier::new("Put Test");
+    let client_put_identifier = UniqueIdentifier::new("Put Test").unwrap();
     let mut payload_bytes = [0; 128];
     rand::thread_rng().fill_bytes(&mut payload_bytes);
     let client_put = ClientPutMessage {
@@ -67,6 +68,7 @@ async fn integration_put_get_chk() {
             expected_hashes: false,
             expected_mime: false,
             expected_data_length: false,
+            compression: false,
         },
         max_retries: 0,
         priority: PriorityClass::Medium,
@@ -79,16 +81,18 @@ async fn integration_put_get_chk() {
         },
         is_binary_blob: false,
         real_time: true,
+        compatibility_mode: None,
+        override_splitfile_crypto_key: None,
     };
 
-    let encoded = client_put.to_message().encode();
+    let encoded = client_put.to_message().encode().unwrap();
 
     tx.write_all(encoded.as_slice()).await.unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let generated_uri_message: UriGeneratedMessage = message.try_into().unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let put_sucessful: PutSuccessfulMessage = message.try_into().unwrap();
 
     assert_eq!(generated_uri_message.uri, put_sucessful.uri);
@@ -96,7 +100,7 @@ async fn integration_put_get_chk() {
 
     // Get
     // #############################################################################################
-    let client_get_identifier = UniqueIdentifier::new("Get Message");
+    let client_get_identifier = UniqueIdentifier::new("Get Message").unwrap();
     let client_get_message = ClientGetMessage {
         identifier: client_get_identifier.clone(),
         uri: generated_uri_message.uri,
@@ -107,6 +111,7 @@ async fn integration_put_get_chk() {
             expected_hashes: false,
             expected_mime: false,
             expected_data_length: false,
+            compression: false,
         },
         return_type: ReturnType::Direct,
         max_size: None,
@@ -119,13 +124,13 @@ async fn integration_put_get_chk() {
         real_time: false,
     };
 
-    let encoded = client_get_message.to_message().encode();
+    let encoded = client_get_message.to_message().encode().unwrap();
     tx.write_all(encoded.as_slice()).await.unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let _data_found: DataFoundMessage = message.try_into().unwrap();
 
-    let message = Message::decode(&mut peekable_reader).await.unwrap();
+    let message = Message::decode(&mut message_reader).await.unwrap();
     let all_data_message: AllDataMessage = message.try_into().unwrap();
 
     assert_eq!(